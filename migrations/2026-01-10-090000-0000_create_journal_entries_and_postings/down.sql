@@ -0,0 +1,4 @@
+DROP TRIGGER IF EXISTS postings_balanced ON postings;
+DROP FUNCTION IF EXISTS check_journal_entry_balanced();
+DROP TABLE postings;
+DROP TABLE journal_entries;
@@ -0,0 +1,66 @@
+-- Double-entry journal: every transaction produces one journal entry whose
+-- postings always sum to zero. A positive posting amount credits the account,
+-- a negative one debits it. account_id is NULL for the external side of money
+-- entering (credit) or leaving (debit) the ledger.
+CREATE TABLE journal_entries (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BIGINT NOT NULL REFERENCES transactions(id),
+    description VARCHAR(500),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_journal_entries_transaction_id ON journal_entries(transaction_id);
+
+CREATE TABLE postings (
+    id BIGSERIAL PRIMARY KEY,
+    journal_entry_id BIGINT NOT NULL REFERENCES journal_entries(id),
+    account_id BIGINT REFERENCES accounts(id),
+    amount BIGINT NOT NULL CHECK (amount <> 0),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_postings_journal_entry_id ON postings(journal_entry_id);
+CREATE INDEX idx_postings_account_id ON postings(account_id);
+
+-- Reject any journal entry whose postings do not sum to zero. Deferred so that
+-- all legs of an entry can be written before the check runs.
+CREATE OR REPLACE FUNCTION check_journal_entry_balanced() RETURNS trigger AS $$
+BEGIN
+    IF (SELECT SUM(amount) FROM postings WHERE journal_entry_id = NEW.journal_entry_id) <> 0 THEN
+        RAISE EXCEPTION 'journal entry % is not balanced', NEW.journal_entry_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER postings_balanced
+    AFTER INSERT OR UPDATE ON postings
+    DEFERRABLE INITIALLY DEFERRED
+    FOR EACH ROW EXECUTE PROCEDURE check_journal_entry_balanced();
+
+-- Backfill journal entries for transactions recorded before the journal existed
+INSERT INTO journal_entries (transaction_id, description, created_at)
+SELECT id, description, created_at
+FROM transactions
+WHERE status = 'completed';
+
+INSERT INTO postings (journal_entry_id, account_id, amount, created_at)
+SELECT je.id, t.from_account_id, -t.amount, t.created_at
+FROM journal_entries je
+JOIN transactions t ON t.id = je.transaction_id
+WHERE t.tx_type IN ('debit', 'transfer')
+UNION ALL
+SELECT je.id, t.to_account_id, t.amount, t.created_at
+FROM journal_entries je
+JOIN transactions t ON t.id = je.transaction_id
+WHERE t.tx_type IN ('credit', 'transfer')
+UNION ALL
+SELECT je.id, NULL, t.amount, t.created_at
+FROM journal_entries je
+JOIN transactions t ON t.id = je.transaction_id
+WHERE t.tx_type = 'debit'
+UNION ALL
+SELECT je.id, NULL, -t.amount, t.created_at
+FROM journal_entries je
+JOIN transactions t ON t.id = je.transaction_id
+WHERE t.tx_type = 'credit';
@@ -0,0 +1,15 @@
+-- Funds reserved by an authorized (not yet captured) transaction. Active holds
+-- reduce an account's available balance but not its ledger balance.
+CREATE TABLE holds (
+    id BIGSERIAL PRIMARY KEY,
+    account_id BIGINT NOT NULL REFERENCES accounts(id),
+    transaction_id BIGINT NOT NULL REFERENCES transactions(id),
+    amount BIGINT NOT NULL CHECK (amount > 0),
+    expires_at TIMESTAMP,
+    released_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_holds_account_id ON holds(account_id) WHERE released_at IS NULL;
+CREATE INDEX idx_holds_transaction_id ON holds(transaction_id);
+CREATE INDEX idx_holds_expires_at ON holds(expires_at) WHERE released_at IS NULL;
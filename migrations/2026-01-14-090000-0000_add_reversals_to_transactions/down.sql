@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_transactions_reversal_of_id;
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS chk_transactions_refunded_amount;
+ALTER TABLE transactions DROP COLUMN refunded_amount;
+ALTER TABLE transactions DROP COLUMN reversal_of_id;
@@ -0,0 +1,10 @@
+-- Reversals/refunds are ordinary transactions linked to the one they compensate.
+-- refunded_amount tracks how much of the original has been given back so far.
+ALTER TABLE transactions
+ADD COLUMN reversal_of_id BIGINT REFERENCES transactions(id),
+ADD COLUMN refunded_amount BIGINT NOT NULL DEFAULT 0;
+
+ALTER TABLE transactions
+ADD CONSTRAINT chk_transactions_refunded_amount CHECK (refunded_amount >= 0 AND refunded_amount <= amount);
+
+CREATE INDEX idx_transactions_reversal_of_id ON transactions(reversal_of_id);
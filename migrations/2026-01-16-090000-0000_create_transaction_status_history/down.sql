@@ -0,0 +1,11 @@
+DROP TABLE transaction_status_history;
+
+-- Postgres cannot drop a single enum value, so rebuild the type without 'reversed'
+UPDATE transactions SET status = 'completed' WHERE status = 'reversed';
+ALTER TYPE transaction_status RENAME TO transaction_status_old;
+CREATE TYPE transaction_status AS ENUM ('pending', 'completed', 'failed', 'cancelled');
+ALTER TABLE transactions ALTER COLUMN status DROP DEFAULT;
+ALTER TABLE transactions
+    ALTER COLUMN status TYPE transaction_status USING status::text::transaction_status;
+ALTER TABLE transactions ALTER COLUMN status SET DEFAULT 'pending';
+DROP TYPE transaction_status_old;
@@ -0,0 +1,20 @@
+-- Fully refunded transactions end in the 'reversed' state
+ALTER TYPE transaction_status ADD VALUE IF NOT EXISTS 'reversed';
+
+-- Every status transition of a transaction, including its initial status
+CREATE TABLE transaction_status_history (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BIGINT NOT NULL REFERENCES transactions(id),
+    from_status transaction_status, -- NULL for the initial status
+    to_status transaction_status NOT NULL,
+    actor_api_key_id BIGINT REFERENCES api_keys(id), -- NULL for system transitions
+    reason VARCHAR(500),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_transaction_status_history_transaction_id ON transaction_status_history(transaction_id);
+
+-- Seed the history of existing transactions with their current status
+INSERT INTO transaction_status_history (transaction_id, from_status, to_status, created_at)
+SELECT id, NULL, status, created_at
+FROM transactions;
@@ -0,0 +1,16 @@
+-- A group of transactions committed all-or-nothing under a single parent id
+CREATE TABLE transaction_batches (
+    id BIGSERIAL PRIMARY KEY,
+    description VARCHAR(500),
+    idempotency_key VARCHAR(255),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX idx_transaction_batches_unique_idempotency ON transaction_batches(idempotency_key)
+    WHERE idempotency_key IS NOT NULL;
+
+ALTER TABLE transactions
+    ADD COLUMN batch_id BIGINT REFERENCES transaction_batches(id);
+
+CREATE INDEX idx_transactions_batch_id ON transactions(batch_id)
+    WHERE batch_id IS NOT NULL;
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_transactions_scheduled;
+ALTER TABLE transactions DROP COLUMN IF EXISTS execute_at;
@@ -0,0 +1,6 @@
+-- Scheduled transactions stay pending until execute_at, then the scheduler runs them
+ALTER TABLE transactions
+    ADD COLUMN execute_at TIMESTAMP;
+
+CREATE INDEX idx_transactions_scheduled ON transactions(execute_at)
+    WHERE status = 'pending' AND execute_at IS NOT NULL;
@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS recurring_transfer_runs;
+DROP TABLE IF EXISTS recurring_transfers;
+DROP TYPE IF EXISTS recurring_run_status;
+DROP TYPE IF EXISTS catch_up_policy;
+DROP TYPE IF EXISTS recurring_transfer_status;
+DROP TYPE IF EXISTS recurrence_interval;
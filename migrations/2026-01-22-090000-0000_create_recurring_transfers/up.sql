@@ -0,0 +1,46 @@
+CREATE TYPE recurrence_interval AS ENUM ('hour', 'day', 'week', 'month');
+CREATE TYPE recurring_transfer_status AS ENUM ('active', 'paused', 'completed', 'cancelled');
+CREATE TYPE catch_up_policy AS ENUM ('run_all', 'run_latest', 'skip');
+CREATE TYPE recurring_run_status AS ENUM ('succeeded', 'failed', 'skipped');
+
+-- Standing orders: a transfer repeated on an interval or cron rule
+CREATE TABLE recurring_transfers (
+    id BIGSERIAL PRIMARY KEY,
+    from_account_id BIGINT NOT NULL REFERENCES accounts(id),
+    to_account_id BIGINT NOT NULL REFERENCES accounts(id),
+    amount BIGINT NOT NULL CHECK (amount > 0),
+    description VARCHAR(500),
+    interval_unit recurrence_interval,
+    interval_count INTEGER NOT NULL DEFAULT 1 CHECK (interval_count > 0),
+    cron_expression VARCHAR(255),
+    start_at TIMESTAMP NOT NULL,
+    end_at TIMESTAMP,
+    max_occurrences INTEGER CHECK (max_occurrences > 0),
+    occurrences INTEGER NOT NULL DEFAULT 0,
+    next_run_at TIMESTAMP, -- NULL once the schedule has finished
+    status recurring_transfer_status NOT NULL DEFAULT 'active',
+    catch_up_policy catch_up_policy NOT NULL DEFAULT 'run_latest',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- Exactly one kind of rule
+    CONSTRAINT chk_recurring_transfers_rule CHECK ((interval_unit IS NULL) <> (cron_expression IS NULL))
+);
+
+SELECT diesel_manage_updated_at('recurring_transfers');
+
+CREATE INDEX idx_recurring_transfers_from_account ON recurring_transfers(from_account_id);
+CREATE INDEX idx_recurring_transfers_due ON recurring_transfers(next_run_at)
+    WHERE status = 'active';
+
+-- Outcome of every occurrence of a recurring transfer
+CREATE TABLE recurring_transfer_runs (
+    id BIGSERIAL PRIMARY KEY,
+    recurring_transfer_id BIGINT NOT NULL REFERENCES recurring_transfers(id),
+    scheduled_for TIMESTAMP NOT NULL,
+    status recurring_run_status NOT NULL,
+    transaction_id BIGINT REFERENCES transactions(id),
+    error VARCHAR(500),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_recurring_transfer_runs_recurring_transfer_id ON recurring_transfer_runs(recurring_transfer_id);
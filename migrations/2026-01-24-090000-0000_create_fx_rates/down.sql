@@ -0,0 +1,5 @@
+ALTER TABLE transactions
+    DROP COLUMN IF EXISTS converted_currency,
+    DROP COLUMN IF EXISTS converted_amount,
+    DROP COLUMN IF EXISTS fx_rate;
+DROP TABLE IF EXISTS fx_rates;
@@ -0,0 +1,22 @@
+-- Exchange rates managed by admins. rate is the number of quote minor units per
+-- base minor unit, scaled by 10^8 (1.0845 is stored as 108450000).
+CREATE TABLE fx_rates (
+    id BIGSERIAL PRIMARY KEY,
+    base_currency VARCHAR(3) NOT NULL,
+    quote_currency VARCHAR(3) NOT NULL,
+    rate BIGINT NOT NULL CHECK (rate > 0),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK (base_currency <> quote_currency)
+);
+
+CREATE UNIQUE INDEX idx_fx_rates_pair ON fx_rates(base_currency, quote_currency);
+
+SELECT diesel_manage_updated_at('fx_rates');
+
+-- Cross-currency transfers record the rate applied and the amount credited
+-- to the destination account in its own currency
+ALTER TABLE transactions
+    ADD COLUMN fx_rate BIGINT,
+    ADD COLUMN converted_amount BIGINT,
+    ADD COLUMN converted_currency VARCHAR(3);
@@ -0,0 +1,26 @@
+CREATE OR REPLACE FUNCTION check_journal_entry_balanced() RETURNS trigger AS $$
+BEGIN
+    IF (SELECT SUM(amount) FROM postings WHERE journal_entry_id = NEW.journal_entry_id) <> 0 THEN
+        RAISE EXCEPTION 'journal entry % is not balanced', NEW.journal_entry_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE postings DROP COLUMN IF EXISTS currency;
+
+DROP INDEX IF EXISTS idx_holds_account_id;
+ALTER TABLE holds DROP COLUMN IF EXISTS currency;
+CREATE INDEX idx_holds_account_id ON holds(account_id) WHERE released_at IS NULL;
+
+ALTER TABLE transactions DROP COLUMN IF EXISTS currency;
+
+-- Only the default currency balance survives the rollback
+ALTER TABLE accounts ADD COLUMN balance BIGINT NOT NULL DEFAULT 0;
+
+UPDATE accounts a
+SET balance = b.balance
+FROM account_balances b
+WHERE b.account_id = a.id AND b.currency = a.currency;
+
+DROP TABLE IF EXISTS account_balances;
@@ -0,0 +1,78 @@
+-- An account holds one balance per currency. accounts.currency remains the
+-- account's default currency, whose wallet is opened with the account.
+CREATE TABLE account_balances (
+    id BIGSERIAL PRIMARY KEY,
+    account_id BIGINT NOT NULL REFERENCES accounts(id),
+    currency VARCHAR(3) NOT NULL,
+    balance BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (account_id, currency)
+);
+
+SELECT diesel_manage_updated_at('account_balances');
+
+INSERT INTO account_balances (account_id, currency, balance, created_at)
+SELECT id, currency, balance, created_at
+FROM accounts;
+
+ALTER TABLE accounts DROP COLUMN balance;
+
+-- Transactions name the currency they move. Existing ones moved the currency
+-- of the account they debited (or credited).
+ALTER TABLE transactions ADD COLUMN currency VARCHAR(3);
+
+UPDATE transactions t
+SET currency = a.currency
+FROM accounts a
+WHERE a.id = COALESCE(t.from_account_id, t.to_account_id);
+
+ALTER TABLE transactions ALTER COLUMN currency SET NOT NULL;
+
+-- Holds reserve funds in the wallet of the transaction's currency
+ALTER TABLE holds ADD COLUMN currency VARCHAR(3);
+
+UPDATE holds h
+SET currency = t.currency
+FROM transactions t
+WHERE t.id = h.transaction_id;
+
+ALTER TABLE holds ALTER COLUMN currency SET NOT NULL;
+
+DROP INDEX IF EXISTS idx_holds_account_id;
+CREATE INDEX idx_holds_account_id ON holds(account_id, currency) WHERE released_at IS NULL;
+
+-- Every posting is in one currency. The converted side of a cross-currency
+-- transfer (the destination leg and the external leg funding it) is in
+-- converted_currency, everything else in the transaction's currency.
+ALTER TABLE postings ADD COLUMN currency VARCHAR(3);
+
+UPDATE postings p
+SET currency = CASE
+    WHEN t.converted_currency IS NOT NULL
+        AND (p.account_id = t.to_account_id
+            OR (p.account_id IS NULL AND p.amount = -t.converted_amount))
+    THEN t.converted_currency
+    ELSE t.currency
+END
+FROM journal_entries je
+JOIN transactions t ON t.id = je.transaction_id
+WHERE je.id = p.journal_entry_id;
+
+ALTER TABLE postings ALTER COLUMN currency SET NOT NULL;
+
+-- Journal entries must now balance within each currency
+CREATE OR REPLACE FUNCTION check_journal_entry_balanced() RETURNS trigger AS $$
+BEGIN
+    IF EXISTS (
+        SELECT 1
+        FROM postings
+        WHERE journal_entry_id = NEW.journal_entry_id
+        GROUP BY currency
+        HAVING SUM(amount) <> 0
+    ) THEN
+        RAISE EXCEPTION 'journal entry % is not balanced', NEW.journal_entry_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
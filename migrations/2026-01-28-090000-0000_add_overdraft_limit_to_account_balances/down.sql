@@ -0,0 +1 @@
+ALTER TABLE account_balances DROP COLUMN IF EXISTS overdraft_limit;
@@ -0,0 +1,3 @@
+-- How far below zero a wallet may be debited, in minor units. Zero means no overdraft.
+ALTER TABLE account_balances
+ADD COLUMN overdraft_limit BIGINT NOT NULL DEFAULT 0 CHECK (overdraft_limit >= 0);
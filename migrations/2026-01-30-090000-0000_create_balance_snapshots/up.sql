@@ -0,0 +1,13 @@
+-- Balance of a wallet at a point in time, so historical balances only replay
+-- the postings made after the latest snapshot
+CREATE TABLE balance_snapshots (
+    id BIGSERIAL PRIMARY KEY,
+    account_id BIGINT NOT NULL REFERENCES accounts(id),
+    currency VARCHAR(3) NOT NULL,
+    balance BIGINT NOT NULL,
+    as_of TIMESTAMP NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (account_id, currency, as_of)
+);
+
+CREATE INDEX idx_postings_account_currency_created_at ON postings(account_id, currency, created_at);
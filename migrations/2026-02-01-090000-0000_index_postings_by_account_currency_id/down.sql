@@ -0,0 +1 @@
+DROP INDEX IF EXISTS idx_postings_account_currency_id;
@@ -0,0 +1,2 @@
+-- Running balances walk an account's postings in id order from the latest backwards
+CREATE INDEX idx_postings_account_currency_id ON postings(account_id, currency, id);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_transactions_metadata;
+ALTER TABLE accounts DROP COLUMN IF EXISTS metadata;
+ALTER TABLE transactions DROP COLUMN IF EXISTS metadata;
@@ -0,0 +1,6 @@
+-- Free-form key/value pairs attached by integrations (order ids, invoice numbers, ...)
+ALTER TABLE transactions ADD COLUMN metadata JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE accounts ADD COLUMN metadata JSONB NOT NULL DEFAULT '{}';
+
+-- Serves metadata searches by key (?) and by key/value (@>)
+CREATE INDEX idx_transactions_metadata ON transactions USING GIN (metadata);
@@ -0,0 +1,5 @@
+ALTER TABLE transactions
+    DROP COLUMN IF EXISTS fee_account_id,
+    DROP COLUMN IF EXISTS fee_amount;
+DROP TABLE IF EXISTS fee_schedules;
+DROP TYPE IF EXISTS fee_type;
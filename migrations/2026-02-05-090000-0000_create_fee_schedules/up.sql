@@ -0,0 +1,35 @@
+CREATE TYPE fee_type AS ENUM ('flat', 'percentage', 'tiered');
+
+-- Fees charged to the paying account of debits and transfers. A schedule with an
+-- account_id applies to that account only and takes precedence over the global
+-- schedule (account_id NULL) of the same transaction type and currency.
+-- percentage is scaled by 10^8 (2.9% is stored as 290000000). tiers is a list of
+-- {"up_to", "flat_amount", "percentage"} ordered by up_to, the last one unbounded.
+CREATE TABLE fee_schedules (
+    id BIGSERIAL PRIMARY KEY,
+    account_id BIGINT REFERENCES accounts(id) ON DELETE CASCADE,
+    tx_type transaction_type NOT NULL CHECK (tx_type <> 'credit'),
+    currency VARCHAR(3) NOT NULL,
+    fee_type fee_type NOT NULL,
+    flat_amount BIGINT NOT NULL DEFAULT 0 CHECK (flat_amount >= 0),
+    percentage BIGINT NOT NULL DEFAULT 0 CHECK (percentage >= 0),
+    min_fee BIGINT CHECK (min_fee >= 0),
+    max_fee BIGINT CHECK (max_fee >= 0),
+    tiers JSONB NOT NULL DEFAULT '[]',
+    fee_account_id BIGINT NOT NULL REFERENCES accounts(id),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK (min_fee IS NULL OR max_fee IS NULL OR min_fee <= max_fee)
+);
+
+CREATE UNIQUE INDEX idx_fee_schedules_account
+    ON fee_schedules(account_id, tx_type, currency) WHERE account_id IS NOT NULL;
+CREATE UNIQUE INDEX idx_fee_schedules_global
+    ON fee_schedules(tx_type, currency) WHERE account_id IS NULL;
+
+SELECT diesel_manage_updated_at('fee_schedules');
+
+-- The fee a transaction charged its paying account and the account it was credited to
+ALTER TABLE transactions
+    ADD COLUMN fee_amount BIGINT NOT NULL DEFAULT 0 CHECK (fee_amount >= 0),
+    ADD COLUMN fee_account_id BIGINT REFERENCES accounts(id);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_transaction_status_history_created_by;
+DROP TABLE IF EXISTS transaction_limits;
@@ -0,0 +1,22 @@
+-- Velocity and amount limits on the debits and transfers paid from an account or
+-- created with an API key, per currency. A NULL limit is not enforced.
+CREATE TABLE transaction_limits (
+    id BIGSERIAL PRIMARY KEY,
+    account_id BIGINT REFERENCES accounts(id) ON DELETE CASCADE,
+    api_key_id BIGINT REFERENCES api_keys(id) ON DELETE CASCADE,
+    currency VARCHAR(3) NOT NULL,
+    max_amount BIGINT CHECK (max_amount > 0),
+    max_daily_outflow BIGINT CHECK (max_daily_outflow >= 0),
+    max_monthly_outflow BIGINT CHECK (max_monthly_outflow >= 0),
+    max_daily_transactions INTEGER CHECK (max_daily_transactions >= 0),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((account_id IS NULL) <> (api_key_id IS NULL)),
+    UNIQUE NULLS NOT DISTINCT (account_id, api_key_id, currency)
+);
+
+SELECT diesel_manage_updated_at('transaction_limits');
+
+-- Usage of an API key is counted from the transactions it created
+CREATE INDEX idx_transaction_status_history_created_by
+    ON transaction_status_history(actor_api_key_id) WHERE from_status IS NULL;
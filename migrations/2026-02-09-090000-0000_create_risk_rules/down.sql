@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS risk_reviews;
+DROP TABLE IF EXISTS risk_rules;
+DROP TYPE IF EXISTS risk_review_status;
+DROP TYPE IF EXISTS risk_outcome;
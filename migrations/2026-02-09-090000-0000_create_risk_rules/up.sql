@@ -0,0 +1,37 @@
+CREATE TYPE risk_outcome AS ENUM ('allow', 'review', 'block');
+CREATE TYPE risk_review_status AS ENUM ('pending', 'approved', 'declined');
+
+-- Checks run on every new debit and transfer. definition is a JSON condition tagged
+-- by "type"; a rule whose condition matches yields its outcome, a transaction no
+-- rule matches is allowed. currency NULL applies the rule to every currency.
+CREATE TABLE risk_rules (
+    id BIGSERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    currency VARCHAR(3),
+    definition JSONB NOT NULL,
+    outcome risk_outcome NOT NULL CHECK (outcome <> 'allow'),
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+SELECT diesel_manage_updated_at('risk_rules');
+
+-- Transactions held as pending until an admin approves or declines them.
+-- capture records whether the transaction was created as an authorization.
+CREATE TABLE risk_reviews (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BIGINT NOT NULL UNIQUE REFERENCES transactions(id) ON DELETE CASCADE,
+    matched_rules JSONB NOT NULL,
+    capture BOOLEAN NOT NULL,
+    status risk_review_status NOT NULL DEFAULT 'pending',
+    reviewer_api_key_id BIGINT REFERENCES api_keys(id),
+    reason VARCHAR(500),
+    decided_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_risk_reviews_pending ON risk_reviews(created_at) WHERE status = 'pending';
+
+SELECT diesel_manage_updated_at('risk_reviews');
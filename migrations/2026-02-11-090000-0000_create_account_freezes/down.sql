@@ -0,0 +1,12 @@
+DELETE FROM holds WHERE transaction_id IS NULL;
+
+ALTER TABLE holds
+    DROP CONSTRAINT IF EXISTS holds_transaction_or_reason,
+    DROP COLUMN IF EXISTS release_reason,
+    DROP COLUMN IF EXISTS released_by_api_key_id,
+    DROP COLUMN IF EXISTS placed_by_api_key_id,
+    DROP COLUMN IF EXISTS reason,
+    ALTER COLUMN transaction_id SET NOT NULL;
+
+DROP TABLE IF EXISTS account_freezes;
+DROP TYPE IF EXISTS freeze_scope;
@@ -0,0 +1,31 @@
+CREATE TYPE freeze_scope AS ENUM ('outflows', 'all');
+
+-- Admin freezes of an account. 'outflows' blocks money leaving the account,
+-- 'all' blocks every posting to it. Lifted freezes are kept as history; an
+-- account has at most one active freeze.
+CREATE TABLE account_freezes (
+    id BIGSERIAL PRIMARY KEY,
+    account_id BIGINT NOT NULL REFERENCES accounts(id),
+    scope freeze_scope NOT NULL,
+    reason VARCHAR(500) NOT NULL,
+    frozen_by_api_key_id BIGINT NOT NULL REFERENCES api_keys(id),
+    lifted_by_api_key_id BIGINT REFERENCES api_keys(id),
+    lift_reason VARCHAR(500),
+    lifted_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX idx_account_freezes_active ON account_freezes(account_id) WHERE lifted_at IS NULL;
+
+SELECT diesel_manage_updated_at('account_freezes');
+
+-- Holds placed by an admin for legal or compliance reasons have no transaction
+-- and carry a reason instead
+ALTER TABLE holds
+    ALTER COLUMN transaction_id DROP NOT NULL,
+    ADD COLUMN reason VARCHAR(500),
+    ADD COLUMN placed_by_api_key_id BIGINT REFERENCES api_keys(id),
+    ADD COLUMN released_by_api_key_id BIGINT REFERENCES api_keys(id),
+    ADD COLUMN release_reason VARCHAR(500),
+    ADD CONSTRAINT holds_transaction_or_reason CHECK (transaction_id IS NOT NULL OR reason IS NOT NULL);
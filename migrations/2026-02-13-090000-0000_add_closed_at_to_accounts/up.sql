@@ -0,0 +1,2 @@
+-- Set when an account is closed; closed accounts stay inactive for good
+ALTER TABLE accounts ADD COLUMN closed_at TIMESTAMP;
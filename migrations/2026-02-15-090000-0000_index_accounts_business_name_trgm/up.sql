@@ -0,0 +1,5 @@
+-- Admin account search matches any part of business_name case-insensitively,
+-- which the btree index on business_name cannot serve
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX idx_accounts_business_name_trgm ON accounts USING gin (business_name gin_trgm_ops);
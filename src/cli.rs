@@ -0,0 +1,58 @@
+use crate::{models::ImportFormat, services, utils::db::DbPool};
+
+const USAGE: &str =
+    "Usage: ledger-core import <file> [--format csv|ndjson] [--upload-id <id>] [--dry-run]";
+
+// Commands that run once and exit instead of starting the HTTP server
+pub fn run(args: &[String], pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
+    match args.first().map(String::as_str) {
+        Some("import") => import(&args[1..], pool),
+        Some(command) => Err(format!("Unknown command: {}\n{}", command, USAGE).into()),
+        None => Err(USAGE.into()),
+    }
+}
+
+fn import(args: &[String], pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut path = None;
+    let mut format = None;
+    let mut upload_id = None;
+    let mut dry_run = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                let value = args.next().ok_or(USAGE)?;
+                format = Some(ImportFormat::detect(&format!(".{}", value)).ok_or(USAGE)?);
+            }
+            "--upload-id" => upload_id = Some(args.next().ok_or(USAGE)?.clone()),
+            "--dry-run" => dry_run = true,
+            _ if path.is_none() => path = Some(arg.clone()),
+            _ => return Err(USAGE.into()),
+        }
+    }
+
+    let path = path.ok_or(USAGE)?;
+    let format = format
+        .or_else(|| ImportFormat::detect(&path))
+        .ok_or("Cannot tell the file format from its name, pass --format csv|ndjson")?;
+    let data = std::fs::read_to_string(&path)?;
+
+    let mut conn = pool.get()?;
+    let report = services::import_service::import_transactions(
+        &data,
+        format,
+        upload_id.as_deref(),
+        dry_run,
+        None,
+        &mut conn,
+    )
+    .map_err(|e| e.message())?;
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+
+    if report.failed > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -7,9 +7,17 @@ use crate::{
 };
 use axum::{
     Extension, Json,
-    extract::{Path, State},
+    body::Body,
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
 };
+use futures_util::stream;
 use std::sync::Arc;
+use tokio::sync::mpsc;
+
+// Chunks buffered between the database reader and the response body
+const STATEMENT_CHANNEL_SIZE: usize = 8;
 
 pub async fn create_account(
     State(state): State<Arc<AppState>>,
@@ -25,6 +33,23 @@ pub async fn create_account(
     Ok(Json(response))
 }
 
+// Admin: search and page through every account
+pub async fn list_accounts(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Query(query): Query<ListAccountsQuery>,
+) -> Result<Json<AccountListResponse>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::account_service::list_accounts(query, &mut conn)?;
+    Ok(Json(response))
+}
+
 pub async fn get_account(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<ApiKeyAuth>,
@@ -42,11 +67,83 @@ pub async fn get_account(
     Ok(Json(response))
 }
 
+// Update an account (admin or own account); only admins may change is_active
+pub async fn update_account(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateAccountRequest>,
+) -> Result<Json<AccountResponse>, AppError> {
+    authorization::require_account_access(&auth, id)?;
+    if req.is_active.is_some() {
+        authorization::require_admin(&auth)?;
+    }
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::account_service::update_account(id, req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Close an account for good, sweeping what it holds if asked to (admin or own account)
+pub async fn close_account(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+    Json(req): Json<CloseAccountRequest>,
+) -> Result<Json<CloseAccountResponse>, AppError> {
+    authorization::require_account_access(&auth, id)?;
+    // Only an admin may close an account that has been deactivated
+    let allow_inactive = authorization::require_admin(&auth).is_ok();
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::account_service::close_account(
+        id,
+        Some(auth.key_id),
+        allow_inactive,
+        req,
+        &mut conn,
+    )?;
+    Ok(Json(response))
+}
+
 pub async fn get_balance(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<ApiKeyAuth>,
     Path(id): Path<i64>,
-) -> Result<Json<serde_json::Value>, AppError> {
+    Query(query): Query<BalanceQuery>,
+) -> Result<Json<BalanceResponse>, AppError> {
+    // Require account access (admin or own account)
+    authorization::require_account_access(&auth, id)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = match query.as_of {
+        Some(as_of) => {
+            services::account_service::get_balance_as_of(id, as_of.naive_utc(), &mut conn)?
+        }
+        None => services::account_service::get_balance(id, &mut conn)?,
+    };
+    Ok(Json(response))
+}
+
+// Open an additional currency wallet on an account
+pub async fn open_wallet(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+    Json(req): Json<OpenWalletRequest>,
+) -> Result<Json<BalanceResponse>, AppError> {
     // Require account access (admin or own account)
     authorization::require_account_access(&auth, id)?;
 
@@ -55,6 +152,222 @@ pub async fn get_balance(
         .get()
         .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
 
-    let balance = services::account_service::get_balance(id, &mut conn)?;
-    Ok(Json(serde_json::json!({ "balance": balance })))
+    let response = services::account_service::open_wallet(id, req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Admin: set how far below zero a wallet may be debited
+pub async fn set_overdraft_limit(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+    Json(req): Json<SetOverdraftLimitRequest>,
+) -> Result<Json<BalanceResponse>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::account_service::set_overdraft_limit(id, req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Admin: freeze an account's outflows or all of its activity
+pub async fn freeze_account(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+    Json(req): Json<FreezeAccountRequest>,
+) -> Result<Json<AccountFreeze>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::account_service::freeze_account(id, auth.key_id, req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Admin: lift an account's freeze
+pub async fn unfreeze_account(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+    Json(req): Json<LiftFreezeRequest>,
+) -> Result<Json<AccountFreeze>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::account_service::lift_freeze(id, auth.key_id, req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Admin: freezes placed on an account and who placed and lifted them
+pub async fn list_freezes(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<AccountFreeze>>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::account_service::list_freezes(id, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Admin: hold funds on a wallet for legal or compliance reasons
+pub async fn place_hold(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+    Json(req): Json<PlaceHoldRequest>,
+) -> Result<Json<HoldResponse>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::account_service::place_hold(id, auth.key_id, req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Admin: active holds on an account
+pub async fn list_holds(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<HoldResponse>>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::account_service::list_holds(id, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Admin: release a compliance hold
+pub async fn release_hold(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(hold_id): Path<i64>,
+    Json(req): Json<ReleaseHoldRequest>,
+) -> Result<Json<HoldResponse>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::account_service::release_hold(hold_id, auth.key_id, req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Admin: replace the transaction limits of an account in one currency
+pub async fn set_limits(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+    Json(req): Json<SetTransactionLimitsRequest>,
+) -> Result<Json<TransactionLimitResponse>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::limit_service::set_limits(LimitScope::Account, id, req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Limits of an account and their usage today and this month
+pub async fn get_limits(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<TransactionLimitResponse>>, AppError> {
+    // Require account access (admin or own account)
+    authorization::require_account_access(&auth, id)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::limit_service::get_limits(LimitScope::Account, id, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Stream an account statement as CSV or JSON. Rows are produced on a blocking
+// thread and handed to the response body as they are written.
+pub async fn export_statement(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+    Query(query): Query<StatementQuery>,
+) -> Result<Response, AppError> {
+    // Require account access (admin or own account)
+    authorization::require_account_access(&auth, id)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let statement = services::statement_service::open_statement(id, query, &mut conn)?;
+    let format = statement.format;
+    let filename = format!(
+        "statement_{}_{}_{}.{}",
+        statement.account_id,
+        statement.from.format("%Y%m%d"),
+        statement.to.format("%Y%m%d"),
+        format.extension()
+    );
+
+    let (sender, receiver) =
+        mpsc::channel::<Result<String, std::io::Error>>(STATEMENT_CHANNEL_SIZE);
+    tokio::task::spawn_blocking(move || {
+        let result = services::statement_service::write_statement(&statement, &mut conn, |chunk| {
+            sender.blocking_send(Ok(chunk)).is_ok()
+        });
+        if let Err(e) = result {
+            tracing::error!(account_id = statement.account_id, error = ?e, "Statement export failed");
+            // Cut the response short so the client does not take it as complete
+            let _ = sender.blocking_send(Err(std::io::Error::other(e.message())));
+        }
+    });
+
+    let body = Body::from_stream(stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    }));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    )
+        .into_response())
 }
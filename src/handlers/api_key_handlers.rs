@@ -76,3 +76,40 @@ pub async fn update_api_key(
     let response = services::api_key_service::update_api_key(key_id, req, &mut conn)?;
     Ok(Json(response))
 }
+
+// Admin: replace the transaction limits of an API key in one currency
+pub async fn set_api_key_limits(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    axum::extract::Path(key_id): axum::extract::Path<i64>,
+    Json(req): Json<SetTransactionLimitsRequest>,
+) -> Result<Json<TransactionLimitResponse>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::limit_service::set_limits(LimitScope::ApiKey, key_id, req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Limits of an API key and their usage today and this month (admin or the key itself)
+pub async fn get_api_key_limits(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    axum::extract::Path(key_id): axum::extract::Path<i64>,
+) -> Result<Json<Vec<TransactionLimitResponse>>, AppError> {
+    if auth.key_id != key_id {
+        authorization::require_admin(&auth)?;
+    }
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::limit_service::get_limits(LimitScope::ApiKey, key_id, &mut conn)?;
+    Ok(Json(response))
+}
@@ -0,0 +1,63 @@
+use crate::{
+    AppState,
+    middleware::{ApiKeyAuth, authorization},
+    models::*,
+    services,
+    utils::app_error::AppError,
+};
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+};
+use std::sync::Arc;
+
+// Admin: add a fee schedule for one account or for every account
+pub async fn create_fee_schedule(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Json(req): Json<CreateFeeScheduleRequest>,
+) -> Result<Json<FeeScheduleResponse>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::fee_service::create_fee_schedule(req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Admin: every fee schedule, or those of one account with ?account_id=
+pub async fn list_fee_schedules(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Query(query): Query<ListFeeSchedulesQuery>,
+) -> Result<Json<Vec<FeeScheduleResponse>>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let schedules = services::fee_service::list_fee_schedules(query, &mut conn)?;
+    Ok(Json(schedules))
+}
+
+// Admin: remove a schedule; transactions already created keep the fee they were quoted
+pub async fn delete_fee_schedule(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+) -> Result<(), AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    services::fee_service::delete_fee_schedule(id, &mut conn)?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use crate::{
+    AppState,
+    middleware::{ApiKeyAuth, authorization},
+    models::*,
+    services,
+    utils::app_error::AppError,
+};
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+};
+use std::sync::Arc;
+
+// Admin: create or replace the exchange rate of a currency pair
+pub async fn set_fx_rate(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Json(req): Json<SetFxRateRequest>,
+) -> Result<Json<FxRateResponse>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::fx_service::set_fx_rate(req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Current rates, readable by any key so clients can preview conversions
+pub async fn list_fx_rates(
+    State(state): State<Arc<AppState>>,
+    Extension(_auth): Extension<ApiKeyAuth>,
+) -> Result<Json<Vec<FxRateResponse>>, AppError> {
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let rates = services::fx_service::list_fx_rates(&mut conn)?;
+    Ok(Json(rates))
+}
+
+// Admin: remove a rate, which blocks new conversions for that pair
+pub async fn delete_fx_rate(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+) -> Result<(), AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    services::fx_service::delete_fx_rate(id, &mut conn)?;
+    Ok(())
+}
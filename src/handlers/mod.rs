@@ -2,4 +2,9 @@ pub mod health;
 pub mod account_handlers;
 pub mod transaction_handlers;
 pub mod api_key_handlers;
-pub mod webhook_handlers;
\ No newline at end of file
+pub mod webhook_handlers;
+pub mod recurring_transfer_handlers;
+pub mod fx_rate_handlers;
+pub mod reconciliation_handlers;
+pub mod fee_schedule_handlers;
+pub mod risk_handlers;
\ No newline at end of file
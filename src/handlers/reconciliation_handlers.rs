@@ -0,0 +1,30 @@
+use crate::{
+    AppState,
+    middleware::{ApiKeyAuth, authorization},
+    models::*,
+    services,
+    utils::app_error::AppError,
+};
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+};
+use std::sync::Arc;
+
+// Run a reconciliation of every wallet against the journal now (admin only)
+pub async fn run_reconciliation(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Query(query): Query<ReconciliationQuery>,
+) -> Result<Json<ReconciliationReport>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let report =
+        services::reconciliation_service::reconcile(query.alert.unwrap_or(false), &mut conn)?;
+    Ok(Json(report))
+}
@@ -0,0 +1,133 @@
+use crate::{
+    AppState,
+    middleware::{ApiKeyAuth, authorization},
+    models::*,
+    services,
+    utils::app_error::AppError,
+};
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+};
+use std::sync::Arc;
+
+pub async fn create_recurring_transfer(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Json(req): Json<CreateRecurringTransferRequest>,
+) -> Result<Json<RecurringTransfer>, AppError> {
+    // Standing orders can only be set up on the paying account
+    authorization::require_account_access(&auth, req.from_account_id)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::recurring_transfer_service::create_recurring_transfer(req, &mut conn)?;
+
+    Ok(Json(response))
+}
+
+pub async fn get_recurring_transfer(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+) -> Result<Json<RecurringTransfer>, AppError> {
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let transfer = services::recurring_transfer_service::get_recurring_transfer(id, &mut conn)?;
+    authorization::require_account_access(&auth, transfer.from_account_id)?;
+
+    Ok(Json(transfer))
+}
+
+// Outcome of every occurrence, most recent first
+pub async fn list_recurring_transfer_runs(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<RecurringTransferRun>>, AppError> {
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let transfer = services::recurring_transfer_service::get_recurring_transfer(id, &mut conn)?;
+    authorization::require_account_access(&auth, transfer.from_account_id)?;
+
+    let runs = services::recurring_transfer_service::list_recurring_transfer_runs(id, &mut conn)?;
+    Ok(Json(runs))
+}
+
+pub async fn pause_recurring_transfer(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+) -> Result<Json<RecurringTransfer>, AppError> {
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let transfer = services::recurring_transfer_service::get_recurring_transfer(id, &mut conn)?;
+    authorization::require_account_access(&auth, transfer.from_account_id)?;
+
+    let response = services::recurring_transfer_service::pause_recurring_transfer(id, &mut conn)?;
+    Ok(Json(response))
+}
+
+pub async fn resume_recurring_transfer(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+) -> Result<Json<RecurringTransfer>, AppError> {
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let transfer = services::recurring_transfer_service::get_recurring_transfer(id, &mut conn)?;
+    authorization::require_account_access(&auth, transfer.from_account_id)?;
+
+    let response = services::recurring_transfer_service::resume_recurring_transfer(id, &mut conn)?;
+    Ok(Json(response))
+}
+
+pub async fn cancel_recurring_transfer(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+) -> Result<Json<RecurringTransfer>, AppError> {
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let transfer = services::recurring_transfer_service::get_recurring_transfer(id, &mut conn)?;
+    authorization::require_account_access(&auth, transfer.from_account_id)?;
+
+    let response = services::recurring_transfer_service::cancel_recurring_transfer(id, &mut conn)?;
+    Ok(Json(response))
+}
+
+pub async fn list_account_recurring_transfers(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(account_id): Path<i64>,
+) -> Result<Json<Vec<RecurringTransfer>>, AppError> {
+    authorization::require_account_access(&auth, account_id)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let transfers = services::recurring_transfer_service::list_account_recurring_transfers(
+        account_id, &mut conn,
+    )?;
+    Ok(Json(transfers))
+}
@@ -0,0 +1,133 @@
+use crate::{
+    AppState,
+    middleware::{ApiKeyAuth, authorization},
+    models::*,
+    services,
+    utils::app_error::AppError,
+};
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+};
+use std::sync::Arc;
+
+// Admin: add a risk rule
+pub async fn create_risk_rule(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Json(req): Json<CreateRiskRuleRequest>,
+) -> Result<Json<RiskRuleResponse>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::risk_service::create_rule(req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Admin: every risk rule, active or not
+pub async fn list_risk_rules(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+) -> Result<Json<Vec<RiskRuleResponse>>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let rules = services::risk_service::list_rules(&mut conn)?;
+    Ok(Json(rules))
+}
+
+// Admin: change a rule's definition or outcome, or switch it on or off
+pub async fn update_risk_rule(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateRiskRuleRequest>,
+) -> Result<Json<RiskRuleResponse>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::risk_service::update_rule(id, req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Admin: remove a rule; reviews it already opened are kept
+pub async fn delete_risk_rule(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+) -> Result<(), AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    services::risk_service::delete_rule(id, &mut conn)?;
+    Ok(())
+}
+
+// Admin: transactions held for review, pending ones by default
+pub async fn list_risk_reviews(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Query(query): Query<ListRiskReviewsQuery>,
+) -> Result<Json<Vec<RiskReviewResponse>>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let reviews = services::risk_service::list_reviews(query, &mut conn)?;
+    Ok(Json(reviews))
+}
+
+// Admin: let a held transaction go ahead
+pub async fn approve_risk_review(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+    Json(req): Json<DecideRiskReviewRequest>,
+) -> Result<Json<RiskReviewResponse>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::risk_service::approve_review(id, auth.key_id, req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Admin: fail a held transaction
+pub async fn decline_risk_review(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+    Json(req): Json<DecideRiskReviewRequest>,
+) -> Result<Json<RiskReviewResponse>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::risk_service::decline_review(id, auth.key_id, req, &mut conn)?;
+    Ok(Json(response))
+}
@@ -1,7 +1,14 @@
-use crate::{AppState, middleware::ApiKeyAuth, models::*, services, utils::app_error::AppError};
+use crate::{
+    AppState,
+    middleware::{ApiKeyAuth, authorization},
+    models::*,
+    services,
+    utils::app_error::AppError,
+};
 use axum::{
     Extension, Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::{HeaderMap, header},
 };
 use std::sync::Arc;
 
@@ -20,11 +27,130 @@ pub async fn create_transaction(
         .get()
         .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
 
-    let response = services::transaction_service::create_transaction(account_id, req, &mut conn)?;
+    let response = services::transaction_service::create_transaction(
+        account_id,
+        Some(auth.key_id),
+        req,
+        &mut conn,
+    )?;
+
+    Ok(Json(response))
+}
+
+// Dry run of create_transaction that returns the fee it would be charged. Customers
+// can only quote transactions paid from their own account.
+pub async fn quote_transaction(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Json(req): Json<CreateTransactionRequest>,
+) -> Result<Json<FeeQuoteResponse>, AppError> {
+    if let Some(from_account_id) = req.from_account_id {
+        authorization::require_account_access(&auth, from_account_id)?;
+    }
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
 
+    let response = services::transaction_service::quote_transaction(req, &mut conn)?;
     Ok(Json(response))
 }
 
+// Create several legs atomically under one parent batch
+pub async fn create_batch(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Json(req): Json<CreateBatchRequest>,
+) -> Result<Json<BatchResponse>, AppError> {
+    if auth.account_id.is_none() {
+        return Err(AppError::BadRequest(
+            "Admin keys cannot create transactions".to_string(),
+        ));
+    }
+    // Every leg that pays out must come from the caller's own account
+    for leg in &req.legs {
+        if let Some(from_account_id) = leg.from_account_id {
+            authorization::require_account_access(&auth, from_account_id)?;
+        }
+    }
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let response = services::transaction_service::create_batch(Some(auth.key_id), req, &mut conn)?;
+
+    Ok(Json(response))
+}
+
+pub async fn get_batch(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+) -> Result<Json<BatchResponse>, AppError> {
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let batch = services::transaction_service::get_batch(id, &mut conn)?;
+
+    // Customers can see a batch if they are party to any of its legs
+    if auth.role != "admin"
+        && !batch
+            .legs
+            .iter()
+            .any(|leg| authorize_transaction_view(&auth, &leg.transaction).is_ok())
+    {
+        return Err(AppError::Forbidden);
+    }
+
+    Ok(Json(batch))
+}
+
+// Admin: bulk import historical transactions from a CSV or NDJSON body
+pub async fn import_transactions(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Query(query): Query<ImportQuery>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<ImportReport>, AppError> {
+    authorization::require_admin(&auth)?;
+
+    let format = query
+        .format
+        .or_else(|| {
+            headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(ImportFormat::detect)
+        })
+        .ok_or_else(|| {
+            AppError::BadRequest(
+                "Unknown import format, use ?format=csv or ?format=ndjson".to_string(),
+            )
+        })?;
+
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let report = services::import_service::import_transactions(
+        &body,
+        format,
+        query.upload_id.as_deref(),
+        query.dry_run.unwrap_or(false),
+        Some(auth.key_id),
+        &mut conn,
+    )?;
+
+    Ok(Json(report))
+}
+
 pub async fn get_transaction(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<ApiKeyAuth>,
@@ -37,20 +163,131 @@ pub async fn get_transaction(
 
     let transaction = services::transaction_service::get_transaction(id, &mut conn)?;
 
-    // Authorization: Admin can see any transaction, customer only their own account's transactions
-    if auth.role != "admin" {
-        let account_id = auth.account_id.ok_or_else(|| AppError::Forbidden)?;
+    authorize_transaction_view(&auth, &transaction)?;
 
-        // Customer can see transaction if they are either sender or receiver
-        let is_sender = transaction.from_account_id == Some(account_id);
-        let is_receiver = transaction.to_account_id == Some(account_id);
+    Ok(Json(transaction))
+}
 
-        if !is_sender && !is_receiver {
+// Capture a pending authorization (full or partial amount)
+pub async fn capture_transaction(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+    Json(req): Json<CaptureTransactionRequest>,
+) -> Result<Json<TransactionResponse>, AppError> {
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let transaction = services::transaction_service::get_transaction(id, &mut conn)?;
+    authorize_transaction_view(&auth, &transaction)?;
+
+    let response =
+        services::transaction_service::capture_transaction(id, Some(auth.key_id), req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Void a pending authorization and release its hold
+pub async fn void_transaction(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+) -> Result<Json<TransactionResponse>, AppError> {
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let transaction = services::transaction_service::get_transaction(id, &mut conn)?;
+    authorize_transaction_view(&auth, &transaction)?;
+
+    let response =
+        services::transaction_service::void_transaction(id, Some(auth.key_id), &mut conn)?;
+    Ok(Json(response))
+}
+
+// Refund a completed transaction (full or partial) with a linked compensating transaction
+pub async fn reverse_transaction(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+    Json(req): Json<ReverseTransactionRequest>,
+) -> Result<Json<TransactionResponse>, AppError> {
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let transaction = services::transaction_service::get_transaction(id, &mut conn)?;
+
+    // Only the account that received the funds (or an admin) can give them back
+    if auth.role != "admin" {
+        let account_id = auth.account_id.ok_or(AppError::Forbidden)?;
+        if transaction.to_account_id != Some(account_id) {
             return Err(AppError::Forbidden);
         }
     }
 
-    Ok(Json(transaction))
+    let response =
+        services::transaction_service::reverse_transaction(id, Some(auth.key_id), req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Cancel a pending transaction before it settles
+pub async fn cancel_transaction(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+    Json(req): Json<CancelTransactionRequest>,
+) -> Result<Json<TransactionResponse>, AppError> {
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let transaction = services::transaction_service::get_transaction(id, &mut conn)?;
+    authorize_transaction_view(&auth, &transaction)?;
+
+    let response =
+        services::transaction_service::cancel_transaction(id, Some(auth.key_id), req, &mut conn)?;
+    Ok(Json(response))
+}
+
+// Status transitions of a transaction, oldest first
+pub async fn get_transaction_history(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<TransactionStatusHistory>>, AppError> {
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let transaction = services::transaction_service::get_transaction(id, &mut conn)?;
+    authorize_transaction_view(&auth, &transaction)?;
+
+    let history = services::transaction_service::get_transaction_history(id, &mut conn)?;
+    Ok(Json(history))
+}
+
+// Journal entries and postings behind a transaction, for auditing
+pub async fn get_transaction_journal(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<ApiKeyAuth>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<JournalEntryResponse>>, AppError> {
+    let mut conn = state
+        .db_pool
+        .get()
+        .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+    let transaction = services::transaction_service::get_transaction(id, &mut conn)?;
+    authorize_transaction_view(&auth, &transaction)?;
+
+    let journal = services::transaction_service::get_transaction_journal(id, &mut conn)?;
+    Ok(Json(journal))
 }
 
 // List all transactions for an account (both sent and received)
@@ -58,10 +295,11 @@ pub async fn list_account_transactions(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<ApiKeyAuth>,
     Path(account_id): Path<i64>,
-) -> Result<Json<Vec<TransactionResponse>>, AppError> {
+    Query(query): Query<ListTransactionsQuery>,
+) -> Result<Json<AccountTransactionListResponse>, AppError> {
     // Require account access (admin or own account)
     if auth.role != "admin" {
-        let user_account_id = auth.account_id.ok_or_else(|| AppError::Forbidden)?;
+        let user_account_id = auth.account_id.ok_or(AppError::Forbidden)?;
 
         if user_account_id != account_id {
             return Err(AppError::Forbidden);
@@ -74,7 +312,27 @@ pub async fn list_account_transactions(
         .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
 
     let transactions =
-        services::transaction_service::list_account_transactions(account_id, &mut conn)?;
+        services::transaction_service::list_account_transactions(account_id, query, &mut conn)?;
 
     Ok(Json(transactions))
 }
+
+// Authorization: Admin can see any transaction, customer only their own account's transactions
+fn authorize_transaction_view(
+    auth: &ApiKeyAuth,
+    transaction: &TransactionResponse,
+) -> Result<(), AppError> {
+    if auth.role != "admin" {
+        let account_id = auth.account_id.ok_or(AppError::Forbidden)?;
+
+        // Customer can see transaction if they are either sender or receiver
+        let is_sender = transaction.from_account_id == Some(account_id);
+        let is_receiver = transaction.to_account_id == Some(account_id);
+
+        if !is_sender && !is_receiver {
+            return Err(AppError::Forbidden);
+        }
+    }
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+mod cli;
 mod handlers;
 mod middleware;
 mod models;
@@ -8,7 +9,6 @@ mod services;
 mod utils;
 
 use std::sync::Arc;
-use tracing_subscriber;
 
 use middleware::rate_limit::RateLimiter;
 use utils::{db, db::DbPool};
@@ -38,11 +38,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let db_pool = db::create_pool(&database_url)?;
 
+    // `ledger-core <command> ...` runs a one-off command instead of the server
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        return cli::run(&args, &db_pool);
+    }
+
     let state = Arc::new(AppState {
         db_pool: Arc::new(db_pool),
         rate_limiter: Arc::new(RateLimiter::new()),
     });
 
+    // Background workers
+    tokio::spawn(services::hold_expiry_worker::run(state.clone()));
+    tokio::spawn(services::scheduler_worker::run(state.clone()));
+    tokio::spawn(services::balance_snapshot_worker::run(state.clone()));
+    tokio::spawn(services::reconciliation_worker::run(state.clone()));
+
     let cors = middleware::cors::create_cors_layer();
     let app = routes::create_router(state).layer(cors);
 
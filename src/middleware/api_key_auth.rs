@@ -10,6 +10,7 @@ use std::sync::Arc;
 
 #[derive(Clone, Debug)]
 pub struct ApiKeyAuth {
+    pub key_id: i64,
     pub account_id: Option<i64>, // None for admin keys
     pub role: String,
 }
@@ -51,6 +52,7 @@ pub async fn api_key_auth_middleware(
     repositories::update_last_used(api_key_record.id, &mut conn).ok();
 
     let auth = ApiKeyAuth {
+        key_id: api_key_record.id,
         account_id: api_key_record.account_id,
         role: api_key_record.role.clone(),
     };
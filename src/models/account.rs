@@ -1,8 +1,10 @@
-use crate::schema::accounts;
-use chrono::NaiveDateTime;
+use crate::models::TransactionResponse;
+use crate::schema::{account_balances, accounts};
+use crate::utils::currency;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use diesel::{
     Selectable,
-    prelude::{Insertable, Queryable},
+    prelude::{AsChangeset, Insertable, Queryable},
 };
 use serde::{Deserialize, Serialize};
 
@@ -11,51 +13,204 @@ use serde::{Deserialize, Serialize};
 pub struct Account {
     pub id: i64,
     pub business_name: String,
-    pub balance: i64,
-    pub currency: String,
+    pub currency: String, // Default currency, used when a transaction does not name one
     pub is_active: bool,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub metadata: serde_json::Value,
+    pub closed_at: Option<NaiveDateTime>,
 }
 
 #[derive(Debug, Insertable)]
 #[diesel(table_name = accounts)]
 pub struct NewAccount {
     pub business_name: String,
-    pub balance: i64,
     pub currency: String,
     pub is_active: bool,
+    pub metadata: serde_json::Value,
+}
+
+// The balance an account holds in one currency
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = account_balances)]
+pub struct AccountBalance {
+    pub id: i64,
+    pub account_id: i64,
+    pub currency: String,
+    pub balance: i64,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub overdraft_limit: i64, // How far below zero the balance may go
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = account_balances)]
+pub struct NewAccountBalance {
+    pub account_id: i64,
+    pub currency: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateAccountRequest {
     pub business_name: String,
     pub currency: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenWalletRequest {
+    pub currency: String,
+}
+
+// Fields left out are not changed. Only admins may change is_active.
+#[derive(Debug, Deserialize)]
+pub struct UpdateAccountRequest {
+    pub business_name: Option<String>,
+    pub currency: Option<String>, // Must be a currency the account has a wallet in
+    pub is_active: Option<bool>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Default, AsChangeset)]
+#[diesel(table_name = accounts)]
+pub struct AccountChanges {
+    pub business_name: Option<String>,
+    pub currency: Option<String>,
+    pub is_active: Option<bool>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+// Wallets that still hold funds are swept to `sweep_to_account_id`; without it every
+// wallet must already be empty
+#[derive(Debug, Deserialize)]
+pub struct CloseAccountRequest {
+    pub sweep_to_account_id: Option<i64>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct CloseAccountResponse {
+    pub account: AccountResponse,
+    pub sweeps: Vec<TransactionResponse>,
+}
+
+// Admin account listing, newest first. Balances filter on the default currency wallet.
+#[derive(Debug, Deserialize)]
+pub struct ListAccountsQuery {
+    pub limit: Option<i64>,
+    pub starting_after: Option<i64>, // Id of the last account of the previous page
+    pub search: Option<String>,      // Part of business_name, case-insensitive
+    pub is_active: Option<bool>,
+    pub currency: Option<String>, // Default currency
+    pub min_balance: Option<i64>, // Balance filters need `currency` and apply to its wallet
+    pub max_balance: Option<i64>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+}
+
+// Totals cover every account matching the filters, not only the current page
+#[derive(Debug, Serialize)]
+pub struct AccountListResponse {
+    pub data: Vec<AccountResponse>,
+    pub has_more: bool,
+    pub total_count: i64,
+    pub active_count: i64,
+    pub inactive_count: i64,
+    pub closed_count: i64,
+    pub totals: Vec<CurrencyTotal>,
+}
+
+// Sum of the balances all matching accounts hold in one currency
+#[derive(Debug, Serialize)]
+pub struct CurrencyTotal {
+    pub currency: String,
+    pub wallet_count: i64,
+    pub total_balance: i64,
+    pub total_balance_decimal: String,
+}
+
+impl CurrencyTotal {
+    pub fn new(currency: String, wallet_count: i64, total_balance: i64) -> Self {
+        CurrencyTotal {
+            total_balance_decimal: currency::format_amount(total_balance, &currency),
+            currency,
+            wallet_count,
+            total_balance,
+        }
+    }
+}
+
+// Applies to the default currency wallet unless another currency is named
+#[derive(Debug, Deserialize)]
+pub struct SetOverdraftLimitRequest {
+    pub overdraft_limit: i64,
+    pub currency: Option<String>,
+}
+
+// balance and available_balance are those of the default currency
 #[derive(Debug, Serialize)]
 pub struct AccountResponse {
     pub id: i64,
     pub business_name: String,
     pub balance: i64,
+    pub available_balance: i64, // balance minus active holds
+    pub balance_decimal: String,
+    pub available_balance_decimal: String,
     pub currency: String,
     pub is_active: bool,
+    pub metadata: serde_json::Value,
+    pub balances: Vec<WalletBalance>,
+    pub closed_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountCreationResponse {
+    pub account: AccountResponse,
+    pub secret_api_key: String,
+}
+
+// Amounts are in minor units, with a decimal rendering in the currency's precision
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletBalance {
+    pub currency: String,
+    pub balance: i64,
+    pub available_balance: i64,
+    pub balance_decimal: String,
+    pub available_balance_decimal: String,
+    pub overdraft_limit: i64,
 }
 
-impl From<Account> for AccountResponse {
-    fn from(account: Account) -> Self {
-        AccountResponse {
-            id: account.id,
-            business_name: account.business_name,
-            balance: account.balance,
-            currency: account.currency,
-            is_active: account.is_active,
+impl WalletBalance {
+    pub fn new(
+        currency: String,
+        balance: i64,
+        available_balance: i64,
+        overdraft_limit: i64,
+    ) -> Self {
+        WalletBalance {
+            balance_decimal: currency::format_amount(balance, &currency),
+            available_balance_decimal: currency::format_amount(available_balance, &currency),
+            currency,
+            balance,
+            available_balance,
+            overdraft_limit,
         }
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct BalanceQuery {
+    pub as_of: Option<DateTime<Utc>>, // Balance at a past point in time instead of now
+}
+
+// The default currency at the top level, followed by every wallet of the account
 #[derive(Debug, Serialize)]
-pub struct AccountCreationResponse {
-    pub account: AccountResponse,
-    pub secret_api_key: String,
+pub struct BalanceResponse {
+    pub currency: String,
+    pub balance: i64,
+    pub available_balance: i64,
+    pub balance_decimal: String,
+    pub available_balance_decimal: String,
+    pub balances: Vec<WalletBalance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<NaiveDateTime>,
 }
@@ -0,0 +1,28 @@
+use crate::schema::balance_snapshots;
+use chrono::NaiveDateTime;
+use diesel::{
+    Selectable,
+    prelude::{Insertable, Queryable},
+};
+use serde::Serialize;
+
+// The balance of one wallet including every posting made up to `as_of`
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = balance_snapshots)]
+pub struct BalanceSnapshot {
+    pub id: i64,
+    pub account_id: i64,
+    pub currency: String,
+    pub balance: i64,
+    pub as_of: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = balance_snapshots)]
+pub struct NewBalanceSnapshot {
+    pub account_id: i64,
+    pub currency: String,
+    pub balance: i64,
+    pub as_of: NaiveDateTime,
+}
@@ -0,0 +1,58 @@
+use crate::models::{TransactionResponse, TransactionType};
+use crate::schema::transaction_batches;
+use chrono::NaiveDateTime;
+use diesel::{
+    Selectable,
+    prelude::{Insertable, Queryable},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = transaction_batches)]
+pub struct TransactionBatch {
+    pub id: i64,
+    pub description: Option<String>,
+    pub idempotency_key: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = transaction_batches)]
+pub struct NewTransactionBatch {
+    pub description: Option<String>,
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBatchRequest {
+    pub legs: Vec<BatchLegRequest>,
+    pub description: Option<String>,
+    pub idempotency_key: Option<String>, // Applies to the batch as a whole
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchLegRequest {
+    pub from_account_id: Option<i64>,
+    pub to_account_id: Option<i64>,
+    pub amount: i64,
+    pub currency: Option<String>,
+    pub tx_type: TransactionType,
+    pub description: Option<String>,
+    pub convert: Option<bool>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchLegResult {
+    pub index: usize,
+    pub transaction: TransactionResponse,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchResponse {
+    pub id: i64,
+    pub description: Option<String>,
+    pub idempotency_key: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub legs: Vec<BatchLegResult>,
+}
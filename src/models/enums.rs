@@ -1,3 +1,4 @@
+#![allow(dead_code)]
 use diesel_derive_enum::DbEnum;
 use serde::{Deserialize, Serialize};
 
@@ -18,6 +19,37 @@ pub enum TransactionStatus {
     Completed,
     Failed,
     Cancelled,
+    Reversed,
+}
+
+impl TransactionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionStatus::Pending => "pending",
+            TransactionStatus::Completed => "completed",
+            TransactionStatus::Failed => "failed",
+            TransactionStatus::Cancelled => "cancelled",
+            TransactionStatus::Reversed => "reversed",
+        }
+    }
+
+    // Allowed lifecycle: pending -> completed | failed | cancelled, completed -> reversed
+    pub fn can_transition_to(&self, next: TransactionStatus) -> bool {
+        use TransactionStatus::*;
+
+        matches!(
+            (self, next),
+            (Pending, Completed) | (Pending, Failed) | (Pending, Cancelled) | (Completed, Reversed)
+        )
+    }
+}
+
+// Whether a transaction moved money into or out of the account it is listed for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryDirection {
+    Inflow,
+    Outflow,
 }
 
 #[derive(DbEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -29,6 +61,125 @@ pub enum WebhookStatus {
     Failed,
 }
 
+#[derive(DbEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[db_enum(existing_type_path = "crate::schema::sql_types::RecurrenceInterval")]
+pub enum RecurrenceInterval {
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(DbEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[db_enum(existing_type_path = "crate::schema::sql_types::RecurringTransferStatus")]
+pub enum RecurringTransferStatus {
+    Active,
+    Paused,
+    Completed,
+    Cancelled,
+}
+
+// What to do with occurrences that were missed while the server was down
+#[derive(DbEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[db_enum(existing_type_path = "crate::schema::sql_types::CatchUpPolicy")]
+pub enum CatchUpPolicy {
+    RunAll,    // Run every missed occurrence
+    RunLatest, // Run only the most recent missed occurrence
+    Skip,      // Skip missed occurrences and wait for the next one
+}
+
+impl CatchUpPolicy {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "run_all" => Some(CatchUpPolicy::RunAll),
+            "run_latest" => Some(CatchUpPolicy::RunLatest),
+            "skip" => Some(CatchUpPolicy::Skip),
+            _ => None,
+        }
+    }
+}
+
+#[derive(DbEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[db_enum(existing_type_path = "crate::schema::sql_types::RecurringRunStatus")]
+pub enum RecurringRunStatus {
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+// What a set of transaction limits applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitScope {
+    Account,
+    ApiKey,
+}
+
+impl LimitScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LimitScope::Account => "account",
+            LimitScope::ApiKey => "api_key",
+        }
+    }
+}
+
+// How a fee schedule works out the fee of a transaction
+#[derive(DbEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[db_enum(existing_type_path = "crate::schema::sql_types::FeeType")]
+pub enum FeeType {
+    Flat,       // A fixed amount
+    Percentage, // A share of the amount, plus an optional fixed part
+    Tiered,     // The flat and percentage parts of the tier the amount falls in
+}
+
+// Result of the risk checks on a transaction, from least to most severe
+#[derive(DbEnum, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[db_enum(existing_type_path = "crate::schema::sql_types::RiskOutcome")]
+pub enum RiskOutcome {
+    Allow,
+    Review, // Held as pending until an admin approves or declines it
+    Block,
+}
+
+#[derive(DbEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[db_enum(existing_type_path = "crate::schema::sql_types::RiskReviewStatus")]
+pub enum RiskReviewStatus {
+    Pending,
+    Approved,
+    Declined,
+}
+
+// What an admin freeze on an account blocks
+#[derive(DbEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[db_enum(existing_type_path = "crate::schema::sql_types::FreezeScope")]
+pub enum FreezeScope {
+    Outflows, // Money can still come in
+    All,
+}
+
+impl FreezeScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FreezeScope::Outflows => "outflows",
+            FreezeScope::All => "all",
+        }
+    }
+
+    // Whether a posting taking money out of (or, if not `outflow`, into) the account is blocked
+    pub fn blocks(&self, outflow: bool) -> bool {
+        outflow || *self == FreezeScope::All
+    }
+}
+
 // Simple enum for API key roles (stored as VARCHAR in DB)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -53,3 +204,33 @@ impl ApiKeyRole {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_status_transitions() {
+        use TransactionStatus::*;
+
+        assert!(Pending.can_transition_to(Completed));
+        assert!(Pending.can_transition_to(Failed));
+        assert!(Pending.can_transition_to(Cancelled));
+        assert!(Completed.can_transition_to(Reversed));
+
+        // Terminal states and skipped steps are rejected
+        assert!(!Pending.can_transition_to(Reversed));
+        assert!(!Completed.can_transition_to(Cancelled));
+        assert!(!Cancelled.can_transition_to(Completed));
+        assert!(!Failed.can_transition_to(Pending));
+        assert!(!Reversed.can_transition_to(Completed));
+    }
+
+    #[test]
+    fn test_freeze_scope_blocks() {
+        assert!(FreezeScope::Outflows.blocks(true));
+        assert!(!FreezeScope::Outflows.blocks(false));
+        assert!(FreezeScope::All.blocks(true));
+        assert!(FreezeScope::All.blocks(false));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::models::{FeeType, TransactionType, format_fx_rate};
+use crate::schema::fee_schedules;
+use crate::utils::currency;
+use chrono::NaiveDateTime;
+use diesel::{
+    Selectable,
+    prelude::{Insertable, Queryable},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = fee_schedules)]
+pub struct FeeSchedule {
+    pub id: i64,
+    pub account_id: Option<i64>,
+    pub tx_type: TransactionType,
+    pub currency: String,
+    pub fee_type: FeeType,
+    pub flat_amount: i64,
+    pub percentage: i64,
+    pub min_fee: Option<i64>,
+    pub max_fee: Option<i64>,
+    pub tiers: serde_json::Value,
+    pub fee_account_id: i64,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = fee_schedules)]
+pub struct NewFeeSchedule {
+    pub account_id: Option<i64>,
+    pub tx_type: TransactionType,
+    pub currency: String,
+    pub fee_type: FeeType,
+    pub flat_amount: i64,
+    pub percentage: i64,
+    pub min_fee: Option<i64>,
+    pub max_fee: Option<i64>,
+    pub tiers: serde_json::Value,
+    pub fee_account_id: i64,
+}
+
+// One band of a tiered schedule: amounts up to `up_to` (inclusive) pay `flat_amount`
+// plus `percentage` of the amount. The last tier has no `up_to`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeTier {
+    pub up_to: Option<i64>,
+    #[serde(default)]
+    pub flat_amount: i64,
+    pub percentage: Option<String>, // Decimal percent, e.g. "2.9"
+}
+
+// Amounts are in minor units of `currency`; percentages are decimal strings such as "2.9"
+#[derive(Debug, Deserialize)]
+pub struct CreateFeeScheduleRequest {
+    pub account_id: Option<i64>, // Omitted for the global schedule
+    pub tx_type: TransactionType,
+    pub currency: String,
+    pub fee_type: FeeType,
+    pub flat_amount: Option<i64>,
+    pub percentage: Option<String>,
+    pub min_fee: Option<i64>,
+    pub max_fee: Option<i64>,
+    pub tiers: Option<Vec<FeeTier>>,
+    pub fee_account_id: i64, // Account whose wallet in `currency` receives the fees
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListFeeSchedulesQuery {
+    pub account_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeeScheduleResponse {
+    pub id: i64,
+    pub account_id: Option<i64>,
+    pub tx_type: TransactionType,
+    pub currency: String,
+    pub fee_type: FeeType,
+    pub flat_amount: i64,
+    pub percentage: Option<String>,
+    pub min_fee: Option<i64>,
+    pub max_fee: Option<i64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tiers: Vec<FeeTier>,
+    pub fee_account_id: i64,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<FeeSchedule> for FeeScheduleResponse {
+    fn from(schedule: FeeSchedule) -> Self {
+        FeeScheduleResponse {
+            id: schedule.id,
+            account_id: schedule.account_id,
+            tx_type: schedule.tx_type,
+            currency: schedule.currency,
+            fee_type: schedule.fee_type,
+            flat_amount: schedule.flat_amount,
+            percentage: (schedule.percentage > 0).then(|| format_fx_rate(schedule.percentage)),
+            min_fee: schedule.min_fee,
+            max_fee: schedule.max_fee,
+            tiers: serde_json::from_value(schedule.tiers).unwrap_or_default(),
+            fee_account_id: schedule.fee_account_id,
+            created_at: schedule.created_at,
+        }
+    }
+}
+
+// What a transaction would cost without creating it
+#[derive(Debug, Serialize)]
+pub struct FeeQuoteResponse {
+    pub tx_type: TransactionType,
+    pub amount: i64,
+    pub amount_decimal: String,
+    pub currency: String,
+    pub fee_amount: i64,
+    pub fee_amount_decimal: String,
+    pub fee_account_id: Option<i64>,
+    pub fee_schedule_id: Option<i64>,
+    pub total_amount: i64, // Amount plus fee, taken from the paying account
+    pub total_amount_decimal: String,
+}
+
+impl FeeQuoteResponse {
+    pub fn new(
+        tx_type: TransactionType,
+        amount: i64,
+        currency: String,
+        fee: Option<&AppliedFee>,
+    ) -> Self {
+        let fee_amount = fee.map_or(0, |fee| fee.amount);
+        FeeQuoteResponse {
+            tx_type,
+            amount,
+            amount_decimal: currency::format_amount(amount, &currency),
+            fee_amount,
+            fee_amount_decimal: currency::format_amount(fee_amount, &currency),
+            fee_account_id: fee.map(|fee| fee.fee_account_id),
+            fee_schedule_id: fee.map(|fee| fee.schedule_id),
+            total_amount: amount + fee_amount,
+            total_amount_decimal: currency::format_amount(amount + fee_amount, &currency),
+            currency,
+        }
+    }
+}
+
+// The fee a schedule charges one transaction
+#[derive(Debug, Clone)]
+pub struct AppliedFee {
+    pub schedule_id: i64,
+    pub fee_account_id: i64,
+    pub amount: i64,
+}
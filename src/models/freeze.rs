@@ -0,0 +1,43 @@
+use crate::models::FreezeScope;
+use crate::schema::account_freezes;
+use chrono::NaiveDateTime;
+use diesel::{
+    Selectable,
+    prelude::{Insertable, Queryable},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = account_freezes)]
+pub struct AccountFreeze {
+    pub id: i64,
+    pub account_id: i64,
+    pub scope: FreezeScope,
+    pub reason: String,
+    pub frozen_by_api_key_id: i64,
+    pub lifted_by_api_key_id: Option<i64>,
+    pub lift_reason: Option<String>,
+    pub lifted_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = account_freezes)]
+pub struct NewAccountFreeze {
+    pub account_id: i64,
+    pub scope: FreezeScope,
+    pub reason: String,
+    pub frozen_by_api_key_id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FreezeAccountRequest {
+    pub scope: FreezeScope,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LiftFreezeRequest {
+    pub reason: Option<String>,
+}
@@ -0,0 +1,123 @@
+use crate::schema::fx_rates;
+use chrono::NaiveDateTime;
+use diesel::{
+    Selectable,
+    prelude::{Insertable, Queryable},
+};
+use serde::{Deserialize, Serialize};
+
+// Rates are fixed-point: the stored integer is the decimal rate times FX_RATE_SCALE
+pub const FX_RATE_SCALE: i64 = 100_000_000;
+pub const FX_RATE_DECIMALS: usize = 8;
+
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = fx_rates)]
+pub struct FxRate {
+    pub id: i64,
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub rate: i64,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = fx_rates)]
+pub struct NewFxRate {
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub rate: i64,
+}
+
+// One unit of the base currency is worth `rate` units of the quote currency. The rate is a decimal
+// string such as "1.0845" so it is never rounded through a float.
+#[derive(Debug, Deserialize)]
+pub struct SetFxRateRequest {
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub rate: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FxRateResponse {
+    pub id: i64,
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub rate: String,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<FxRate> for FxRateResponse {
+    fn from(rate: FxRate) -> Self {
+        FxRateResponse {
+            id: rate.id,
+            base_currency: rate.base_currency,
+            quote_currency: rate.quote_currency,
+            rate: format_fx_rate(rate.rate),
+            updated_at: rate.updated_at,
+        }
+    }
+}
+
+// Parse a positive decimal string into a scaled rate. More than 8 decimals is rejected.
+pub fn parse_fx_rate(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    if fraction.len() > FX_RATE_DECIMALS
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let whole: i64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().ok()?
+    };
+    let fraction: i64 = format!("{:0<width$}", fraction, width = FX_RATE_DECIMALS)
+        .parse()
+        .ok()?;
+    let rate = whole.checked_mul(FX_RATE_SCALE)?.checked_add(fraction)?;
+    (rate > 0).then_some(rate)
+}
+
+// Render a scaled rate as a decimal string without trailing zeros
+pub fn format_fx_rate(rate: i64) -> String {
+    let fraction = format!("{:0width$}", rate % FX_RATE_SCALE, width = FX_RATE_DECIMALS);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        (rate / FX_RATE_SCALE).to_string()
+    } else {
+        format!("{}.{}", rate / FX_RATE_SCALE, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fx_rate_round_trips_through_decimal_string() {
+        assert_eq!(parse_fx_rate("1.0845"), Some(108_450_000));
+        assert_eq!(parse_fx_rate("150"), Some(15_000_000_000));
+        assert_eq!(parse_fx_rate(".5"), Some(50_000_000));
+        assert_eq!(format_fx_rate(108_450_000), "1.0845");
+        assert_eq!(format_fx_rate(15_000_000_000), "150");
+        assert_eq!(format_fx_rate(1), "0.00000001");
+    }
+
+    #[test]
+    fn test_fx_rate_rejects_invalid_values() {
+        assert_eq!(parse_fx_rate("0"), None);
+        assert_eq!(parse_fx_rate("-1.2"), None);
+        assert_eq!(parse_fx_rate("1.123456789"), None);
+        assert_eq!(parse_fx_rate("1e3"), None);
+        assert_eq!(parse_fx_rate("."), None);
+    }
+}
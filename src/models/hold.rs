@@ -0,0 +1,90 @@
+use crate::schema::holds;
+use crate::utils::currency;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::{
+    Selectable,
+    prelude::{Insertable, Queryable},
+};
+use serde::{Deserialize, Serialize};
+
+// A hold reserves funds either for an authorized transaction or, with no
+// transaction and a reason, for legal or compliance purposes
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = holds)]
+pub struct Hold {
+    pub id: i64,
+    pub account_id: i64,
+    pub transaction_id: Option<i64>,
+    pub amount: i64,
+    pub expires_at: Option<NaiveDateTime>,
+    pub released_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub currency: String,
+    pub reason: Option<String>,
+    pub placed_by_api_key_id: Option<i64>,
+    pub released_by_api_key_id: Option<i64>,
+    pub release_reason: Option<String>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = holds)]
+pub struct NewHold {
+    pub account_id: i64,
+    pub transaction_id: Option<i64>,
+    pub amount: i64,
+    pub expires_at: Option<NaiveDateTime>,
+    pub currency: String,
+    pub reason: Option<String>,
+    pub placed_by_api_key_id: Option<i64>,
+}
+
+// Admin: reserve funds on a wallet; defaults to the account's currency
+#[derive(Debug, Deserialize)]
+pub struct PlaceHoldRequest {
+    pub amount: i64,
+    pub currency: Option<String>,
+    pub reason: String,
+    pub expires_at: Option<DateTime<Utc>>, // Omitted to hold until released
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReleaseHoldRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HoldResponse {
+    pub id: i64,
+    pub account_id: i64,
+    pub transaction_id: Option<i64>, // None for compliance holds
+    pub amount: i64,
+    pub amount_decimal: String,
+    pub currency: String,
+    pub reason: Option<String>,
+    pub placed_by_api_key_id: Option<i64>,
+    pub expires_at: Option<NaiveDateTime>,
+    pub released_at: Option<NaiveDateTime>,
+    pub released_by_api_key_id: Option<i64>,
+    pub release_reason: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<Hold> for HoldResponse {
+    fn from(hold: Hold) -> Self {
+        HoldResponse {
+            id: hold.id,
+            account_id: hold.account_id,
+            transaction_id: hold.transaction_id,
+            amount: hold.amount,
+            amount_decimal: currency::format_amount(hold.amount, &hold.currency),
+            currency: hold.currency,
+            reason: hold.reason,
+            placed_by_api_key_id: hold.placed_by_api_key_id,
+            expires_at: hold.expires_at,
+            released_at: hold.released_at,
+            released_by_api_key_id: hold.released_by_api_key_id,
+            release_reason: hold.release_reason,
+            created_at: hold.created_at,
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ImportFormat {
+    // Guess the format from a file name or a Content-Type header
+    pub fn detect(hint: &str) -> Option<Self> {
+        let hint = hint.to_ascii_lowercase();
+        if hint.ends_with(".csv") || hint.contains("text/csv") {
+            Some(ImportFormat::Csv)
+        } else if hint.ends_with(".ndjson")
+            || hint.ends_with(".jsonl")
+            || hint.contains("ndjson")
+            || hint.contains("jsonl")
+        {
+            Some(ImportFormat::Ndjson)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    pub format: Option<ImportFormat>, // Defaults to the request Content-Type
+    pub dry_run: Option<bool>,        // Validate only, nothing is written
+    pub upload_id: Option<String>,    // Scope of derived idempotency keys, defaults to the file
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportRowError {
+    pub line: usize,
+    pub error: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub total_rows: usize,
+    pub imported: usize,
+    pub skipped: usize, // Rows whose idempotency key was already imported
+    pub failed: usize,
+    pub dry_run: bool,
+    pub errors: Vec<ImportRowError>,
+}
@@ -0,0 +1,62 @@
+use crate::schema::{journal_entries, postings};
+use chrono::NaiveDateTime;
+use diesel::{
+    Selectable,
+    prelude::{Insertable, Queryable},
+};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = journal_entries)]
+pub struct JournalEntry {
+    pub id: i64,
+    pub transaction_id: i64,
+    pub description: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = journal_entries)]
+pub struct NewJournalEntry {
+    pub transaction_id: i64,
+    pub description: Option<String>,
+}
+
+// Positive amounts credit the account's wallet in `currency`, negative amounts debit it.
+// account_id is None for the external side of money entering or leaving the ledger.
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = postings)]
+pub struct Posting {
+    pub id: i64,
+    pub journal_entry_id: i64,
+    pub account_id: Option<i64>,
+    pub amount: i64,
+    pub created_at: NaiveDateTime,
+    pub currency: String,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = postings)]
+pub struct NewPosting {
+    pub journal_entry_id: i64,
+    pub account_id: Option<i64>,
+    pub amount: i64,
+    pub currency: String,
+}
+
+// A single debit/credit leg before it is written to the journal
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PostingLeg {
+    pub account_id: Option<i64>,
+    pub currency: String,
+    pub amount: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JournalEntryResponse {
+    pub id: i64,
+    pub transaction_id: i64,
+    pub description: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub postings: Vec<Posting>,
+}
@@ -0,0 +1,118 @@
+use crate::models::LimitScope;
+use crate::schema::transaction_limits;
+use crate::utils::currency;
+use chrono::NaiveDateTime;
+use diesel::{
+    Selectable,
+    prelude::{AsChangeset, Insertable, Queryable},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = transaction_limits)]
+pub struct TransactionLimit {
+    pub id: i64,
+    pub account_id: Option<i64>,
+    pub api_key_id: Option<i64>,
+    pub currency: String,
+    pub max_amount: Option<i64>,
+    pub max_daily_outflow: Option<i64>,
+    pub max_monthly_outflow: Option<i64>,
+    pub max_daily_transactions: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+// Setting limits replaces all of them, so a limit left out is cleared
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = transaction_limits, treat_none_as_null = true)]
+pub struct NewTransactionLimit {
+    pub account_id: Option<i64>,
+    pub api_key_id: Option<i64>,
+    pub currency: String,
+    pub max_amount: Option<i64>,
+    pub max_daily_outflow: Option<i64>,
+    pub max_monthly_outflow: Option<i64>,
+    pub max_daily_transactions: Option<i32>,
+}
+
+// Amounts are in minor units of `currency`; a limit left out is not enforced
+#[derive(Debug, Deserialize)]
+pub struct SetTransactionLimitsRequest {
+    pub currency: Option<String>, // Defaults to the account's currency
+    pub max_amount: Option<i64>,  // Largest single transaction
+    pub max_daily_outflow: Option<i64>,
+    pub max_monthly_outflow: Option<i64>,
+    pub max_daily_transactions: Option<i32>,
+}
+
+// What has been paid out in the current UTC day and month, fees included
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LimitUsage {
+    pub daily_outflow: i64,
+    pub monthly_outflow: i64,
+    pub daily_transactions: i64,
+}
+
+// A limit a transaction would break, and what is left of it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitBreach {
+    pub limit: &'static str,
+    pub max: i64,
+    pub remaining: i64,
+}
+
+// Limits of one scope in one currency with their usage so far. remaining_* is unset
+// for limits that are not enforced.
+#[derive(Debug, Serialize)]
+pub struct TransactionLimitResponse {
+    pub scope: LimitScope,
+    pub scope_id: i64,
+    pub currency: String,
+    pub max_amount: Option<i64>,
+    pub max_daily_outflow: Option<i64>,
+    pub max_monthly_outflow: Option<i64>,
+    pub max_daily_transactions: Option<i32>,
+    pub daily_outflow: i64,
+    pub daily_outflow_decimal: String,
+    pub monthly_outflow: i64,
+    pub monthly_outflow_decimal: String,
+    pub daily_transactions: i64,
+    pub remaining_daily_outflow: Option<i64>,
+    pub remaining_monthly_outflow: Option<i64>,
+    pub remaining_daily_transactions: Option<i64>,
+}
+
+impl TransactionLimitResponse {
+    pub fn new(
+        scope: LimitScope,
+        scope_id: i64,
+        limit: TransactionLimit,
+        usage: LimitUsage,
+    ) -> Self {
+        let remaining = |max: Option<i64>, used: i64| max.map(|max| (max - used).max(0));
+        TransactionLimitResponse {
+            scope,
+            scope_id,
+            max_amount: limit.max_amount,
+            max_daily_outflow: limit.max_daily_outflow,
+            max_monthly_outflow: limit.max_monthly_outflow,
+            max_daily_transactions: limit.max_daily_transactions,
+            daily_outflow: usage.daily_outflow,
+            daily_outflow_decimal: currency::format_amount(usage.daily_outflow, &limit.currency),
+            monthly_outflow: usage.monthly_outflow,
+            monthly_outflow_decimal: currency::format_amount(
+                usage.monthly_outflow,
+                &limit.currency,
+            ),
+            daily_transactions: usage.daily_transactions,
+            remaining_daily_outflow: remaining(limit.max_daily_outflow, usage.daily_outflow),
+            remaining_monthly_outflow: remaining(limit.max_monthly_outflow, usage.monthly_outflow),
+            remaining_daily_transactions: remaining(
+                limit.max_daily_transactions.map(i64::from),
+                usage.daily_transactions,
+            ),
+            currency: limit.currency,
+        }
+    }
+}
@@ -3,9 +3,35 @@ pub mod transaction;
 pub mod api_key;
 pub mod webhook;
 pub mod enums;
+pub mod journal;
+pub mod hold;
+pub mod batch;
+pub mod import;
+pub mod recurring_transfer;
+pub mod fx_rate;
+pub mod balance_snapshot;
+pub mod statement;
+pub mod reconciliation;
+pub mod fee;
+pub mod limit;
+pub mod risk;
+pub mod freeze;
 
 pub use account::*;
 pub use transaction::*;
 pub use api_key::*;
 pub use webhook::*;
-pub use enums::*;
\ No newline at end of file
+pub use enums::*;
+pub use journal::*;
+pub use hold::*;
+pub use batch::*;
+pub use import::*;
+pub use recurring_transfer::*;
+pub use fx_rate::*;
+pub use balance_snapshot::*;
+pub use statement::*;
+pub use reconciliation::*;
+pub use fee::*;
+pub use limit::*;
+pub use risk::*;
+pub use freeze::*;
\ No newline at end of file
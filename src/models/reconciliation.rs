@@ -0,0 +1,67 @@
+use crate::{models::PostingLeg, utils::currency};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ReconciliationQuery {
+    pub alert: Option<bool>, // Emit account.balance_drift for every drifted wallet
+}
+
+// A wallet whose stored balance disagrees with the sum of its postings.
+// delta = stored_balance - ledger_balance, in minor units.
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletDrift {
+    pub account_id: i64,
+    pub currency: String,
+    pub stored_balance: i64,
+    pub ledger_balance: i64,
+    pub delta: i64,
+    pub delta_decimal: String,
+}
+
+impl WalletDrift {
+    pub fn new(
+        account_id: i64,
+        currency: String,
+        stored_balance: i64,
+        ledger_balance: i64,
+    ) -> Self {
+        let delta = stored_balance - ledger_balance;
+        WalletDrift {
+            delta_decimal: currency::format_amount(delta, &currency),
+            account_id,
+            currency,
+            stored_balance,
+            ledger_balance,
+            delta,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalProblem {
+    MissingEntry,     // Completed without a journal entry
+    DuplicateEntries, // Posted more than once
+    PostingsMismatch, // Postings disagree with the amount, rate or fee
+    UnexpectedEntry,  // Posted although the transaction never completed
+}
+
+// A transaction whose journal entries disagree with it. Both sets of legs are sorted
+// by account, currency and amount.
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalMismatch {
+    pub transaction_id: i64,
+    pub problem: JournalProblem,
+    pub expected_postings: Vec<PostingLeg>,
+    pub postings: Vec<PostingLeg>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReconciliationReport {
+    pub checked_at: NaiveDateTime,
+    pub wallets_checked: usize,
+    pub drifted: Vec<WalletDrift>,
+    pub transactions_checked: usize,
+    pub mismatched: Vec<JournalMismatch>,
+}
@@ -0,0 +1,88 @@
+use crate::models::{
+    CatchUpPolicy, RecurrenceInterval, RecurringRunStatus, RecurringTransferStatus,
+};
+use crate::schema::{recurring_transfer_runs, recurring_transfers};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::{
+    Selectable,
+    prelude::{Insertable, Queryable},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = recurring_transfers)]
+pub struct RecurringTransfer {
+    pub id: i64,
+    pub from_account_id: i64,
+    pub to_account_id: i64,
+    pub amount: i64,
+    pub description: Option<String>,
+    pub interval_unit: Option<RecurrenceInterval>,
+    pub interval_count: i32,
+    pub cron_expression: Option<String>,
+    pub start_at: NaiveDateTime,
+    pub end_at: Option<NaiveDateTime>,
+    pub max_occurrences: Option<i32>,
+    pub occurrences: i32,
+    pub next_run_at: Option<NaiveDateTime>,
+    pub status: RecurringTransferStatus,
+    pub catch_up_policy: CatchUpPolicy,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = recurring_transfers)]
+pub struct NewRecurringTransfer {
+    pub from_account_id: i64,
+    pub to_account_id: i64,
+    pub amount: i64,
+    pub description: Option<String>,
+    pub interval_unit: Option<RecurrenceInterval>,
+    pub interval_count: i32,
+    pub cron_expression: Option<String>,
+    pub start_at: NaiveDateTime,
+    pub end_at: Option<NaiveDateTime>,
+    pub max_occurrences: Option<i32>,
+    pub next_run_at: Option<NaiveDateTime>,
+    pub catch_up_policy: CatchUpPolicy,
+}
+
+// Either `interval` (every `interval_count` hours/days/weeks/months from `start_at`)
+// or a `cron` expression must be given
+#[derive(Debug, Deserialize)]
+pub struct CreateRecurringTransferRequest {
+    pub from_account_id: i64,
+    pub to_account_id: i64,
+    pub amount: i64,
+    pub description: Option<String>,
+    pub interval: Option<RecurrenceInterval>,
+    pub interval_count: Option<i32>, // Defaults to 1
+    pub cron: Option<String>,
+    pub start_at: Option<DateTime<Utc>>, // Defaults to now
+    pub end_at: Option<DateTime<Utc>>,
+    pub max_occurrences: Option<i32>,
+    pub catch_up_policy: Option<CatchUpPolicy>, // Defaults to RECURRING_CATCH_UP_POLICY
+}
+
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = recurring_transfer_runs)]
+pub struct RecurringTransferRun {
+    pub id: i64,
+    pub recurring_transfer_id: i64,
+    pub scheduled_for: NaiveDateTime,
+    pub status: RecurringRunStatus,
+    pub transaction_id: Option<i64>,
+    pub error: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = recurring_transfer_runs)]
+pub struct NewRecurringTransferRun {
+    pub recurring_transfer_id: i64,
+    pub scheduled_for: NaiveDateTime,
+    pub status: RecurringRunStatus,
+    pub transaction_id: Option<i64>,
+    pub error: Option<String>,
+}
@@ -0,0 +1,174 @@
+use crate::models::{RiskOutcome, RiskReviewStatus, TransactionResponse};
+use crate::schema::{risk_reviews, risk_rules};
+use chrono::NaiveDateTime;
+use diesel::{
+    Selectable,
+    prelude::{AsChangeset, Insertable, Queryable},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = risk_rules)]
+pub struct RiskRule {
+    pub id: i64,
+    pub name: String,
+    pub currency: Option<String>,
+    pub definition: serde_json::Value,
+    pub outcome: RiskOutcome,
+    pub is_active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = risk_rules)]
+pub struct NewRiskRule {
+    pub name: String,
+    pub currency: Option<String>,
+    pub definition: serde_json::Value,
+    pub outcome: RiskOutcome,
+}
+
+// What a rule looks for in a new debit or transfer, stored as JSON tagged by "type".
+// Amounts are in minor units of the transaction's currency.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum RiskCondition {
+    // A paying account opened less than `account_age_hours` ago sending more than
+    // `max_outflow` in total since it was opened
+    NewAccountOutflow {
+        account_age_hours: i64,
+        max_outflow: i64,
+    },
+    // Transfers to more than `max_recipients` distinct accounts within `window_minutes`
+    DistinctRecipients {
+        window_minutes: i64,
+        max_recipients: i64,
+    },
+    // An amount of at least `min_amount` that is a multiple of `multiple_of`
+    RoundAmount {
+        min_amount: i64,
+        multiple_of: i64,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRiskRuleRequest {
+    pub name: String,
+    pub currency: Option<String>, // Omitted to check transactions in every currency
+    pub definition: RiskCondition,
+    pub outcome: RiskOutcome, // review or block
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateRiskRuleRequest {
+    pub definition: Option<RiskCondition>,
+    pub outcome: Option<RiskOutcome>,
+    pub is_active: Option<bool>,
+}
+
+// Fields left as None are not changed
+#[derive(Debug, Default, AsChangeset)]
+#[diesel(table_name = risk_rules)]
+pub struct RiskRuleChanges {
+    pub definition: Option<serde_json::Value>,
+    pub outcome: Option<RiskOutcome>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RiskRuleResponse {
+    pub id: i64,
+    pub name: String,
+    pub currency: Option<String>,
+    pub definition: serde_json::Value,
+    pub outcome: RiskOutcome,
+    pub is_active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<RiskRule> for RiskRuleResponse {
+    fn from(rule: RiskRule) -> Self {
+        RiskRuleResponse {
+            id: rule.id,
+            name: rule.name,
+            currency: rule.currency,
+            definition: rule.definition,
+            outcome: rule.outcome,
+            is_active: rule.is_active,
+            created_at: rule.created_at,
+            updated_at: rule.updated_at,
+        }
+    }
+}
+
+// A rule that matched a transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchedRiskRule {
+    pub id: i64,
+    pub name: String,
+    pub outcome: RiskOutcome,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = risk_reviews)]
+pub struct RiskReview {
+    pub id: i64,
+    pub transaction_id: i64,
+    pub matched_rules: serde_json::Value,
+    pub capture: bool,
+    pub status: RiskReviewStatus,
+    pub reviewer_api_key_id: Option<i64>,
+    pub reason: Option<String>,
+    pub decided_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = risk_reviews)]
+pub struct NewRiskReview {
+    pub transaction_id: i64,
+    pub matched_rules: serde_json::Value,
+    pub capture: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListRiskReviewsQuery {
+    pub status: Option<RiskReviewStatus>, // Defaults to pending
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DecideRiskReviewRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RiskReviewResponse {
+    pub id: i64,
+    pub status: RiskReviewStatus,
+    pub matched_rules: Vec<MatchedRiskRule>,
+    pub reviewer_api_key_id: Option<i64>,
+    pub reason: Option<String>,
+    pub decided_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub transaction: TransactionResponse,
+}
+
+impl RiskReviewResponse {
+    pub fn new(review: RiskReview, transaction: TransactionResponse) -> Self {
+        RiskReviewResponse {
+            id: review.id,
+            status: review.status,
+            matched_rules: serde_json::from_value(review.matched_rules).unwrap_or_default(),
+            reviewer_api_key_id: review.reviewer_api_key_id,
+            reason: review.reason,
+            decided_at: review.decided_at,
+            created_at: review.created_at,
+            updated_at: review.updated_at,
+            transaction,
+        }
+    }
+}
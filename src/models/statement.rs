@@ -0,0 +1,61 @@
+use crate::models::{EntryDirection, TransactionType};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementFormat {
+    Csv,
+    Json,
+}
+
+impl StatementFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            StatementFormat::Csv => "text/csv",
+            StatementFormat::Json => "application/json",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StatementFormat::Csv => "csv",
+            StatementFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatementQuery {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub format: Option<StatementFormat>, // Defaults to JSON
+    pub currency: Option<String>,        // Wallet to report on, defaults to the account's currency
+}
+
+// A statement covers the postings made to one wallet in (from, to]
+#[derive(Debug, Clone, Serialize)]
+pub struct StatementHeader {
+    pub account_id: i64,
+    pub business_name: String,
+    pub currency: String,
+    pub from: NaiveDateTime,
+    pub to: NaiveDateTime,
+    pub opening_balance: i64,
+    pub opening_balance_decimal: String,
+    #[serde(skip)]
+    pub format: StatementFormat,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatementEntry {
+    pub posted_at: NaiveDateTime,
+    pub transaction_id: i64,
+    pub tx_type: TransactionType,
+    pub direction: EntryDirection,
+    pub description: Option<String>,
+    pub amount: i64, // Signed: negative amounts left the account
+    pub amount_decimal: String,
+    pub balance: i64, // Running balance after the entry
+    pub balance_decimal: String,
+}
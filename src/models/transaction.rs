@@ -1,6 +1,7 @@
-use crate::models::{TransactionStatus, TransactionType};
-use crate::schema::transactions;
-use chrono::NaiveDateTime;
+use crate::models::{EntryDirection, TransactionStatus, TransactionType, format_fx_rate};
+use crate::schema::{transaction_status_history, transactions};
+use crate::utils::currency;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use diesel::{
     Selectable,
     prelude::{Insertable, Queryable},
@@ -20,9 +21,20 @@ pub struct Transaction {
     pub idempotency_key: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub reversal_of_id: Option<i64>,
+    pub refunded_amount: i64,
+    pub batch_id: Option<i64>,
+    pub execute_at: Option<NaiveDateTime>,
+    pub fx_rate: Option<i64>,
+    pub converted_amount: Option<i64>,
+    pub converted_currency: Option<String>,
+    pub currency: String,
+    pub metadata: serde_json::Value,
+    pub fee_amount: i64,
+    pub fee_account_id: Option<i64>,
 }
 
-#[derive(Debug, Insertable)]
+#[derive(Debug, Clone, Insertable)]
 #[diesel(table_name = transactions)]
 pub struct NewTransaction {
     pub from_account_id: Option<i64>,
@@ -32,6 +44,16 @@ pub struct NewTransaction {
     pub status: TransactionStatus,
     pub description: Option<String>,
     pub idempotency_key: Option<String>,
+    pub reversal_of_id: Option<i64>,
+    pub batch_id: Option<i64>,
+    pub execute_at: Option<NaiveDateTime>,
+    pub fx_rate: Option<i64>,
+    pub converted_amount: Option<i64>,
+    pub converted_currency: Option<String>,
+    pub currency: String,
+    pub metadata: serde_json::Value,
+    pub fee_amount: i64,
+    pub fee_account_id: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -39,9 +61,70 @@ pub struct CreateTransactionRequest {
     pub from_account_id: Option<i64>,
     pub to_account_id: Option<i64>,
     pub amount: i64,
+    pub currency: Option<String>, // Defaults to the paying (or, for credits, receiving) account's currency
     pub tx_type: TransactionType,
     pub description: Option<String>,
     pub idempotency_key: Option<String>,
+    pub capture: Option<bool>, // false places a hold instead of moving funds, defaults to true
+    pub execute_at: Option<DateTime<Utc>>, // Schedule the transaction instead of running it now
+    pub convert: Option<bool>, // Required for transfers between accounts in different currencies
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListTransactionsQuery {
+    pub upcoming: Option<bool>, // Only scheduled transactions that have not run yet
+    pub limit: Option<i64>,
+    pub starting_after: Option<i64>, // Id of the last transaction of the previous page
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+    pub tx_type: Option<TransactionType>,
+    pub status: Option<TransactionStatus>,
+    pub min_amount: Option<i64>,
+    pub max_amount: Option<i64>,
+    pub counterparty_account_id: Option<i64>, // The account on the other side of a transfer
+    pub metadata_key: Option<String>,         // Transactions whose metadata has this key...
+    pub metadata_value: Option<String>,       // ...set to this value
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CaptureTransactionRequest {
+    pub amount: Option<i64>, // Defaults to the full authorized amount
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReverseTransactionRequest {
+    pub amount: Option<i64>, // Partial refund, defaults to everything not yet refunded
+    pub description: Option<String>,
+    pub idempotency_key: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CancelTransactionRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = transaction_status_history)]
+pub struct TransactionStatusHistory {
+    pub id: i64,
+    pub transaction_id: i64,
+    pub from_status: Option<TransactionStatus>,
+    pub to_status: TransactionStatus,
+    pub actor_api_key_id: Option<i64>,
+    pub reason: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = transaction_status_history)]
+pub struct NewTransactionStatusHistory {
+    pub transaction_id: i64,
+    pub from_status: Option<TransactionStatus>,
+    pub to_status: TransactionStatus,
+    pub actor_api_key_id: Option<i64>,
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -50,21 +133,79 @@ pub struct TransactionResponse {
     pub from_account_id: Option<i64>,
     pub to_account_id: Option<i64>,
     pub amount: i64,
+    pub amount_decimal: String,
+    pub currency: String,
     pub tx_type: TransactionType,
     pub status: TransactionStatus,
+    pub description: Option<String>,
+    pub idempotency_key: Option<String>,
+    pub metadata: serde_json::Value,
     pub created_at: NaiveDateTime,
+    pub reversal_of_id: Option<i64>,
+    pub refunded_amount: i64,
+    pub refunded_amount_decimal: String,
+    pub batch_id: Option<i64>,
+    pub execute_at: Option<NaiveDateTime>,
+    pub fx_rate: Option<String>,
+    pub converted_amount: Option<i64>,
+    pub converted_amount_decimal: Option<String>,
+    pub converted_currency: Option<String>,
+    pub fee_amount: i64,
+    pub fee_amount_decimal: String,
+    pub fee_account_id: Option<i64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reversals: Vec<TransactionResponse>,
+}
+
+// A transaction as seen from one account. balance_after is the account's balance in the
+// currency it was posted in, right after posting; unset while nothing has been posted.
+#[derive(Debug, Serialize)]
+pub struct AccountTransactionResponse {
+    #[serde(flatten)]
+    pub transaction: TransactionResponse,
+    pub direction: EntryDirection,
+    pub balance_after: Option<i64>,
+    pub balance_after_decimal: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountTransactionListResponse {
+    pub data: Vec<AccountTransactionResponse>,
+    pub has_more: bool,
 }
 
 impl From<Transaction> for TransactionResponse {
     fn from(tx: Transaction) -> Self {
+        let converted_amount_decimal = tx
+            .converted_amount
+            .zip(tx.converted_currency.as_deref())
+            .map(|(amount, code)| currency::format_amount(amount, code));
         TransactionResponse {
             id: tx.id,
             from_account_id: tx.from_account_id,
             to_account_id: tx.to_account_id,
             amount: tx.amount,
+            amount_decimal: currency::format_amount(tx.amount, &tx.currency),
+            refunded_amount_decimal: currency::format_amount(tx.refunded_amount, &tx.currency),
+            fee_amount_decimal: currency::format_amount(tx.fee_amount, &tx.currency),
+            currency: tx.currency,
             tx_type: tx.tx_type,
             status: tx.status,
+            description: tx.description,
+            idempotency_key: tx.idempotency_key,
+            metadata: tx.metadata,
             created_at: tx.created_at,
+            reversal_of_id: tx.reversal_of_id,
+            refunded_amount: tx.refunded_amount,
+            batch_id: tx.batch_id,
+            execute_at: tx.execute_at,
+            fx_rate: tx.fx_rate.map(format_fx_rate),
+            converted_amount: tx.converted_amount,
+            converted_amount_decimal,
+            converted_currency: tx.converted_currency,
+            fee_amount: tx.fee_amount,
+            fee_account_id: tx.fee_account_id,
+            reversals: Vec::new(),
         }
     }
 }
@@ -1,7 +1,14 @@
-use crate::models::{Account, NewAccount};
-use crate::schema::accounts;
+use crate::models::{
+    Account, AccountBalance, AccountChanges, ListAccountsQuery, NewAccount, NewAccountBalance,
+};
+use crate::schema::{account_balances, accounts};
 use crate::utils::app_error::AppError;
+use chrono::{NaiveDateTime, Utc};
+use diesel::dsl::{exists, sql};
+use diesel::pg::Pg;
 use diesel::prelude::*;
+use diesel::result::DatabaseErrorKind;
+use diesel::sql_types::BigInt;
 
 pub fn create_account(
     new_account: &NewAccount,
@@ -20,26 +27,314 @@ pub fn get_account_by_id(id: i64, conn: &mut PgConnection) -> Result<Account, Ap
         .map_err(|_| AppError::AccountNotFound)
 }
 
-pub fn debit_account(id: i64, amount: i64, conn: &mut PgConnection) -> Result<(), AppError> {
-    // Check balance first
-    let account = get_account_by_id(id, conn)?;
-    if account.balance < amount {
-        return Err(AppError::InsufficientBalance);
+// The existing accounts among the given ids
+pub fn get_accounts(ids: &[i64], conn: &mut PgConnection) -> Result<Vec<Account>, AppError> {
+    accounts::table
+        .filter(accounts::id.eq_any(ids))
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+pub fn update_account(
+    id: i64,
+    changes: &AccountChanges,
+    conn: &mut PgConnection,
+) -> Result<Account, AppError> {
+    if changes.business_name.is_none()
+        && changes.currency.is_none()
+        && changes.is_active.is_none()
+        && changes.metadata.is_none()
+    {
+        return get_account_by_id(id, conn);
     }
+    diesel::update(accounts::table.find(id))
+        .set(changes)
+        .get_result(conn)
+        .optional()?
+        .ok_or(AppError::AccountNotFound)
+}
 
+// Deactivate an account for good
+pub fn close_account(id: i64, conn: &mut PgConnection) -> Result<Account, AppError> {
     diesel::update(accounts::table.find(id))
-        .set(accounts::balance.eq(accounts::balance - amount))
-        .execute(conn)
-        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        .set((
+            accounts::is_active.eq(false),
+            accounts::closed_at.eq(Utc::now().naive_utc()),
+        ))
+        .get_result(conn)
+        .map_err(AppError::from)
+}
+
+// Accounts matching the admin listing filters. `currency` must already be normalized.
+fn filtered_accounts<'a>(query: &'a ListAccountsQuery) -> accounts::BoxedQuery<'a, Pg> {
+    let mut sql = accounts::table.into_boxed();
+
+    if let Some(search) = &query.search {
+        sql = sql.filter(accounts::business_name.ilike(format!("%{}%", escape_like(search))));
+    }
+    if let Some(is_active) = query.is_active {
+        sql = sql.filter(accounts::is_active.eq(is_active));
+    }
+    if let Some(currency) = &query.currency {
+        sql = sql.filter(accounts::currency.eq(currency));
+    }
+    if let Some(created_from) = query.created_from {
+        sql = sql.filter(accounts::created_at.ge(created_from.naive_utc()));
+    }
+    if let Some(created_to) = query.created_to {
+        sql = sql.filter(accounts::created_at.le(created_to.naive_utc()));
+    }
+    if let Some(currency) = &query.currency
+        && (query.min_balance.is_some() || query.max_balance.is_some())
+    {
+        let min_balance = query.min_balance.unwrap_or(i64::MIN);
+        let max_balance = query.max_balance.unwrap_or(i64::MAX);
+        sql = sql.filter(exists(
+            account_balances::table
+                .filter(account_balances::account_id.eq(accounts::id))
+                .filter(account_balances::currency.eq(currency))
+                .filter(account_balances::balance.between(min_balance, max_balance)),
+        ));
+    }
+    sql
+}
+
+// One page of the matching accounts, newest first
+pub fn get_accounts_page(
+    query: &ListAccountsQuery,
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<Account>, AppError> {
+    let mut sql = filtered_accounts(query);
+    if let Some(after) = query.starting_after {
+        sql = sql.filter(accounts::id.lt(after));
+    }
+    sql.order(accounts::id.desc())
+        .limit(limit)
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+// (total, active, closed) counts of the matching accounts
+pub fn count_accounts(
+    query: &ListAccountsQuery,
+    conn: &mut PgConnection,
+) -> Result<(i64, i64, i64), AppError> {
+    filtered_accounts(query)
+        .select((
+            sql::<BigInt>("COUNT(*)"),
+            sql::<BigInt>("COUNT(*) FILTER (WHERE is_active)"),
+            sql::<BigInt>("COUNT(*) FILTER (WHERE closed_at IS NOT NULL)"),
+        ))
+        .first(conn)
+        .map_err(AppError::from)
+}
+
+// (currency, wallet count, total balance) of every wallet of the matching accounts
+pub fn sum_balances_by_currency(
+    query: &ListAccountsQuery,
+    conn: &mut PgConnection,
+) -> Result<Vec<(String, i64, i64)>, AppError> {
+    account_balances::table
+        .filter(account_balances::account_id.eq_any(filtered_accounts(query).select(accounts::id)))
+        .group_by(account_balances::currency)
+        .order(account_balances::currency.asc())
+        .select((
+            account_balances::currency,
+            sql::<BigInt>("COUNT(*)"),
+            sql::<BigInt>("COALESCE(SUM(balance), 0)::BIGINT"),
+        ))
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+// Match LIKE wildcards in user input literally
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+// Default currency of each existing account among the given ids
+pub fn get_account_currencies(
+    ids: &[i64],
+    conn: &mut PgConnection,
+) -> Result<Vec<(i64, String)>, AppError> {
+    accounts::table
+        .filter(accounts::id.eq_any(ids))
+        .select((accounts::id, accounts::currency))
+        .load(conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+}
+
+// Lock the given accounts with SELECT ... FOR UPDATE. Rows are always locked in
+// ascending id order so concurrent transactions cannot deadlock on each other.
+pub fn lock_accounts(ids: &[i64], conn: &mut PgConnection) -> Result<Vec<Account>, AppError> {
+    let mut ids = ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+
+    let locked: Vec<Account> = accounts::table
+        .filter(accounts::id.eq_any(&ids))
+        .order(accounts::id.asc())
+        .for_update()
+        .load(conn)
+        .map_err(AppError::from)?;
+
+    if locked.len() != ids.len() {
+        return Err(AppError::AccountNotFound);
+    }
+
+    Ok(locked)
+}
+
+pub fn create_account_balance(
+    new_balance: &NewAccountBalance,
+    conn: &mut PgConnection,
+) -> Result<AccountBalance, AppError> {
+    diesel::insert_into(account_balances::table)
+        .values(new_balance)
+        .get_result(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                AppError::Conflict(format!(
+                    "Account already has a {} wallet",
+                    new_balance.currency
+                ))
+            }
+            e => AppError::from(e),
+        })
+}
+
+pub fn get_account_balance(
+    account_id: i64,
+    currency: &str,
+    conn: &mut PgConnection,
+) -> Result<Option<AccountBalance>, AppError> {
+    account_balances::table
+        .filter(account_balances::account_id.eq(account_id))
+        .filter(account_balances::currency.eq(currency))
+        .first(conn)
+        .optional()
+        .map_err(AppError::from)
+}
+
+pub fn get_account_balances(
+    account_id: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<AccountBalance>, AppError> {
+    account_balances::table
+        .filter(account_balances::account_id.eq(account_id))
+        .order(account_balances::currency.asc())
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+// Stored balance of every wallet: (account_id, currency, balance)
+pub fn get_all_wallet_balances(
+    conn: &mut PgConnection,
+) -> Result<Vec<(i64, String, i64)>, AppError> {
+    account_balances::table
+        .order(account_balances::id.asc())
+        .select((
+            account_balances::account_id,
+            account_balances::currency,
+            account_balances::balance,
+        ))
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+// Wallets opened up to `created_before`, in id order after `after_id`
+pub fn get_account_balances_page(
+    after_id: i64,
+    created_before: NaiveDateTime,
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<AccountBalance>, AppError> {
+    account_balances::table
+        .filter(account_balances::id.gt(after_id))
+        .filter(account_balances::created_at.le(created_before))
+        .order(account_balances::id.asc())
+        .limit(limit)
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+pub fn debit_account(
+    id: i64,
+    currency: &str,
+    amount: i64,
+    conn: &mut PgConnection,
+) -> Result<(), AppError> {
+    // Check and update the balance in a single statement. The wallet may go
+    // negative down to its overdraft limit.
+    let updated = diesel::update(
+        account_balances::table
+            .filter(account_balances::account_id.eq(id))
+            .filter(account_balances::currency.eq(currency))
+            .filter((account_balances::balance + account_balances::overdraft_limit).ge(amount)),
+    )
+    .set(account_balances::balance.eq(account_balances::balance - amount))
+    .execute(conn)
+    .map_err(AppError::from)?;
+
+    if updated == 0 {
+        if get_account_balance(id, currency, conn)?.is_none() {
+            return Err(AppError::WalletNotFound {
+                account_id: id,
+                currency: currency.to_string(),
+            });
+        }
+        return Err(AppError::InsufficientBalance);
+    }
 
     Ok(())
 }
 
-pub fn credit_account(id: i64, amount: i64, conn: &mut PgConnection) -> Result<(), AppError> {
-    diesel::update(accounts::table.find(id))
-        .set(accounts::balance.eq(accounts::balance + amount))
-        .execute(conn)
-        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+pub fn set_overdraft_limit(
+    account_id: i64,
+    currency: &str,
+    overdraft_limit: i64,
+    conn: &mut PgConnection,
+) -> Result<AccountBalance, AppError> {
+    diesel::update(
+        account_balances::table
+            .filter(account_balances::account_id.eq(account_id))
+            .filter(account_balances::currency.eq(currency)),
+    )
+    .set(account_balances::overdraft_limit.eq(overdraft_limit))
+    .get_result(conn)
+    .optional()
+    .map_err(AppError::from)?
+    .ok_or_else(|| AppError::WalletNotFound {
+        account_id,
+        currency: currency.to_string(),
+    })
+}
+
+pub fn credit_account(
+    id: i64,
+    currency: &str,
+    amount: i64,
+    conn: &mut PgConnection,
+) -> Result<(), AppError> {
+    let updated = diesel::update(
+        account_balances::table
+            .filter(account_balances::account_id.eq(id))
+            .filter(account_balances::currency.eq(currency)),
+    )
+    .set(account_balances::balance.eq(account_balances::balance + amount))
+    .execute(conn)
+    .map_err(AppError::from)?;
+
+    if updated == 0 {
+        return Err(AppError::WalletNotFound {
+            account_id: id,
+            currency: currency.to_string(),
+        });
+    }
 
     Ok(())
 }
@@ -19,6 +19,13 @@ pub fn get_api_key_by_hash(hash: &str, conn: &mut PgConnection) -> Result<ApiKey
         .map_err(|_| AppError::InvalidApiKey)
 }
 
+pub fn get_api_key_by_id(id: i64, conn: &mut PgConnection) -> Result<ApiKey, AppError> {
+    api_keys::table
+        .find(id)
+        .first(conn)
+        .map_err(|_| AppError::NotFound)
+}
+
 pub fn update_last_used(id: i64, conn: &mut PgConnection) -> Result<(), AppError> {
     diesel::update(api_keys::table.find(id))
         .set(api_keys::last_used_at.eq(Utc::now().naive_utc()))
@@ -78,3 +85,18 @@ pub fn update_api_key(
         .first(conn)
         .map_err(|e| AppError::DatabaseError(e.to_string()))
 }
+
+// Deactivate every key of an account. Returns how many were active.
+pub fn deactivate_api_keys_by_account(
+    account_id: i64,
+    conn: &mut PgConnection,
+) -> Result<usize, AppError> {
+    diesel::update(
+        api_keys::table
+            .filter(api_keys::account_id.eq(account_id))
+            .filter(api_keys::is_active.eq(true)),
+    )
+    .set(api_keys::is_active.eq(false))
+    .execute(conn)
+    .map_err(AppError::from)
+}
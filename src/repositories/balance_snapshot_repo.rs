@@ -0,0 +1,39 @@
+use crate::models::{BalanceSnapshot, NewBalanceSnapshot};
+use crate::schema::balance_snapshots;
+use crate::utils::app_error::AppError;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+// Returns 0 when the wallet already has a snapshot at that time
+pub fn create_balance_snapshot(
+    new_snapshot: &NewBalanceSnapshot,
+    conn: &mut PgConnection,
+) -> Result<usize, AppError> {
+    diesel::insert_into(balance_snapshots::table)
+        .values(new_snapshot)
+        .on_conflict((
+            balance_snapshots::account_id,
+            balance_snapshots::currency,
+            balance_snapshots::as_of,
+        ))
+        .do_nothing()
+        .execute(conn)
+        .map_err(AppError::from)
+}
+
+// The most recent snapshot of a wallet taken at or before `as_of`
+pub fn get_latest_balance_snapshot(
+    account_id: i64,
+    currency: &str,
+    as_of: NaiveDateTime,
+    conn: &mut PgConnection,
+) -> Result<Option<BalanceSnapshot>, AppError> {
+    balance_snapshots::table
+        .filter(balance_snapshots::account_id.eq(account_id))
+        .filter(balance_snapshots::currency.eq(currency))
+        .filter(balance_snapshots::as_of.le(as_of))
+        .order(balance_snapshots::as_of.desc())
+        .first(conn)
+        .optional()
+        .map_err(AppError::from)
+}
@@ -0,0 +1,71 @@
+use crate::models::{FeeSchedule, NewFeeSchedule, TransactionType};
+use crate::schema::fee_schedules;
+use crate::utils::app_error::AppError;
+use diesel::prelude::*;
+use diesel::result::DatabaseErrorKind;
+
+pub fn create_fee_schedule(
+    new_schedule: &NewFeeSchedule,
+    conn: &mut PgConnection,
+) -> Result<FeeSchedule, AppError> {
+    diesel::insert_into(fee_schedules::table)
+        .values(new_schedule)
+        .get_result(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                AppError::Conflict(
+                    "A fee schedule for this account, transaction type and currency already exists"
+                        .to_string(),
+                )
+            }
+            e => AppError::from(e),
+        })
+}
+
+// Schedules of one account, or every schedule when no account is given
+pub fn list_fee_schedules(
+    account_id: Option<i64>,
+    conn: &mut PgConnection,
+) -> Result<Vec<FeeSchedule>, AppError> {
+    let mut query = fee_schedules::table.into_boxed();
+    if let Some(account_id) = account_id {
+        query = query.filter(fee_schedules::account_id.eq(account_id));
+    }
+    query
+        .order(fee_schedules::id.asc())
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+// The schedule that prices a transaction: the paying account's own one if it has
+// one, otherwise the global one
+pub fn get_applicable_fee_schedule(
+    account_id: i64,
+    tx_type: TransactionType,
+    currency: &str,
+    conn: &mut PgConnection,
+) -> Result<Option<FeeSchedule>, AppError> {
+    fee_schedules::table
+        .filter(
+            fee_schedules::account_id
+                .eq(account_id)
+                .or(fee_schedules::account_id.is_null()),
+        )
+        .filter(fee_schedules::tx_type.eq(tx_type))
+        .filter(fee_schedules::currency.eq(currency))
+        .order(fee_schedules::account_id.asc().nulls_last())
+        .first(conn)
+        .optional()
+        .map_err(AppError::from)
+}
+
+pub fn delete_fee_schedule(id: i64, conn: &mut PgConnection) -> Result<(), AppError> {
+    let deleted = diesel::delete(fee_schedules::table.find(id))
+        .execute(conn)
+        .map_err(AppError::from)?;
+
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use crate::models::{AccountFreeze, NewAccountFreeze};
+use crate::schema::account_freezes;
+use crate::utils::app_error::AppError;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::result::DatabaseErrorKind;
+
+pub fn create_account_freeze(
+    new_freeze: &NewAccountFreeze,
+    conn: &mut PgConnection,
+) -> Result<AccountFreeze, AppError> {
+    diesel::insert_into(account_freezes::table)
+        .values(new_freeze)
+        .get_result(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                AppError::Conflict("Account is already frozen".to_string())
+            }
+            e => AppError::from(e),
+        })
+}
+
+// Active freezes among the given accounts
+pub fn get_active_freezes(
+    account_ids: &[i64],
+    conn: &mut PgConnection,
+) -> Result<Vec<AccountFreeze>, AppError> {
+    account_freezes::table
+        .filter(account_freezes::account_id.eq_any(account_ids))
+        .filter(account_freezes::lifted_at.is_null())
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+// Lift the active freeze of an account, if it has one
+pub fn lift_account_freeze(
+    account_id: i64,
+    lifted_by_api_key_id: i64,
+    lift_reason: Option<String>,
+    conn: &mut PgConnection,
+) -> Result<Option<AccountFreeze>, AppError> {
+    diesel::update(
+        account_freezes::table
+            .filter(account_freezes::account_id.eq(account_id))
+            .filter(account_freezes::lifted_at.is_null()),
+    )
+    .set((
+        account_freezes::lifted_by_api_key_id.eq(lifted_by_api_key_id),
+        account_freezes::lift_reason.eq(lift_reason),
+        account_freezes::lifted_at.eq(Utc::now().naive_utc()),
+    ))
+    .get_result(conn)
+    .optional()
+    .map_err(AppError::from)
+}
+
+// Every freeze an account has had, newest first
+pub fn list_account_freezes(
+    account_id: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<AccountFreeze>, AppError> {
+    account_freezes::table
+        .filter(account_freezes::account_id.eq(account_id))
+        .order(account_freezes::id.desc())
+        .load(conn)
+        .map_err(AppError::from)
+}
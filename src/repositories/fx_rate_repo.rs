@@ -0,0 +1,50 @@
+use crate::models::{FxRate, NewFxRate};
+use crate::schema::fx_rates;
+use crate::utils::app_error::AppError;
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+
+// Insert a rate, or replace the current rate of the same currency pair
+pub fn upsert_fx_rate(new_rate: &NewFxRate, conn: &mut PgConnection) -> Result<FxRate, AppError> {
+    diesel::insert_into(fx_rates::table)
+        .values(new_rate)
+        .on_conflict((fx_rates::base_currency, fx_rates::quote_currency))
+        .do_update()
+        .set(fx_rates::rate.eq(excluded(fx_rates::rate)))
+        .get_result(conn)
+        .map_err(AppError::from)
+}
+
+pub fn get_fx_rate(
+    base_currency: &str,
+    quote_currency: &str,
+    conn: &mut PgConnection,
+) -> Result<Option<FxRate>, AppError> {
+    fx_rates::table
+        .filter(fx_rates::base_currency.eq(base_currency))
+        .filter(fx_rates::quote_currency.eq(quote_currency))
+        .first(conn)
+        .optional()
+        .map_err(AppError::from)
+}
+
+pub fn list_fx_rates(conn: &mut PgConnection) -> Result<Vec<FxRate>, AppError> {
+    fx_rates::table
+        .order((
+            fx_rates::base_currency.asc(),
+            fx_rates::quote_currency.asc(),
+        ))
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+pub fn delete_fx_rate(id: i64, conn: &mut PgConnection) -> Result<(), AppError> {
+    let deleted = diesel::delete(fx_rates::table.find(id))
+        .execute(conn)
+        .map_err(AppError::from)?;
+
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+    Ok(())
+}
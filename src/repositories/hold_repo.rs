@@ -0,0 +1,115 @@
+use crate::models::{Hold, NewHold};
+use crate::schema::holds;
+use crate::utils::app_error::AppError;
+use chrono::{NaiveDateTime, Utc};
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+
+pub fn create_hold(new_hold: &NewHold, conn: &mut PgConnection) -> Result<Hold, AppError> {
+    diesel::insert_into(holds::table)
+        .values(new_hold)
+        .get_result(conn)
+        .map_err(AppError::from)
+}
+
+pub fn get_active_hold_by_transaction(
+    transaction_id: i64,
+    conn: &mut PgConnection,
+) -> Result<Option<Hold>, AppError> {
+    holds::table
+        .filter(holds::transaction_id.eq(transaction_id))
+        .filter(holds::released_at.is_null())
+        .first(conn)
+        .optional()
+        .map_err(AppError::from)
+}
+
+pub fn release_hold(id: i64, conn: &mut PgConnection) -> Result<(), AppError> {
+    diesel::update(holds::table.find(id))
+        .set(holds::released_at.eq(Utc::now().naive_utc()))
+        .execute(conn)
+        .map_err(AppError::from)?;
+    Ok(())
+}
+
+// Release a compliance hold, recording who released it and why
+pub fn release_compliance_hold(
+    id: i64,
+    released_by_api_key_id: i64,
+    release_reason: Option<String>,
+    conn: &mut PgConnection,
+) -> Result<Hold, AppError> {
+    diesel::update(holds::table.find(id))
+        .set((
+            holds::released_at.eq(Utc::now().naive_utc()),
+            holds::released_by_api_key_id.eq(released_by_api_key_id),
+            holds::release_reason.eq(release_reason),
+        ))
+        .get_result(conn)
+        .map_err(AppError::from)
+}
+
+pub fn get_hold_for_update(id: i64, conn: &mut PgConnection) -> Result<Hold, AppError> {
+    holds::table
+        .find(id)
+        .for_update()
+        .first(conn)
+        .optional()?
+        .ok_or(AppError::NotFound)
+}
+
+// Unreleased, unexpired holds of an account, oldest first
+pub fn get_active_holds(account_id: i64, conn: &mut PgConnection) -> Result<Vec<Hold>, AppError> {
+    let now = Utc::now().naive_utc();
+    holds::table
+        .filter(holds::account_id.eq(account_id))
+        .filter(holds::released_at.is_null())
+        .filter(holds::expires_at.is_null().or(holds::expires_at.gt(now)))
+        .order(holds::id.asc())
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+// Total amount reserved by unreleased, unexpired holds on one wallet of an account
+pub fn get_held_amount(
+    account_id: i64,
+    currency: &str,
+    conn: &mut PgConnection,
+) -> Result<i64, AppError> {
+    get_held_amount_as_of(account_id, currency, Utc::now().naive_utc(), conn)
+}
+
+// Total amount reserved on a wallet at a point in time
+pub fn get_held_amount_as_of(
+    account_id: i64,
+    currency: &str,
+    as_of: NaiveDateTime,
+    conn: &mut PgConnection,
+) -> Result<i64, AppError> {
+    holds::table
+        .filter(holds::account_id.eq(account_id))
+        .filter(holds::currency.eq(currency))
+        .filter(holds::created_at.le(as_of))
+        .filter(
+            holds::released_at
+                .is_null()
+                .or(holds::released_at.gt(as_of)),
+        )
+        .filter(holds::expires_at.is_null().or(holds::expires_at.gt(as_of)))
+        .select(sql::<BigInt>("COALESCE(SUM(amount), 0)::BIGINT"))
+        .first(conn)
+        .map_err(AppError::from)
+}
+
+// Expired authorization holds; compliance holds simply stop counting once they expire
+pub fn get_expired_holds(limit: i64, conn: &mut PgConnection) -> Result<Vec<Hold>, AppError> {
+    holds::table
+        .filter(holds::transaction_id.is_not_null())
+        .filter(holds::released_at.is_null())
+        .filter(holds::expires_at.le(Utc::now().naive_utc()))
+        .order(holds::expires_at.asc())
+        .limit(limit)
+        .load(conn)
+        .map_err(AppError::from)
+}
@@ -0,0 +1,240 @@
+use crate::models::{JournalEntry, NewJournalEntry, NewPosting, Posting, PostingLeg, Transaction};
+use crate::repositories::{credit_account, debit_account};
+use crate::schema::{account_balances, journal_entries, postings, transactions};
+use crate::utils::app_error::AppError;
+use chrono::NaiveDateTime;
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use std::collections::HashMap;
+
+// Write a balanced journal entry for a transaction and update the cached
+// balance of every account it touches.
+pub fn create_journal_entry(
+    transaction_id: i64,
+    description: Option<String>,
+    legs: &[PostingLeg],
+    conn: &mut PgConnection,
+) -> Result<JournalEntry, AppError> {
+    // Each currency must balance on its own
+    let mut totals: HashMap<&str, i64> = HashMap::new();
+    for leg in legs {
+        *totals.entry(leg.currency.as_str()).or_default() += leg.amount;
+    }
+    if totals.values().any(|total| *total != 0) {
+        return Err(AppError::InternalError(format!(
+            "Unbalanced journal entry for transaction {}",
+            transaction_id
+        )));
+    }
+
+    let entry: JournalEntry = diesel::insert_into(journal_entries::table)
+        .values(&NewJournalEntry {
+            transaction_id,
+            description,
+        })
+        .get_result(conn)
+        .map_err(AppError::from)?;
+
+    let new_postings: Vec<NewPosting> = legs
+        .iter()
+        .map(|leg| NewPosting {
+            journal_entry_id: entry.id,
+            account_id: leg.account_id,
+            amount: leg.amount,
+            currency: leg.currency.clone(),
+        })
+        .collect();
+
+    // All legs go in one statement so the deferred balance check sees the whole entry
+    diesel::insert_into(postings::table)
+        .values(&new_postings)
+        .execute(conn)
+        .map_err(AppError::from)?;
+
+    // account_balances is a cache of the postings
+    for leg in legs {
+        if let Some(account_id) = leg.account_id {
+            if leg.amount < 0 {
+                debit_account(account_id, &leg.currency, -leg.amount, conn)?;
+            } else {
+                credit_account(account_id, &leg.currency, leg.amount, conn)?;
+            }
+        }
+    }
+
+    Ok(entry)
+}
+
+// Net amount posted to one wallet in the window (after, until]
+pub fn sum_postings(
+    account_id: i64,
+    currency: &str,
+    after: Option<NaiveDateTime>,
+    until: NaiveDateTime,
+    conn: &mut PgConnection,
+) -> Result<i64, AppError> {
+    let mut query = postings::table
+        .filter(postings::account_id.eq(account_id))
+        .filter(postings::currency.eq(currency))
+        .filter(postings::created_at.le(until))
+        .into_boxed();
+    if let Some(after) = after {
+        query = query.filter(postings::created_at.gt(after));
+    }
+    query
+        .select(sql::<BigInt>("COALESCE(SUM(amount), 0)::BIGINT"))
+        .first(conn)
+        .map_err(AppError::from)
+}
+
+// Every journal entry as (journal_entry_id, transaction_id)
+pub fn get_all_journal_entry_ids(conn: &mut PgConnection) -> Result<Vec<(i64, i64)>, AppError> {
+    journal_entries::table
+        .select((journal_entries::id, journal_entries::transaction_id))
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+// Every posting as (journal_entry_id, leg)
+pub fn get_all_posting_legs(conn: &mut PgConnection) -> Result<Vec<(i64, PostingLeg)>, AppError> {
+    let rows: Vec<(i64, Option<i64>, String, i64)> = postings::table
+        .select((
+            postings::journal_entry_id,
+            postings::account_id,
+            postings::currency,
+            postings::amount,
+        ))
+        .load(conn)
+        .map_err(AppError::from)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(entry_id, account_id, currency, amount)| {
+            (
+                entry_id,
+                PostingLeg {
+                    account_id,
+                    currency,
+                    amount,
+                },
+            )
+        })
+        .collect())
+}
+
+// Net amount posted to every wallet that has postings: (account_id, currency, total)
+pub fn sum_postings_by_wallet(
+    conn: &mut PgConnection,
+) -> Result<Vec<(i64, String, i64)>, AppError> {
+    postings::table
+        .filter(postings::account_id.is_not_null())
+        .group_by((postings::account_id, postings::currency))
+        .select((
+            postings::account_id.assume_not_null(),
+            postings::currency,
+            sql::<BigInt>("SUM(amount)::BIGINT"),
+        ))
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+// Postings made to one wallet in the window (from, to], in posting order, with the
+// transaction each belongs to. Pages continue after the (created_at, id) of `after`.
+pub fn get_wallet_postings_page(
+    account_id: i64,
+    currency: &str,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+    after: Option<(NaiveDateTime, i64)>,
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<(Posting, Transaction)>, AppError> {
+    let mut query = postings::table
+        .inner_join(journal_entries::table.inner_join(transactions::table))
+        .filter(postings::account_id.eq(account_id))
+        .filter(postings::currency.eq(currency))
+        .filter(postings::created_at.gt(from))
+        .filter(postings::created_at.le(to))
+        .into_boxed();
+    if let Some((created_at, id)) = after {
+        query = query.filter(
+            postings::created_at
+                .gt(created_at)
+                .or(postings::created_at.eq(created_at).and(postings::id.gt(id))),
+        );
+    }
+    query
+        .order((postings::created_at.asc(), postings::id.asc()))
+        .limit(limit)
+        .select((Posting::as_select(), Transaction::as_select()))
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+// The postings the given transactions made to the account:
+// (transaction_id, currency, posting_id)
+pub fn get_account_postings_for_transactions(
+    account_id: i64,
+    transaction_ids: &[i64],
+    conn: &mut PgConnection,
+) -> Result<Vec<(i64, String, i64)>, AppError> {
+    postings::table
+        .inner_join(journal_entries::table)
+        .filter(postings::account_id.eq(account_id))
+        .filter(journal_entries::transaction_id.eq_any(transaction_ids))
+        .order(postings::id.asc())
+        .select((
+            journal_entries::transaction_id,
+            postings::currency,
+            postings::id,
+        ))
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+// Balance of a wallet right after one of its postings: the current balance minus
+// everything posted since, read in a single statement
+pub fn get_balance_after_posting(
+    account_id: i64,
+    currency: &str,
+    posting_id: i64,
+    conn: &mut PgConnection,
+) -> Result<i64, AppError> {
+    account_balances::table
+        .filter(account_balances::account_id.eq(account_id))
+        .filter(account_balances::currency.eq(currency))
+        .select(
+            sql::<BigInt>(
+                "balance - COALESCE((SELECT SUM(p.amount) FROM postings p \
+                 WHERE p.account_id = account_balances.account_id \
+                 AND p.currency = account_balances.currency AND p.id > ",
+            )
+            .bind::<BigInt, _>(posting_id)
+            .sql("), 0)::BIGINT"),
+        )
+        .first(conn)
+        .map_err(AppError::from)
+}
+
+pub fn get_journal_entries_by_transaction(
+    transaction_id: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<JournalEntry>, AppError> {
+    journal_entries::table
+        .filter(journal_entries::transaction_id.eq(transaction_id))
+        .order(journal_entries::id.asc())
+        .load(conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+}
+
+pub fn get_postings_by_journal_entry(
+    journal_entry_id: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<Posting>, AppError> {
+    postings::table
+        .filter(postings::journal_entry_id.eq(journal_entry_id))
+        .order(postings::id.asc())
+        .load(conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+}
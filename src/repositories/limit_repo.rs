@@ -0,0 +1,105 @@
+use crate::models::{
+    LimitScope, NewTransactionLimit, TransactionLimit, TransactionStatus, TransactionType,
+};
+use crate::schema::{transaction_limits, transaction_status_history, transactions};
+use crate::utils::app_error::AppError;
+use chrono::NaiveDateTime;
+use diesel::dsl::{count_star, sql};
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+
+// Insert the limits of a scope and currency, or replace the current ones
+pub fn upsert_transaction_limit(
+    new_limit: &NewTransactionLimit,
+    conn: &mut PgConnection,
+) -> Result<TransactionLimit, AppError> {
+    diesel::insert_into(transaction_limits::table)
+        .values(new_limit)
+        .on_conflict((
+            transaction_limits::account_id,
+            transaction_limits::api_key_id,
+            transaction_limits::currency,
+        ))
+        .do_update()
+        .set(new_limit)
+        .get_result(conn)
+        .map_err(AppError::from)
+}
+
+pub fn get_transaction_limits(
+    scope: LimitScope,
+    scope_id: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<TransactionLimit>, AppError> {
+    let query = match scope {
+        LimitScope::Account => transaction_limits::table
+            .filter(transaction_limits::account_id.eq(scope_id))
+            .into_boxed(),
+        LimitScope::ApiKey => transaction_limits::table
+            .filter(transaction_limits::api_key_id.eq(scope_id))
+            .into_boxed(),
+    };
+    query
+        .order(transaction_limits::currency.asc())
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+pub fn get_transaction_limit(
+    scope: LimitScope,
+    scope_id: i64,
+    currency: &str,
+    conn: &mut PgConnection,
+) -> Result<Option<TransactionLimit>, AppError> {
+    let query = match scope {
+        LimitScope::Account => transaction_limits::table
+            .filter(transaction_limits::account_id.eq(scope_id))
+            .into_boxed(),
+        LimitScope::ApiKey => transaction_limits::table
+            .filter(transaction_limits::api_key_id.eq(scope_id))
+            .into_boxed(),
+    };
+    query
+        .filter(transaction_limits::currency.eq(currency))
+        .first(conn)
+        .optional()
+        .map_err(AppError::from)
+}
+
+// Amount (fees included) and number of the debits and transfers paid from an account,
+// or created with an API key, since `since`. Failed and cancelled transactions and
+// reversals do not count; pending ones do, as their funds are spoken for.
+pub fn get_outflow_usage(
+    scope: LimitScope,
+    scope_id: i64,
+    currency: &str,
+    since: NaiveDateTime,
+    conn: &mut PgConnection,
+) -> Result<(i64, i64), AppError> {
+    let outflows = transactions::table
+        .filter(transactions::tx_type.eq_any([TransactionType::Debit, TransactionType::Transfer]))
+        .filter(
+            transactions::status.ne_all([TransactionStatus::Failed, TransactionStatus::Cancelled]),
+        )
+        .filter(transactions::reversal_of_id.is_null())
+        .filter(transactions::currency.eq(currency))
+        .filter(transactions::created_at.ge(since));
+    let totals = (
+        sql::<BigInt>("COALESCE(SUM(transactions.amount + transactions.fee_amount), 0)::BIGINT"),
+        count_star(),
+    );
+
+    match scope {
+        LimitScope::Account => outflows
+            .filter(transactions::from_account_id.eq(scope_id))
+            .select(totals)
+            .first(conn),
+        LimitScope::ApiKey => outflows
+            .inner_join(transaction_status_history::table)
+            .filter(transaction_status_history::from_status.is_null())
+            .filter(transaction_status_history::actor_api_key_id.eq(scope_id))
+            .select(totals)
+            .first(conn),
+    }
+    .map_err(AppError::from)
+}
@@ -2,8 +2,26 @@ pub mod account_repo;
 pub mod transaction_repo;
 pub mod api_key_repo;
 pub mod webhook_repo;
+pub mod journal_repo;
+pub mod hold_repo;
+pub mod recurring_transfer_repo;
+pub mod fx_rate_repo;
+pub mod balance_snapshot_repo;
+pub mod fee_schedule_repo;
+pub mod limit_repo;
+pub mod risk_repo;
+pub mod freeze_repo;
 
 pub use account_repo::*;
 pub use transaction_repo::*;
 pub use api_key_repo::*;
-pub use webhook_repo::*;
\ No newline at end of file
+pub use webhook_repo::*;
+pub use journal_repo::*;
+pub use hold_repo::*;
+pub use recurring_transfer_repo::*;
+pub use fx_rate_repo::*;
+pub use balance_snapshot_repo::*;
+pub use fee_schedule_repo::*;
+pub use limit_repo::*;
+pub use risk_repo::*;
+pub use freeze_repo::*;
\ No newline at end of file
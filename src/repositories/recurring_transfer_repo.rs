@@ -0,0 +1,132 @@
+use crate::models::{
+    NewRecurringTransfer, NewRecurringTransferRun, RecurringTransfer, RecurringTransferRun,
+    RecurringTransferStatus,
+};
+use crate::schema::{recurring_transfer_runs, recurring_transfers};
+use crate::utils::app_error::AppError;
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+
+pub fn create_recurring_transfer(
+    new_transfer: &NewRecurringTransfer,
+    conn: &mut PgConnection,
+) -> Result<RecurringTransfer, AppError> {
+    diesel::insert_into(recurring_transfers::table)
+        .values(new_transfer)
+        .get_result(conn)
+        .map_err(AppError::from)
+}
+
+pub fn get_recurring_transfer_by_id(
+    id: i64,
+    conn: &mut PgConnection,
+) -> Result<RecurringTransfer, AppError> {
+    recurring_transfers::table
+        .find(id)
+        .first(conn)
+        .map_err(|_| AppError::NotFound)
+}
+
+// Lock a schedule so the scheduler and pause/resume requests do not interleave
+pub fn get_recurring_transfer_for_update(
+    id: i64,
+    conn: &mut PgConnection,
+) -> Result<RecurringTransfer, AppError> {
+    recurring_transfers::table
+        .find(id)
+        .for_update()
+        .first(conn)
+        .optional()
+        .map_err(AppError::from)?
+        .ok_or(AppError::NotFound)
+}
+
+pub fn get_recurring_transfers_by_account(
+    account_id: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<RecurringTransfer>, AppError> {
+    recurring_transfers::table
+        .filter(recurring_transfers::from_account_id.eq(account_id))
+        .order(recurring_transfers::created_at.desc())
+        .load(conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+}
+
+// Ids of active schedules with an occurrence due, soonest first
+pub fn get_due_recurring_transfer_ids(
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<i64>, AppError> {
+    recurring_transfers::table
+        .filter(recurring_transfers::status.eq(RecurringTransferStatus::Active))
+        .filter(recurring_transfers::next_run_at.le(Utc::now().naive_utc()))
+        .order(recurring_transfers::next_run_at.asc())
+        .select(recurring_transfers::id)
+        .limit(limit)
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+pub fn update_recurring_transfer_schedule(
+    id: i64,
+    status: RecurringTransferStatus,
+    next_run_at: Option<NaiveDateTime>,
+    occurrences: i32,
+    conn: &mut PgConnection,
+) -> Result<RecurringTransfer, AppError> {
+    diesel::update(recurring_transfers::table.find(id))
+        .set((
+            recurring_transfers::status.eq(status),
+            recurring_transfers::next_run_at.eq(next_run_at),
+            recurring_transfers::occurrences.eq(occurrences),
+        ))
+        .get_result(conn)
+        .map_err(AppError::from)
+}
+
+// Cancel the active and paused schedules paying from or to an account. Returns how
+// many were cancelled.
+pub fn cancel_recurring_transfers_by_account(
+    account_id: i64,
+    conn: &mut PgConnection,
+) -> Result<usize, AppError> {
+    diesel::update(
+        recurring_transfers::table
+            .filter(
+                recurring_transfers::from_account_id
+                    .eq(account_id)
+                    .or(recurring_transfers::to_account_id.eq(account_id)),
+            )
+            .filter(recurring_transfers::status.eq_any([
+                RecurringTransferStatus::Active,
+                RecurringTransferStatus::Paused,
+            ])),
+    )
+    .set((
+        recurring_transfers::status.eq(RecurringTransferStatus::Cancelled),
+        recurring_transfers::next_run_at.eq(None::<NaiveDateTime>),
+    ))
+    .execute(conn)
+    .map_err(AppError::from)
+}
+
+pub fn create_recurring_transfer_run(
+    new_run: &NewRecurringTransferRun,
+    conn: &mut PgConnection,
+) -> Result<RecurringTransferRun, AppError> {
+    diesel::insert_into(recurring_transfer_runs::table)
+        .values(new_run)
+        .get_result(conn)
+        .map_err(AppError::from)
+}
+
+pub fn get_recurring_transfer_runs(
+    recurring_transfer_id: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<RecurringTransferRun>, AppError> {
+    recurring_transfer_runs::table
+        .filter(recurring_transfer_runs::recurring_transfer_id.eq(recurring_transfer_id))
+        .order(recurring_transfer_runs::scheduled_for.desc())
+        .load(conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+}
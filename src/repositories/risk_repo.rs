@@ -0,0 +1,135 @@
+use crate::models::{
+    NewRiskReview, NewRiskRule, RiskReview, RiskReviewStatus, RiskRule, RiskRuleChanges,
+    Transaction,
+};
+use crate::schema::{risk_reviews, risk_rules, transactions};
+use crate::utils::app_error::AppError;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::result::DatabaseErrorKind;
+
+pub fn create_risk_rule(
+    new_rule: &NewRiskRule,
+    conn: &mut PgConnection,
+) -> Result<RiskRule, AppError> {
+    diesel::insert_into(risk_rules::table)
+        .values(new_rule)
+        .get_result(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                AppError::Conflict(format!(
+                    "A risk rule named {} already exists",
+                    new_rule.name
+                ))
+            }
+            e => AppError::from(e),
+        })
+}
+
+pub fn list_risk_rules(conn: &mut PgConnection) -> Result<Vec<RiskRule>, AppError> {
+    risk_rules::table
+        .order(risk_rules::id.asc())
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+// Active rules that check transactions in `currency`
+pub fn get_active_risk_rules(
+    currency: &str,
+    conn: &mut PgConnection,
+) -> Result<Vec<RiskRule>, AppError> {
+    risk_rules::table
+        .filter(risk_rules::is_active.eq(true))
+        .filter(
+            risk_rules::currency
+                .eq(currency)
+                .or(risk_rules::currency.is_null()),
+        )
+        .order(risk_rules::id.asc())
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+pub fn update_risk_rule(
+    id: i64,
+    changes: &RiskRuleChanges,
+    conn: &mut PgConnection,
+) -> Result<RiskRule, AppError> {
+    let query = risk_rules::table.find(id);
+    let result =
+        if changes.definition.is_none() && changes.outcome.is_none() && changes.is_active.is_none()
+        {
+            query.first(conn)
+        } else {
+            diesel::update(query).set(changes).get_result(conn)
+        };
+    result.optional()?.ok_or(AppError::NotFound)
+}
+
+pub fn delete_risk_rule(id: i64, conn: &mut PgConnection) -> Result<(), AppError> {
+    let deleted = diesel::delete(risk_rules::table.find(id))
+        .execute(conn)
+        .map_err(AppError::from)?;
+
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+    Ok(())
+}
+
+pub fn create_risk_review(
+    new_review: &NewRiskReview,
+    conn: &mut PgConnection,
+) -> Result<RiskReview, AppError> {
+    diesel::insert_into(risk_reviews::table)
+        .values(new_review)
+        .get_result(conn)
+        .map_err(AppError::from)
+}
+
+// Lock a review while it is being decided
+pub fn get_risk_review_for_update(
+    id: i64,
+    conn: &mut PgConnection,
+) -> Result<RiskReview, AppError> {
+    risk_reviews::table
+        .find(id)
+        .for_update()
+        .first(conn)
+        .optional()?
+        .ok_or(AppError::NotFound)
+}
+
+// Reviews in a status with their transactions, oldest first
+pub fn list_risk_reviews(
+    status: RiskReviewStatus,
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<(RiskReview, Transaction)>, AppError> {
+    risk_reviews::table
+        .inner_join(transactions::table)
+        .filter(risk_reviews::status.eq(status))
+        .order(risk_reviews::created_at.asc())
+        .limit(limit)
+        .select((RiskReview::as_select(), Transaction::as_select()))
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+pub fn decide_risk_review(
+    id: i64,
+    status: RiskReviewStatus,
+    reviewer_api_key_id: i64,
+    reason: Option<String>,
+    conn: &mut PgConnection,
+) -> Result<RiskReview, AppError> {
+    diesel::update(risk_reviews::table.find(id))
+        .set((
+            risk_reviews::status.eq(status),
+            risk_reviews::reviewer_api_key_id.eq(reviewer_api_key_id),
+            risk_reviews::reason.eq(reason),
+            risk_reviews::decided_at.eq(Utc::now().naive_utc()),
+        ))
+        .get_result(conn)
+        .map_err(AppError::from)
+}
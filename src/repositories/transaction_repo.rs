@@ -1,29 +1,54 @@
 #![allow(dead_code)]
-use crate::models::{NewTransaction, Transaction, TransactionStatus};
-use crate::schema::transactions;
+use crate::models::{
+    ListTransactionsQuery, NewTransaction, NewTransactionBatch, NewTransactionStatusHistory,
+    RiskReviewStatus, Transaction, TransactionBatch, TransactionStatus, TransactionStatusHistory,
+    TransactionType,
+};
+use crate::schema::{risk_reviews, transaction_batches, transaction_status_history, transactions};
 use crate::utils::app_error::AppError;
+use chrono::NaiveDateTime;
+use chrono::Utc;
 use diesel::prelude::*;
+use diesel::result::DatabaseErrorKind;
+use serde_json::json;
 
 pub fn create_transaction(
     new_tx: &NewTransaction,
     conn: &mut PgConnection,
 ) -> Result<Transaction, AppError> {
     // Check for duplicate idempotency key
-    if let Some(ref key) = new_tx.idempotency_key {
-        if transactions::table
+    if let Some(ref key) = new_tx.idempotency_key
+        && transactions::table
             .filter(transactions::idempotency_key.eq(key))
             .first::<Transaction>(conn)
             .ok()
             .is_some()
-        {
-            return Err(AppError::DuplicateIdempotencyKey);
-        }
+    {
+        return Err(AppError::DuplicateIdempotencyKey);
     }
 
     diesel::insert_into(transactions::table)
         .values(new_tx)
         .get_result(conn)
-        .map_err(|e| AppError::DatabaseError(e.to_string()))
+        .map_err(|e| match e {
+            // A concurrent request with the same key won the race
+            diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                AppError::DuplicateIdempotencyKey
+            }
+            e => AppError::from(e),
+        })
+}
+
+// Subset of the given idempotency keys that are already used by a transaction
+pub fn get_existing_idempotency_keys(
+    keys: &[String],
+    conn: &mut PgConnection,
+) -> Result<Vec<String>, AppError> {
+    transactions::table
+        .filter(transactions::idempotency_key.eq_any(keys))
+        .select(transactions::idempotency_key.assume_not_null())
+        .load(conn)
+        .map_err(AppError::from)
 }
 
 pub fn get_transaction_by_id(id: i64, conn: &mut PgConnection) -> Result<Transaction, AppError> {
@@ -33,18 +58,276 @@ pub fn get_transaction_by_id(id: i64, conn: &mut PgConnection) -> Result<Transac
         .map_err(|_| AppError::TransactionNotFound)
 }
 
-// Get all transactions for an account (both sent and received)
-pub fn get_account_transactions(
-    account_id: i64,
+// Lock a transaction row so concurrent capture/void requests are serialized
+pub fn get_transaction_for_update(
+    id: i64,
+    conn: &mut PgConnection,
+) -> Result<Transaction, AppError> {
+    transactions::table
+        .find(id)
+        .for_update()
+        .first(conn)
+        .optional()
+        .map_err(AppError::from)?
+        .ok_or(AppError::TransactionNotFound)
+}
+
+pub fn update_transaction_status(
+    id: i64,
+    status: TransactionStatus,
+    conn: &mut PgConnection,
+) -> Result<Transaction, AppError> {
+    diesel::update(transactions::table.find(id))
+        .set(transactions::status.eq(status))
+        .get_result(conn)
+        .map_err(AppError::from)
+}
+
+// Used when an authorization is captured for less than the authorized amount
+pub fn update_transaction_amount(
+    id: i64,
+    amount: i64,
+    converted_amount: Option<i64>,
+    fee_amount: i64,
+    conn: &mut PgConnection,
+) -> Result<Transaction, AppError> {
+    diesel::update(transactions::table.find(id))
+        .set((
+            transactions::amount.eq(amount),
+            transactions::converted_amount.eq(converted_amount),
+            transactions::fee_amount.eq(fee_amount),
+        ))
+        .get_result(conn)
+        .map_err(AppError::from)
+}
+
+pub fn create_status_history(
+    entry: &NewTransactionStatusHistory,
+    conn: &mut PgConnection,
+) -> Result<TransactionStatusHistory, AppError> {
+    diesel::insert_into(transaction_status_history::table)
+        .values(entry)
+        .get_result(conn)
+        .map_err(AppError::from)
+}
+
+pub fn get_status_history(
+    transaction_id: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<TransactionStatusHistory>, AppError> {
+    transaction_status_history::table
+        .filter(transaction_status_history::transaction_id.eq(transaction_id))
+        .order(transaction_status_history::id.asc())
+        .load(conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+}
+
+pub fn add_refunded_amount(
+    id: i64,
+    amount: i64,
+    conn: &mut PgConnection,
+) -> Result<Transaction, AppError> {
+    diesel::update(transactions::table.find(id))
+        .set(transactions::refunded_amount.eq(transactions::refunded_amount + amount))
+        .get_result(conn)
+        .map_err(AppError::from)
+}
+
+// Compensating transactions created for an original transaction
+pub fn get_reversals(
+    original_id: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<Transaction>, AppError> {
+    transactions::table
+        .filter(transactions::reversal_of_id.eq(original_id))
+        .order(transactions::created_at.asc())
+        .load(conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+}
+
+pub fn create_batch(
+    new_batch: &NewTransactionBatch,
+    conn: &mut PgConnection,
+) -> Result<TransactionBatch, AppError> {
+    diesel::insert_into(transaction_batches::table)
+        .values(new_batch)
+        .get_result(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                AppError::DuplicateIdempotencyKey
+            }
+            e => AppError::from(e),
+        })
+}
+
+pub fn get_batch_by_id(id: i64, conn: &mut PgConnection) -> Result<TransactionBatch, AppError> {
+    transaction_batches::table
+        .find(id)
+        .first(conn)
+        .map_err(|_| AppError::NotFound)
+}
+
+// Legs of a batch in the order they were submitted
+pub fn get_batch_transactions(
+    batch_id: i64,
     conn: &mut PgConnection,
 ) -> Result<Vec<Transaction>, AppError> {
     transactions::table
+        .filter(transactions::batch_id.eq(batch_id))
+        .order(transactions::id.asc())
+        .load(conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+}
+
+// Pending scheduled transactions whose execution time has come, oldest first
+pub fn get_due_scheduled_transactions(
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<Transaction>, AppError> {
+    // Transactions waiting for a risk review run once they are approved
+    let in_review = risk_reviews::table
+        .filter(risk_reviews::transaction_id.eq(transactions::id))
+        .filter(risk_reviews::status.eq(RiskReviewStatus::Pending));
+
+    transactions::table
+        .filter(transactions::status.eq(TransactionStatus::Pending))
+        .filter(transactions::execute_at.le(Utc::now().naive_utc()))
+        .filter(diesel::dsl::not(diesel::dsl::exists(in_review)))
+        .order(transactions::execute_at.asc())
+        .limit(limit)
+        .load(conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+}
+
+// Pending transactions (authorizations, scheduled or held for review) paying from or to
+// an account
+pub fn count_pending_transactions(
+    account_id: i64,
+    conn: &mut PgConnection,
+) -> Result<i64, AppError> {
+    transactions::table
+        .filter(
+            transactions::from_account_id
+                .eq(account_id)
+                .or(transactions::to_account_id.eq(account_id)),
+        )
+        .filter(transactions::status.eq(TransactionStatus::Pending))
+        .count()
+        .get_result(conn)
+        .map_err(AppError::from)
+}
+
+// Distinct accounts an account has sent transfers to since `since`, leaving out
+// failed and cancelled transfers
+pub fn get_recent_recipients(
+    from_account_id: i64,
+    since: NaiveDateTime,
+    conn: &mut PgConnection,
+) -> Result<Vec<i64>, AppError> {
+    transactions::table
+        .filter(transactions::from_account_id.eq(from_account_id))
+        .filter(transactions::tx_type.eq(TransactionType::Transfer))
+        .filter(
+            transactions::status.ne_all([TransactionStatus::Failed, TransactionStatus::Cancelled]),
+        )
+        .filter(transactions::created_at.ge(since))
+        .select(transactions::to_account_id.assume_not_null())
+        .distinct()
+        .load(conn)
+        .map_err(AppError::from)
+}
+
+// One page of an account's transactions (both sent and received), newest first, or
+// soonest first for scheduled transactions that have not run yet. Paging is keyed on
+// the sort column and id so pages stay stable while new transactions arrive.
+pub fn get_account_transactions_page(
+    account_id: i64,
+    query: &ListTransactionsQuery,
+    after: Option<&Transaction>,
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<Transaction>, AppError> {
+    let mut sql = transactions::table
         .filter(
             transactions::from_account_id
                 .eq(account_id)
                 .or(transactions::to_account_id.eq(account_id)),
         )
-        .order(transactions::created_at.desc())
+        .into_boxed();
+
+    if let Some(created_from) = query.created_from {
+        sql = sql.filter(transactions::created_at.ge(created_from.naive_utc()));
+    }
+    if let Some(created_to) = query.created_to {
+        sql = sql.filter(transactions::created_at.le(created_to.naive_utc()));
+    }
+    if let Some(tx_type) = query.tx_type {
+        sql = sql.filter(transactions::tx_type.eq(tx_type));
+    }
+    if let Some(status) = query.status {
+        sql = sql.filter(transactions::status.eq(status));
+    }
+    if let Some(min_amount) = query.min_amount {
+        sql = sql.filter(transactions::amount.ge(min_amount));
+    }
+    if let Some(max_amount) = query.max_amount {
+        sql = sql.filter(transactions::amount.le(max_amount));
+    }
+    if let Some(counterparty) = query.counterparty_account_id {
+        sql = sql.filter(
+            transactions::from_account_id
+                .eq(counterparty)
+                .or(transactions::to_account_id.eq(counterparty)),
+        );
+    }
+    if let Some(key) = &query.metadata_key {
+        sql = match &query.metadata_value {
+            Some(value) => sql.filter(transactions::metadata.contains(json!({ key: value }))),
+            None => sql.filter(transactions::metadata.has_key(key)),
+        };
+    }
+
+    if query.upcoming.unwrap_or(false) {
+        sql = sql
+            .filter(transactions::status.eq(TransactionStatus::Pending))
+            .filter(transactions::execute_at.is_not_null());
+        if let Some(after) = after
+            && let Some(execute_at) = after.execute_at
+        {
+            sql = sql.filter(
+                transactions::execute_at
+                    .gt(execute_at)
+                    .or(transactions::execute_at
+                        .eq(execute_at)
+                        .and(transactions::id.gt(after.id))),
+            );
+        }
+        sql = sql.order((transactions::execute_at.asc(), transactions::id.asc()));
+    } else {
+        if let Some(after) = after {
+            sql = sql.filter(
+                transactions::created_at
+                    .lt(after.created_at)
+                    .or(transactions::created_at
+                        .eq(after.created_at)
+                        .and(transactions::id.lt(after.id))),
+            );
+        }
+        sql = sql.order((transactions::created_at.desc(), transactions::id.desc()));
+    }
+
+    sql.limit(limit)
+        .load(conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+}
+
+// Transactions that have posted to the journal: completed ones and reversed originals
+pub fn get_posted_transactions(conn: &mut PgConnection) -> Result<Vec<Transaction>, AppError> {
+    transactions::table
+        .filter(
+            transactions::status
+                .eq_any([TransactionStatus::Completed, TransactionStatus::Reversed]),
+        )
         .load(conn)
         .map_err(|e| AppError::DatabaseError(e.to_string()))
 }
@@ -26,6 +26,21 @@ pub fn get_webhook_endpoints_by_account(
         .map_err(|e| AppError::DatabaseError(e.to_string()))
 }
 
+// Deactivate every endpoint of an account. Returns how many were active.
+pub fn deactivate_webhook_endpoints_by_account(
+    account_id: i64,
+    conn: &mut PgConnection,
+) -> Result<usize, AppError> {
+    diesel::update(
+        webhook_endpoints::table
+            .filter(webhook_endpoints::account_id.eq(account_id))
+            .filter(webhook_endpoints::is_active.eq(true)),
+    )
+    .set(webhook_endpoints::is_active.eq(false))
+    .execute(conn)
+    .map_err(AppError::from)
+}
+
 pub fn create_webhook_event(
     new_event: &NewWebhookEvent,
     conn: &mut PgConnection,
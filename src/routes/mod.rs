@@ -1,26 +1,75 @@
 use crate::middleware::api_key_auth::api_key_auth_middleware;
 use crate::{AppState, handlers};
 use axum::{
-    Router, middleware as axum_middleware,
+    Router,
+    extract::DefaultBodyLimit,
+    middleware as axum_middleware,
     routing::{delete, get, patch, post},
 };
 use std::sync::Arc;
 
+// Import files are much larger than regular JSON bodies
+const IMPORT_BODY_LIMIT_BYTES: usize = 50 * 1024 * 1024;
+
 pub fn create_router(state: Arc<AppState>) -> Router {
     let protected_routes = Router::new()
         // Accounts
         .route(
             "/api/accounts",
-            post(handlers::account_handlers::create_account),
+            get(handlers::account_handlers::list_accounts)
+                .post(handlers::account_handlers::create_account),
         )
         .route(
             "/api/accounts/:id",
-            get(handlers::account_handlers::get_account),
+            get(handlers::account_handlers::get_account)
+                .patch(handlers::account_handlers::update_account),
+        )
+        .route(
+            "/api/accounts/:id/close",
+            post(handlers::account_handlers::close_account),
         )
         .route(
             "/api/accounts/:id/balance",
             get(handlers::account_handlers::get_balance),
         )
+        .route(
+            "/api/accounts/:id/wallets",
+            post(handlers::account_handlers::open_wallet),
+        )
+        .route(
+            "/api/accounts/:id/statements",
+            get(handlers::account_handlers::export_statement),
+        )
+        .route(
+            "/api/accounts/:id/overdraft_limit",
+            post(handlers::account_handlers::set_overdraft_limit),
+        )
+        .route(
+            "/api/accounts/:id/limits",
+            get(handlers::account_handlers::get_limits)
+                .post(handlers::account_handlers::set_limits),
+        )
+        .route(
+            "/api/accounts/:id/freeze",
+            post(handlers::account_handlers::freeze_account),
+        )
+        .route(
+            "/api/accounts/:id/unfreeze",
+            post(handlers::account_handlers::unfreeze_account),
+        )
+        .route(
+            "/api/accounts/:id/freezes",
+            get(handlers::account_handlers::list_freezes),
+        )
+        .route(
+            "/api/accounts/:id/holds",
+            get(handlers::account_handlers::list_holds)
+                .post(handlers::account_handlers::place_hold),
+        )
+        .route(
+            "/api/holds/:id/release",
+            post(handlers::account_handlers::release_hold),
+        )
         .route(
             "/api/accounts/:id/keys",
             get(handlers::api_key_handlers::get_api_keys),
@@ -30,14 +79,84 @@ pub fn create_router(state: Arc<AppState>) -> Router {
             "/api/transactions",
             post(handlers::transaction_handlers::create_transaction),
         )
+        .route(
+            "/api/transactions/import",
+            post(handlers::transaction_handlers::import_transactions)
+                .layer(DefaultBodyLimit::max(IMPORT_BODY_LIMIT_BYTES)),
+        )
+        .route(
+            "/api/transactions/quote",
+            post(handlers::transaction_handlers::quote_transaction),
+        )
+        .route(
+            "/api/transactions/batch",
+            post(handlers::transaction_handlers::create_batch),
+        )
+        .route(
+            "/api/transactions/batch/:id",
+            get(handlers::transaction_handlers::get_batch),
+        )
         .route(
             "/api/transactions/:id",
             get(handlers::transaction_handlers::get_transaction),
         )
+        .route(
+            "/api/transactions/:id/capture",
+            post(handlers::transaction_handlers::capture_transaction),
+        )
+        .route(
+            "/api/transactions/:id/void",
+            post(handlers::transaction_handlers::void_transaction),
+        )
+        .route(
+            "/api/transactions/:id/reverse",
+            post(handlers::transaction_handlers::reverse_transaction),
+        )
+        .route(
+            "/api/transactions/:id/cancel",
+            post(handlers::transaction_handlers::cancel_transaction),
+        )
+        .route(
+            "/api/transactions/:id/history",
+            get(handlers::transaction_handlers::get_transaction_history),
+        )
+        .route(
+            "/api/transactions/:id/journal",
+            get(handlers::transaction_handlers::get_transaction_journal),
+        )
         .route(
             "/api/transactions/account/:account_id",
             get(handlers::transaction_handlers::list_account_transactions),
         )
+        // Recurring transfers
+        .route(
+            "/api/recurring_transfers",
+            post(handlers::recurring_transfer_handlers::create_recurring_transfer),
+        )
+        .route(
+            "/api/recurring_transfers/:id",
+            get(handlers::recurring_transfer_handlers::get_recurring_transfer),
+        )
+        .route(
+            "/api/recurring_transfers/:id/runs",
+            get(handlers::recurring_transfer_handlers::list_recurring_transfer_runs),
+        )
+        .route(
+            "/api/recurring_transfers/:id/pause",
+            post(handlers::recurring_transfer_handlers::pause_recurring_transfer),
+        )
+        .route(
+            "/api/recurring_transfers/:id/resume",
+            post(handlers::recurring_transfer_handlers::resume_recurring_transfer),
+        )
+        .route(
+            "/api/recurring_transfers/:id/cancel",
+            post(handlers::recurring_transfer_handlers::cancel_recurring_transfer),
+        )
+        .route(
+            "/api/accounts/:id/recurring_transfers",
+            get(handlers::recurring_transfer_handlers::list_account_recurring_transfers),
+        )
         // Webhooks
         .route(
             "/api/webhooks",
@@ -51,6 +170,49 @@ pub fn create_router(state: Arc<AppState>) -> Router {
             "/api/webhooks/:id",
             delete(handlers::webhook_handlers::delete_webhook),
         )
+        // FX rates
+        .route(
+            "/api/fx_rates",
+            get(handlers::fx_rate_handlers::list_fx_rates)
+                .post(handlers::fx_rate_handlers::set_fx_rate),
+        )
+        .route(
+            "/api/fx_rates/:id",
+            delete(handlers::fx_rate_handlers::delete_fx_rate),
+        )
+        // Fee schedules
+        .route(
+            "/api/fee_schedules",
+            get(handlers::fee_schedule_handlers::list_fee_schedules)
+                .post(handlers::fee_schedule_handlers::create_fee_schedule),
+        )
+        .route(
+            "/api/fee_schedules/:id",
+            delete(handlers::fee_schedule_handlers::delete_fee_schedule),
+        )
+        // Risk rules and reviews
+        .route(
+            "/api/risk_rules",
+            get(handlers::risk_handlers::list_risk_rules)
+                .post(handlers::risk_handlers::create_risk_rule),
+        )
+        .route(
+            "/api/risk_rules/:id",
+            patch(handlers::risk_handlers::update_risk_rule)
+                .delete(handlers::risk_handlers::delete_risk_rule),
+        )
+        .route(
+            "/api/risk_reviews",
+            get(handlers::risk_handlers::list_risk_reviews),
+        )
+        .route(
+            "/api/risk_reviews/:id/approve",
+            post(handlers::risk_handlers::approve_risk_review),
+        )
+        .route(
+            "/api/risk_reviews/:id/decline",
+            post(handlers::risk_handlers::decline_risk_review),
+        )
         // Admin
         .route(
             "/api/key_generate",
@@ -64,6 +226,15 @@ pub fn create_router(state: Arc<AppState>) -> Router {
             "/api/keys/:id",
             patch(handlers::api_key_handlers::update_api_key),
         )
+        .route(
+            "/api/keys/:id/limits",
+            get(handlers::api_key_handlers::get_api_key_limits)
+                .post(handlers::api_key_handlers::set_api_key_limits),
+        )
+        .route(
+            "/api/reconciliation",
+            post(handlers::reconciliation_handlers::run_reconciliation),
+        )
         .layer(axum_middleware::from_fn_with_state(
             state.clone(),
             api_key_auth_middleware,
@@ -1,6 +1,38 @@
 // @generated automatically by Diesel CLI.
 
 pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "fee_type"))]
+    pub struct FeeType;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "freeze_scope"))]
+    pub struct FreezeScope;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "catch_up_policy"))]
+    pub struct CatchUpPolicy;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "recurrence_interval"))]
+    pub struct RecurrenceInterval;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "recurring_run_status"))]
+    pub struct RecurringRunStatus;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "recurring_transfer_status"))]
+    pub struct RecurringTransferStatus;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "risk_outcome"))]
+    pub struct RiskOutcome;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "risk_review_status"))]
+    pub struct RiskReviewStatus;
+
     #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "transaction_status"))]
     pub struct TransactionStatus;
@@ -14,17 +46,51 @@ pub mod sql_types {
     pub struct WebhookStatus;
 }
 
+diesel::table! {
+    account_balances (id) {
+        id -> Int8,
+        account_id -> Int8,
+        #[max_length = 3]
+        currency -> Varchar,
+        balance -> Int8,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        overdraft_limit -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::FreezeScope;
+
+    account_freezes (id) {
+        id -> Int8,
+        account_id -> Int8,
+        scope -> FreezeScope,
+        #[max_length = 500]
+        reason -> Varchar,
+        frozen_by_api_key_id -> Int8,
+        lifted_by_api_key_id -> Nullable<Int8>,
+        #[max_length = 500]
+        lift_reason -> Nullable<Varchar>,
+        lifted_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     accounts (id) {
         id -> Int8,
         #[max_length = 255]
         business_name -> Varchar,
-        balance -> Int8,
         #[max_length = 3]
         currency -> Varchar,
         is_active -> Bool,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        metadata -> Jsonb,
+        closed_at -> Nullable<Timestamp>,
     }
 }
 
@@ -48,6 +114,74 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    balance_snapshots (id) {
+        id -> Int8,
+        account_id -> Int8,
+        #[max_length = 3]
+        currency -> Varchar,
+        balance -> Int8,
+        as_of -> Timestamp,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::TransactionType;
+    use super::sql_types::FeeType;
+
+    fee_schedules (id) {
+        id -> Int8,
+        account_id -> Nullable<Int8>,
+        tx_type -> TransactionType,
+        #[max_length = 3]
+        currency -> Varchar,
+        fee_type -> FeeType,
+        flat_amount -> Int8,
+        percentage -> Int8,
+        min_fee -> Nullable<Int8>,
+        max_fee -> Nullable<Int8>,
+        tiers -> Jsonb,
+        fee_account_id -> Int8,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    fx_rates (id) {
+        id -> Int8,
+        #[max_length = 3]
+        base_currency -> Varchar,
+        #[max_length = 3]
+        quote_currency -> Varchar,
+        rate -> Int8,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    holds (id) {
+        id -> Int8,
+        account_id -> Int8,
+        transaction_id -> Nullable<Int8>,
+        amount -> Int8,
+        expires_at -> Nullable<Timestamp>,
+        released_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        #[max_length = 3]
+        currency -> Varchar,
+        #[max_length = 500]
+        reason -> Nullable<Varchar>,
+        placed_by_api_key_id -> Nullable<Int8>,
+        released_by_api_key_id -> Nullable<Int8>,
+        #[max_length = 500]
+        release_reason -> Nullable<Varchar>,
+    }
+}
+
 diesel::table! {
     idempotency_cache (id) {
         id -> Int8,
@@ -60,6 +194,153 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    journal_entries (id) {
+        id -> Int8,
+        transaction_id -> Int8,
+        #[max_length = 500]
+        description -> Nullable<Varchar>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    postings (id) {
+        id -> Int8,
+        journal_entry_id -> Int8,
+        account_id -> Nullable<Int8>,
+        amount -> Int8,
+        created_at -> Timestamp,
+        #[max_length = 3]
+        currency -> Varchar,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::RecurringRunStatus;
+
+    recurring_transfer_runs (id) {
+        id -> Int8,
+        recurring_transfer_id -> Int8,
+        scheduled_for -> Timestamp,
+        status -> RecurringRunStatus,
+        transaction_id -> Nullable<Int8>,
+        #[max_length = 500]
+        error -> Nullable<Varchar>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::RecurrenceInterval;
+    use super::sql_types::RecurringTransferStatus;
+    use super::sql_types::CatchUpPolicy;
+
+    recurring_transfers (id) {
+        id -> Int8,
+        from_account_id -> Int8,
+        to_account_id -> Int8,
+        amount -> Int8,
+        #[max_length = 500]
+        description -> Nullable<Varchar>,
+        interval_unit -> Nullable<RecurrenceInterval>,
+        interval_count -> Int4,
+        #[max_length = 255]
+        cron_expression -> Nullable<Varchar>,
+        start_at -> Timestamp,
+        end_at -> Nullable<Timestamp>,
+        max_occurrences -> Nullable<Int4>,
+        occurrences -> Int4,
+        next_run_at -> Nullable<Timestamp>,
+        status -> RecurringTransferStatus,
+        catch_up_policy -> CatchUpPolicy,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::RiskReviewStatus;
+
+    risk_reviews (id) {
+        id -> Int8,
+        transaction_id -> Int8,
+        matched_rules -> Jsonb,
+        capture -> Bool,
+        status -> RiskReviewStatus,
+        reviewer_api_key_id -> Nullable<Int8>,
+        #[max_length = 500]
+        reason -> Nullable<Varchar>,
+        decided_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::RiskOutcome;
+
+    risk_rules (id) {
+        id -> Int8,
+        #[max_length = 100]
+        name -> Varchar,
+        #[max_length = 3]
+        currency -> Nullable<Varchar>,
+        definition -> Jsonb,
+        outcome -> RiskOutcome,
+        is_active -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    transaction_batches (id) {
+        id -> Int8,
+        #[max_length = 500]
+        description -> Nullable<Varchar>,
+        #[max_length = 255]
+        idempotency_key -> Nullable<Varchar>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    transaction_limits (id) {
+        id -> Int8,
+        account_id -> Nullable<Int8>,
+        api_key_id -> Nullable<Int8>,
+        #[max_length = 3]
+        currency -> Varchar,
+        max_amount -> Nullable<Int8>,
+        max_daily_outflow -> Nullable<Int8>,
+        max_monthly_outflow -> Nullable<Int8>,
+        max_daily_transactions -> Nullable<Int4>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::TransactionStatus;
+
+    transaction_status_history (id) {
+        id -> Int8,
+        transaction_id -> Int8,
+        from_status -> Nullable<TransactionStatus>,
+        to_status -> TransactionStatus,
+        actor_api_key_id -> Nullable<Int8>,
+        #[max_length = 500]
+        reason -> Nullable<Varchar>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::TransactionType;
@@ -78,6 +359,19 @@ diesel::table! {
         idempotency_key -> Nullable<Varchar>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        reversal_of_id -> Nullable<Int8>,
+        refunded_amount -> Int8,
+        batch_id -> Nullable<Int8>,
+        execute_at -> Nullable<Timestamp>,
+        fx_rate -> Nullable<Int8>,
+        converted_amount -> Nullable<Int8>,
+        #[max_length = 3]
+        converted_currency -> Nullable<Varchar>,
+        #[max_length = 3]
+        currency -> Varchar,
+        metadata -> Jsonb,
+        fee_amount -> Int8,
+        fee_account_id -> Nullable<Int8>,
     }
 }
 
@@ -115,14 +409,46 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(account_balances -> accounts (account_id));
+diesel::joinable!(account_freezes -> accounts (account_id));
 diesel::joinable!(api_keys -> accounts (account_id));
+diesel::joinable!(balance_snapshots -> accounts (account_id));
+diesel::joinable!(holds -> accounts (account_id));
+diesel::joinable!(holds -> transactions (transaction_id));
+diesel::joinable!(journal_entries -> transactions (transaction_id));
+diesel::joinable!(postings -> accounts (account_id));
+diesel::joinable!(postings -> journal_entries (journal_entry_id));
+diesel::joinable!(recurring_transfer_runs -> recurring_transfers (recurring_transfer_id));
+diesel::joinable!(recurring_transfer_runs -> transactions (transaction_id));
+diesel::joinable!(risk_reviews -> api_keys (reviewer_api_key_id));
+diesel::joinable!(risk_reviews -> transactions (transaction_id));
+diesel::joinable!(transaction_limits -> accounts (account_id));
+diesel::joinable!(transaction_limits -> api_keys (api_key_id));
+diesel::joinable!(transaction_status_history -> api_keys (actor_api_key_id));
+diesel::joinable!(transaction_status_history -> transactions (transaction_id));
+diesel::joinable!(transactions -> transaction_batches (batch_id));
 diesel::joinable!(webhook_endpoints -> accounts (account_id));
 diesel::joinable!(webhook_events -> webhook_endpoints (webhook_endpoint_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    account_balances,
+    account_freezes,
     accounts,
     api_keys,
+    balance_snapshots,
+    fee_schedules,
+    fx_rates,
+    holds,
     idempotency_cache,
+    journal_entries,
+    postings,
+    recurring_transfer_runs,
+    recurring_transfers,
+    risk_reviews,
+    risk_rules,
+    transaction_batches,
+    transaction_limits,
+    transaction_status_history,
     transactions,
     webhook_endpoints,
     webhook_events,
@@ -1,20 +1,47 @@
-use crate::{models::*, repositories, utils::app_error::AppError};
-use diesel::PgConnection;
+use crate::{
+    models::*,
+    repositories,
+    utils::{app_error::AppError, db, validation},
+};
+use chrono::{Duration, NaiveDateTime, NaiveTime, Utc};
+use diesel::{Connection, PgConnection};
 
-use crate::services::api_key_service;
+use crate::services::{api_key_service, transaction_service, webhook_service};
+
+const SNAPSHOT_BATCH_SIZE: i64 = 500;
+const MAX_BUSINESS_NAME_LENGTH: usize = 255;
+const DEFAULT_LIST_LIMIT: i64 = 20;
+const MAX_LIST_LIMIT: i64 = 100;
+// Postings are dated when their database transaction starts, so a snapshot waits
+// this long after its time for transactions still in flight to commit
+const SNAPSHOT_SETTLE_MINUTES: i64 = 10;
 
 pub fn create_account(
     req: CreateAccountRequest,
     conn: &mut PgConnection,
 ) -> Result<AccountCreationResponse, AppError> {
+    let currency = validation::normalize_currency(req.currency.as_deref().unwrap_or("USD"))
+        .map_err(AppError::BadRequest)?;
+    let metadata = validation::validate_metadata(req.metadata).map_err(AppError::BadRequest)?;
     let new_account = NewAccount {
         business_name: req.business_name,
-        balance: 0,
-        currency: req.currency.unwrap_or_else(|| "USD".to_string()),
+        currency,
         is_active: true,
+        metadata,
     };
 
-    let account = repositories::create_account(&new_account, conn)?;
+    // The account is opened together with the wallet of its default currency
+    let account = conn.transaction(|conn| {
+        let account = repositories::create_account(&new_account, conn)?;
+        repositories::create_account_balance(
+            &NewAccountBalance {
+                account_id: account.id,
+                currency: account.currency.clone(),
+            },
+            conn,
+        )?;
+        Ok::<_, AppError>(account)
+    })?;
 
     // Auto-generate Root API Key
     let key_req = GenerateApiKeyRequest {
@@ -27,17 +54,740 @@ pub fn create_account(
     let key_res = api_key_service::generate_key(key_req, conn)?;
 
     Ok(AccountCreationResponse {
-        account: account.into(),
+        account: account_response(account, conn)?,
         secret_api_key: key_res.key,
     })
 }
 
 pub fn get_account(id: i64, conn: &mut PgConnection) -> Result<AccountResponse, AppError> {
     let account = repositories::get_account_by_id(id, conn)?;
-    Ok(account.into())
+    account_response(account, conn)
+}
+
+// Admin: one page of accounts matching the filters, with counts and balance totals
+// over every matching account
+pub fn list_accounts(
+    mut query: ListAccountsQuery,
+    conn: &mut PgConnection,
+) -> Result<AccountListResponse, AppError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+    if !(1..=MAX_LIST_LIMIT).contains(&limit) {
+        return Err(AppError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_LIST_LIMIT
+        )));
+    }
+    // Balances are minor units of one currency, so the filter needs to know which
+    if (query.min_balance.is_some() || query.max_balance.is_some()) && query.currency.is_none() {
+        return Err(AppError::BadRequest(
+            "min_balance and max_balance require currency".to_string(),
+        ));
+    }
+    if let (Some(min), Some(max)) = (query.min_balance, query.max_balance)
+        && min > max
+    {
+        return Err(AppError::BadRequest(
+            "min_balance cannot exceed max_balance".to_string(),
+        ));
+    }
+    if let (Some(from), Some(to)) = (query.created_from, query.created_to)
+        && from > to
+    {
+        return Err(AppError::BadRequest(
+            "created_from cannot be after created_to".to_string(),
+        ));
+    }
+    query.currency = query
+        .currency
+        .as_deref()
+        .map(validation::normalize_currency)
+        .transpose()
+        .map_err(AppError::BadRequest)?;
+    query.search = query
+        .search
+        .map(|search| search.trim().to_string())
+        .filter(|search| !search.is_empty());
+
+    // One extra row tells whether another page follows
+    let mut accounts = repositories::get_accounts_page(&query, limit + 1, conn)?;
+    let has_more = accounts.len() as i64 > limit;
+    accounts.truncate(limit as usize);
+
+    let (total_count, active_count, closed_count) = repositories::count_accounts(&query, conn)?;
+    let totals = repositories::sum_balances_by_currency(&query, conn)?
+        .into_iter()
+        .map(|(currency, wallet_count, total)| CurrencyTotal::new(currency, wallet_count, total))
+        .collect();
+
+    Ok(AccountListResponse {
+        data: accounts
+            .into_iter()
+            .map(|account| account_response(account, conn))
+            .collect::<Result<_, _>>()?,
+        has_more,
+        total_count,
+        active_count,
+        inactive_count: total_count - active_count,
+        closed_count,
+        totals,
+    })
+}
+
+// Rename an account, change its default currency or metadata, or (admins only, checked
+// by the handler) switch it on or off
+pub fn update_account(
+    id: i64,
+    req: UpdateAccountRequest,
+    conn: &mut PgConnection,
+) -> Result<AccountResponse, AppError> {
+    let business_name = req
+        .business_name
+        .map(|name| name.trim().to_string())
+        .map(|name| {
+            if name.is_empty() || name.len() > MAX_BUSINESS_NAME_LENGTH {
+                return Err(AppError::BadRequest(format!(
+                    "business_name must be between 1 and {} characters",
+                    MAX_BUSINESS_NAME_LENGTH
+                )));
+            }
+            Ok(name)
+        })
+        .transpose()?;
+    let currency = req
+        .currency
+        .as_deref()
+        .map(validation::normalize_currency)
+        .transpose()
+        .map_err(AppError::BadRequest)?;
+    let metadata = req
+        .metadata
+        .map(|metadata| validation::validate_metadata(Some(metadata)))
+        .transpose()
+        .map_err(AppError::BadRequest)?;
+
+    let account = db::run_in_transaction(conn, |conn| {
+        let account = repositories::lock_accounts(&[id], conn)?.remove(0);
+        ensure_not_closed(&account)?;
+        if let Some(currency) = &currency
+            && repositories::get_account_balance(id, currency, conn)?.is_none()
+        {
+            return Err(AppError::WalletNotFound {
+                account_id: id,
+                currency: currency.clone(),
+            });
+        }
+
+        repositories::update_account(
+            id,
+            &AccountChanges {
+                business_name: business_name.clone(),
+                currency: currency.clone(),
+                is_active: req.is_active,
+                metadata: metadata.clone(),
+            },
+            conn,
+        )
+    })?;
+
+    tracing::info!(
+        account_id = account.id,
+        is_active = account.is_active,
+        "Account updated"
+    );
+    account_response(account, conn)
+}
+
+// Close an account for good. Every wallet must be empty, or have its funds swept to
+// `sweep_to_account_id`; nothing may be pending, held or frozen. The account's API keys
+// and webhook endpoints are deactivated and its recurring transfers cancelled. An account
+// an admin has deactivated can only be closed with `allow_inactive`, so that its owner
+// cannot sweep out funds the admin has stopped.
+pub fn close_account(
+    id: i64,
+    actor_key_id: Option<i64>,
+    allow_inactive: bool,
+    req: CloseAccountRequest,
+    conn: &mut PgConnection,
+) -> Result<CloseAccountResponse, AppError> {
+    if req.sweep_to_account_id == Some(id) {
+        return Err(AppError::BadRequest(
+            "Cannot sweep an account into itself".to_string(),
+        ));
+    }
+
+    let (account, sweeps) = db::run_in_transaction(conn, |conn| {
+        let mut ids = vec![id];
+        ids.extend(req.sweep_to_account_id);
+        let accounts = repositories::lock_accounts(&ids, conn)?;
+        let account = accounts
+            .into_iter()
+            .find(|account| account.id == id)
+            .ok_or(AppError::AccountNotFound)?;
+        if account.closed_at.is_some() {
+            return Err(AppError::Conflict("Account is already closed".to_string()));
+        }
+        if !account.is_active && !allow_inactive {
+            return Err(AppError::Forbidden);
+        }
+
+        if repositories::count_pending_transactions(id, conn)? > 0 {
+            return Err(AppError::Conflict(
+                "Account has pending transactions; capture, cancel or wait for them first"
+                    .to_string(),
+            ));
+        }
+        if !repositories::get_active_holds(id, conn)?.is_empty() {
+            return Err(AppError::Conflict("Account has active holds".to_string()));
+        }
+        if !repositories::get_active_freezes(&[id], conn)?.is_empty() {
+            return Err(AppError::Conflict(
+                "Frozen accounts cannot be closed".to_string(),
+            ));
+        }
+
+        let mut sweeps = Vec::new();
+        for wallet in repositories::get_account_balances(id, conn)? {
+            if wallet.balance < 0 {
+                return Err(AppError::Conflict(format!(
+                    "The {} wallet is overdrawn",
+                    wallet.currency
+                )));
+            }
+            if wallet.balance == 0 {
+                continue;
+            }
+            let Some(to_id) = req.sweep_to_account_id else {
+                return Err(AppError::Conflict(format!(
+                    "The {} wallet still holds funds; empty it or set sweep_to_account_id",
+                    wallet.currency
+                )));
+            };
+            let sweep = transaction_service::post_closing_sweep(
+                &NewTransaction {
+                    from_account_id: Some(id),
+                    to_account_id: Some(to_id),
+                    amount: wallet.balance,
+                    tx_type: TransactionType::Transfer,
+                    status: TransactionStatus::Completed,
+                    description: Some(format!("Closing balance of account {}", id)),
+                    idempotency_key: None,
+                    reversal_of_id: None,
+                    batch_id: None,
+                    execute_at: None,
+                    fx_rate: None,
+                    converted_amount: None,
+                    converted_currency: None,
+                    currency: wallet.currency.clone(),
+                    metadata: serde_json::json!({}),
+                    fee_amount: 0,
+                    fee_account_id: None,
+                },
+                actor_key_id,
+                conn,
+            )?;
+            sweeps.push(sweep);
+        }
+
+        let keys = repositories::deactivate_api_keys_by_account(id, conn)?;
+        let webhooks = repositories::deactivate_webhook_endpoints_by_account(id, conn)?;
+        let schedules = repositories::cancel_recurring_transfers_by_account(id, conn)?;
+        let account = repositories::close_account(id, conn)?;
+
+        tracing::info!(
+            account_id = id,
+            sweeps = sweeps.len(),
+            api_keys = keys,
+            webhooks = webhooks,
+            recurring_transfers = schedules,
+            "Account closed"
+        );
+        Ok((account, sweeps))
+    })?;
+
+    Ok(CloseAccountResponse {
+        account: account_response(account, conn)?,
+        sweeps: sweeps.into_iter().map(TransactionResponse::from).collect(),
+    })
+}
+
+pub fn get_balance(id: i64, conn: &mut PgConnection) -> Result<BalanceResponse, AppError> {
+    let account = repositories::get_account_by_id(id, conn)?;
+    let balances = wallet_balances(account.id, conn)?;
+    let default = default_wallet(&account, &balances);
+
+    Ok(BalanceResponse {
+        currency: account.currency,
+        balance: default.balance,
+        available_balance: default.available_balance,
+        balance_decimal: default.balance_decimal,
+        available_balance_decimal: default.available_balance_decimal,
+        balances,
+        as_of: None,
+    })
 }
 
-pub fn get_balance(id: i64, conn: &mut PgConnection) -> Result<i64, AppError> {
+// Ledger and available balance of every wallet as they stood at `as_of`
+pub fn get_balance_as_of(
+    id: i64,
+    as_of: NaiveDateTime,
+    conn: &mut PgConnection,
+) -> Result<BalanceResponse, AppError> {
+    if as_of > Utc::now().naive_utc() {
+        return Err(AppError::BadRequest(
+            "as_of cannot be in the future".to_string(),
+        ));
+    }
     let account = repositories::get_account_by_id(id, conn)?;
-    Ok(account.balance)
+    let balances = repositories::get_account_balances(account.id, conn)?
+        .into_iter()
+        .filter(|wallet| wallet.created_at <= as_of)
+        .map(|wallet| {
+            let balance = balance_as_of(account.id, &wallet.currency, as_of, conn)?;
+            let held =
+                repositories::get_held_amount_as_of(account.id, &wallet.currency, as_of, conn)?;
+            Ok(WalletBalance::new(
+                wallet.currency,
+                balance,
+                balance - held,
+                wallet.overdraft_limit,
+            ))
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+    let default = default_wallet(&account, &balances);
+
+    Ok(BalanceResponse {
+        currency: account.currency,
+        balance: default.balance,
+        available_balance: default.available_balance,
+        balance_decimal: default.balance_decimal,
+        available_balance_decimal: default.available_balance_decimal,
+        balances,
+        as_of: Some(as_of),
+    })
+}
+
+// Snapshot every wallet at the latest settled midnight (UTC) so historical balances
+// only replay the postings made after it
+pub fn take_balance_snapshots(conn: &mut PgConnection) -> Result<usize, AppError> {
+    let as_of = snapshot_time(Utc::now().naive_utc());
+    let mut taken = 0;
+    let mut after_id = 0;
+
+    loop {
+        let wallets =
+            repositories::get_account_balances_page(after_id, as_of, SNAPSHOT_BATCH_SIZE, conn)?;
+        let Some(last) = wallets.last() else {
+            break;
+        };
+        after_id = last.id;
+
+        for wallet in wallets {
+            let latest = repositories::get_latest_balance_snapshot(
+                wallet.account_id,
+                &wallet.currency,
+                as_of,
+                conn,
+            )?;
+            if latest.is_some_and(|snapshot| snapshot.as_of == as_of) {
+                continue;
+            }
+            let balance = balance_as_of(wallet.account_id, &wallet.currency, as_of, conn)?;
+            taken += repositories::create_balance_snapshot(
+                &NewBalanceSnapshot {
+                    account_id: wallet.account_id,
+                    currency: wallet.currency,
+                    balance,
+                    as_of,
+                },
+                conn,
+            )?;
+        }
+    }
+
+    Ok(taken)
+}
+
+// The latest snapshot at or before `as_of` plus the postings made since
+pub fn balance_as_of(
+    account_id: i64,
+    currency: &str,
+    as_of: NaiveDateTime,
+    conn: &mut PgConnection,
+) -> Result<i64, AppError> {
+    let snapshot = repositories::get_latest_balance_snapshot(account_id, currency, as_of, conn)?;
+    let (opening, after) = snapshot.map_or((0, None), |snapshot| {
+        (snapshot.balance, Some(snapshot.as_of))
+    });
+    Ok(opening + repositories::sum_postings(account_id, currency, after, as_of, conn)?)
+}
+
+fn snapshot_time(now: NaiveDateTime) -> NaiveDateTime {
+    (now - Duration::minutes(SNAPSHOT_SETTLE_MINUTES))
+        .date()
+        .and_time(NaiveTime::MIN)
+}
+
+// Open a wallet so the account can hold and move another currency
+pub fn open_wallet(
+    id: i64,
+    req: OpenWalletRequest,
+    conn: &mut PgConnection,
+) -> Result<BalanceResponse, AppError> {
+    let currency = validation::normalize_currency(&req.currency).map_err(AppError::BadRequest)?;
+    let account = db::run_in_transaction(conn, |conn| {
+        let account = repositories::lock_accounts(&[id], conn)?.remove(0);
+        ensure_not_closed(&account)?;
+        repositories::create_account_balance(
+            &NewAccountBalance {
+                account_id: account.id,
+                currency: currency.clone(),
+            },
+            conn,
+        )?;
+        Ok(account)
+    })?;
+
+    tracing::info!(account_id = account.id, currency = %currency, "Wallet opened");
+
+    get_balance(account.id, conn)
+}
+
+// Admin: let a wallet go negative down to `overdraft_limit` minor units.
+// A limit below the current overdraft only blocks further debits.
+pub fn set_overdraft_limit(
+    id: i64,
+    req: SetOverdraftLimitRequest,
+    conn: &mut PgConnection,
+) -> Result<BalanceResponse, AppError> {
+    if req.overdraft_limit < 0 {
+        return Err(AppError::BadRequest(
+            "overdraft_limit cannot be negative".to_string(),
+        ));
+    }
+    let (account, currency) = db::run_in_transaction(conn, |conn| {
+        let account = repositories::lock_accounts(&[id], conn)?.remove(0);
+        ensure_not_closed(&account)?;
+        let currency = match req.currency.as_deref() {
+            Some(currency) => {
+                validation::normalize_currency(currency).map_err(AppError::BadRequest)?
+            }
+            None => account.currency.clone(),
+        };
+        repositories::set_overdraft_limit(account.id, &currency, req.overdraft_limit, conn)?;
+        Ok((account, currency))
+    })?;
+
+    tracing::info!(
+        account_id = account.id,
+        currency = %currency,
+        overdraft_limit = req.overdraft_limit,
+        "Overdraft limit set"
+    );
+
+    get_balance(account.id, conn)
+}
+
+// Admin: freeze an account's outflows or all of its activity. The account is locked so
+// transactions already in flight finish before the freeze applies.
+pub fn freeze_account(
+    id: i64,
+    frozen_by_key_id: i64,
+    req: FreezeAccountRequest,
+    conn: &mut PgConnection,
+) -> Result<AccountFreeze, AppError> {
+    let reason = required_reason(req.reason)?;
+
+    let freeze = db::run_in_transaction(conn, |conn| {
+        repositories::lock_accounts(&[id], conn)?;
+        repositories::create_account_freeze(
+            &NewAccountFreeze {
+                account_id: id,
+                scope: req.scope,
+                reason: reason.clone(),
+                frozen_by_api_key_id: frozen_by_key_id,
+            },
+            conn,
+        )
+    })?;
+
+    tracing::warn!(
+        account_id = id,
+        scope = freeze.scope.as_str(),
+        frozen_by = frozen_by_key_id,
+        "Account frozen"
+    );
+    Ok(freeze)
+}
+
+// Admin: lift the active freeze of an account
+pub fn lift_freeze(
+    id: i64,
+    lifted_by_key_id: i64,
+    req: LiftFreezeRequest,
+    conn: &mut PgConnection,
+) -> Result<AccountFreeze, AppError> {
+    let reason = validation::validate_reason(req.reason).map_err(AppError::BadRequest)?;
+    repositories::get_account_by_id(id, conn)?;
+
+    let freeze = repositories::lift_account_freeze(id, lifted_by_key_id, reason, conn)?
+        .ok_or(AppError::Conflict("Account is not frozen".to_string()))?;
+
+    tracing::info!(
+        account_id = id,
+        lifted_by = lifted_by_key_id,
+        "Account freeze lifted"
+    );
+    Ok(freeze)
+}
+
+// Admin: every freeze placed on an account, newest first
+pub fn list_freezes(id: i64, conn: &mut PgConnection) -> Result<Vec<AccountFreeze>, AppError> {
+    repositories::get_account_by_id(id, conn)?;
+    repositories::list_account_freezes(id, conn)
+}
+
+// Admin: reserve funds on a wallet for legal or compliance reasons. The hold reduces the
+// available balance like an authorization does and may take it below zero.
+pub fn place_hold(
+    id: i64,
+    placed_by_key_id: i64,
+    req: PlaceHoldRequest,
+    conn: &mut PgConnection,
+) -> Result<HoldResponse, AppError> {
+    if req.amount <= 0 {
+        return Err(AppError::BadRequest("Amount must be positive".to_string()));
+    }
+    let reason = required_reason(req.reason)?;
+    let expires_at = req.expires_at.map(|at| at.naive_utc());
+    if expires_at.is_some_and(|at| at <= Utc::now().naive_utc()) {
+        return Err(AppError::BadRequest(
+            "expires_at must be in the future".to_string(),
+        ));
+    }
+
+    let hold = db::run_in_transaction(conn, |conn| {
+        let account = repositories::lock_accounts(&[id], conn)?.remove(0);
+        ensure_not_closed(&account)?;
+        let currency = match req.currency.as_deref() {
+            Some(currency) => {
+                validation::normalize_currency(currency).map_err(AppError::BadRequest)?
+            }
+            None => account.currency.clone(),
+        };
+        if repositories::get_account_balance(id, &currency, conn)?.is_none() {
+            return Err(AppError::WalletNotFound {
+                account_id: id,
+                currency,
+            });
+        }
+        repositories::create_hold(
+            &NewHold {
+                account_id: id,
+                transaction_id: None,
+                amount: req.amount,
+                expires_at,
+                currency,
+                reason: Some(reason.clone()),
+                placed_by_api_key_id: Some(placed_by_key_id),
+            },
+            conn,
+        )
+    })?;
+
+    tracing::warn!(
+        account_id = id,
+        hold_id = hold.id,
+        amount = hold.amount,
+        currency = %hold.currency,
+        placed_by = placed_by_key_id,
+        "Compliance hold placed"
+    );
+    Ok(hold.into())
+}
+
+// Admin: active holds of an account, authorizations and compliance holds alike
+pub fn list_holds(id: i64, conn: &mut PgConnection) -> Result<Vec<HoldResponse>, AppError> {
+    repositories::get_account_by_id(id, conn)?;
+    Ok(repositories::get_active_holds(id, conn)?
+        .into_iter()
+        .map(HoldResponse::from)
+        .collect())
+}
+
+// Admin: release a compliance hold. Authorization holds are released through their
+// transaction instead.
+pub fn release_hold(
+    hold_id: i64,
+    released_by_key_id: i64,
+    req: ReleaseHoldRequest,
+    conn: &mut PgConnection,
+) -> Result<HoldResponse, AppError> {
+    let reason = validation::validate_reason(req.reason).map_err(AppError::BadRequest)?;
+
+    let hold = db::run_in_transaction(conn, |conn| {
+        let hold = repositories::get_hold_for_update(hold_id, conn)?;
+        if hold.transaction_id.is_some() {
+            return Err(AppError::Conflict(
+                "Authorization holds are released by capturing, voiding or cancelling their transaction"
+                    .to_string(),
+            ));
+        }
+        if hold.released_at.is_some() {
+            return Err(AppError::Conflict(
+                "Hold has already been released".to_string(),
+            ));
+        }
+        repositories::release_compliance_hold(hold.id, released_by_key_id, reason.clone(), conn)
+    })?;
+
+    tracing::info!(
+        account_id = hold.account_id,
+        hold_id = hold.id,
+        released_by = released_by_key_id,
+        "Compliance hold released"
+    );
+    Ok(hold.into())
+}
+
+fn required_reason(reason: String) -> Result<String, AppError> {
+    validation::validate_reason(Some(reason))
+        .map_err(AppError::BadRequest)?
+        .ok_or(AppError::BadRequest("reason is required".to_string()))
+}
+
+// Notify the account when a posting moved a wallet across zero or onto its overdraft limit
+pub fn emit_balance_events(
+    before: &AccountBalance,
+    after: &AccountBalance,
+    transaction_id: i64,
+    conn: &mut PgConnection,
+) -> Result<(), AppError> {
+    let payload = serde_json::json!({
+        "account_id": after.account_id,
+        "currency": after.currency,
+        "previous_balance": before.balance,
+        "balance": after.balance,
+        "overdraft_limit": after.overdraft_limit,
+        "transaction_id": transaction_id,
+    });
+    for event_type in balance_events(before.balance, after.balance, after.overdraft_limit) {
+        webhook_service::emit_event(after.account_id, event_type, payload.clone(), conn)?;
+    }
+    Ok(())
+}
+
+fn balance_events(before: i64, after: i64, overdraft_limit: i64) -> Vec<&'static str> {
+    let mut events = Vec::new();
+    if before >= 0 && after < 0 {
+        events.push("account.overdrawn");
+    }
+    if before < 0 && after >= 0 {
+        events.push("account.overdraft_cleared");
+    }
+    if overdraft_limit > 0 && before > -overdraft_limit && after <= -overdraft_limit {
+        events.push("account.overdraft_limit_reached");
+    }
+    events
+}
+
+// Closed accounts keep their history but take no new wallets, limits or holds
+fn ensure_not_closed(account: &Account) -> Result<(), AppError> {
+    if account.closed_at.is_some() {
+        return Err(AppError::AccountInactive {
+            account_id: account.id,
+            closed: true,
+        });
+    }
+    Ok(())
+}
+
+fn account_response(
+    account: Account,
+    conn: &mut PgConnection,
+) -> Result<AccountResponse, AppError> {
+    let balances = wallet_balances(account.id, conn)?;
+    let default = default_wallet(&account, &balances);
+
+    Ok(AccountResponse {
+        id: account.id,
+        business_name: account.business_name,
+        balance: default.balance,
+        available_balance: default.available_balance,
+        balance_decimal: default.balance_decimal,
+        available_balance_decimal: default.available_balance_decimal,
+        currency: account.currency,
+        is_active: account.is_active,
+        metadata: account.metadata,
+        balances,
+        closed_at: account.closed_at,
+    })
+}
+
+// Every wallet of an account, with active holds taken off the available balance
+fn wallet_balances(
+    account_id: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<WalletBalance>, AppError> {
+    repositories::get_account_balances(account_id, conn)?
+        .into_iter()
+        .map(|wallet| {
+            let held = repositories::get_held_amount(account_id, &wallet.currency, conn)?;
+            Ok(WalletBalance::new(
+                wallet.currency,
+                wallet.balance,
+                wallet.balance - held,
+                wallet.overdraft_limit,
+            ))
+        })
+        .collect()
+}
+
+fn default_wallet(account: &Account, balances: &[WalletBalance]) -> WalletBalance {
+    balances
+        .iter()
+        .find(|wallet| wallet.currency == account.currency)
+        .cloned()
+        .unwrap_or_else(|| WalletBalance::new(account.currency.clone(), 0, 0, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_balance_events() {
+        assert!(balance_events(500, 100, 0).is_empty());
+        assert_eq!(balance_events(100, -50, 0), vec!["account.overdrawn"]);
+        assert_eq!(
+            balance_events(-50, 0, 1000),
+            vec!["account.overdraft_cleared"]
+        );
+        assert_eq!(
+            balance_events(100, -1000, 1000),
+            vec!["account.overdrawn", "account.overdraft_limit_reached"]
+        );
+        assert_eq!(
+            balance_events(-400, -1000, 1000),
+            vec!["account.overdraft_limit_reached"]
+        );
+        assert!(balance_events(-1000, -1000, 1000).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_time() {
+        let at = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(
+            snapshot_time(at("2026-01-31 15:30:00")),
+            at("2026-01-31 00:00:00")
+        );
+        // Too close to midnight for that day's transactions to have settled
+        assert_eq!(
+            snapshot_time(at("2026-02-01 00:05:00")),
+            at("2026-01-31 00:00:00")
+        );
+        assert_eq!(
+            snapshot_time(at("2026-02-01 00:10:00")),
+            at("2026-02-01 00:00:00")
+        );
+    }
 }
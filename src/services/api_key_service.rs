@@ -10,7 +10,7 @@ pub fn generate_key(
     // Generate random API key
     let prefix = std::env::var("API_KEY_PREFIX").unwrap();
     // let prefix = std::env::var("API_KEY_PREFIX").unwrap_or_else(|_| "sk_prod_".to_string());
-    let raw_key = format!("{}{}", prefix, Uuid::new_v4().to_string());
+    let raw_key = format!("{}{}", prefix, Uuid::new_v4());
     let key_hash = crypto::hash_api_key(&raw_key);
     let key_prefix = raw_key[..20].to_string();
 
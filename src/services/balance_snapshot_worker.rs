@@ -0,0 +1,30 @@
+use crate::{AppState, services::account_service, utils::app_error::AppError};
+use std::{sync::Arc, time::Duration};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// Background task that records a daily balance snapshot of every wallet
+pub async fn run(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let state = state.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut conn = state
+                .db_pool
+                .get()
+                .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+            account_service::take_balance_snapshots(&mut conn)
+        })
+        .await;
+
+        match result {
+            Ok(Ok(0)) => {}
+            Ok(Ok(count)) => tracing::info!(count = count, "Took balance snapshots"),
+            Ok(Err(e)) => tracing::error!(error = ?e, "Balance snapshot run failed"),
+            Err(e) => tracing::error!(error = %e, "Balance snapshot task panicked"),
+        }
+    }
+}
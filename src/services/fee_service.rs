@@ -0,0 +1,305 @@
+use crate::{
+    models::*,
+    repositories,
+    utils::{app_error::AppError, validation},
+};
+use diesel::PgConnection;
+
+// Percentages are stored like FX rates, as fixed-point decimals scaled by FX_RATE_SCALE
+const MAX_PERCENTAGE: i64 = 100 * FX_RATE_SCALE;
+
+// Admin: add the fee schedule of one account, or the global one when no account is given
+pub fn create_fee_schedule(
+    req: CreateFeeScheduleRequest,
+    conn: &mut PgConnection,
+) -> Result<FeeScheduleResponse, AppError> {
+    if req.tx_type == TransactionType::Credit {
+        return Err(AppError::BadRequest(
+            "Fees can only be charged on debits and transfers".to_string(),
+        ));
+    }
+    let currency = validation::normalize_currency(&req.currency).map_err(AppError::BadRequest)?;
+
+    let flat_amount = req.flat_amount.unwrap_or(0);
+    let percentage = req
+        .percentage
+        .as_deref()
+        .map(parse_percentage)
+        .transpose()?;
+    if flat_amount < 0
+        || req.min_fee.is_some_and(|fee| fee < 0)
+        || req.max_fee.is_some_and(|fee| fee < 0)
+    {
+        return Err(AppError::BadRequest(
+            "Fee amounts cannot be negative".to_string(),
+        ));
+    }
+    if let (Some(min_fee), Some(max_fee)) = (req.min_fee, req.max_fee)
+        && min_fee > max_fee
+    {
+        return Err(AppError::BadRequest(
+            "min_fee cannot be greater than max_fee".to_string(),
+        ));
+    }
+
+    let tiers = req.tiers.unwrap_or_default();
+    match req.fee_type {
+        FeeType::Flat => {
+            if flat_amount == 0 || percentage.is_some() || !tiers.is_empty() {
+                return Err(AppError::BadRequest(
+                    "A flat fee takes a positive flat_amount only".to_string(),
+                ));
+            }
+            if req.min_fee.is_some() || req.max_fee.is_some() {
+                return Err(AppError::BadRequest(
+                    "A flat fee cannot have min_fee or max_fee".to_string(),
+                ));
+            }
+        }
+        FeeType::Percentage => {
+            if percentage.is_none() || !tiers.is_empty() {
+                return Err(AppError::BadRequest(
+                    "A percentage fee takes a percentage and an optional flat_amount".to_string(),
+                ));
+            }
+        }
+        FeeType::Tiered => {
+            if flat_amount != 0 || percentage.is_some() {
+                return Err(AppError::BadRequest(
+                    "A tiered fee sets flat_amount and percentage per tier".to_string(),
+                ));
+            }
+            validate_tiers(&tiers)?;
+        }
+    }
+
+    // The fee account must be able to receive fees in the schedule's currency
+    repositories::get_account_by_id(req.fee_account_id, conn)?;
+    if repositories::get_account_balance(req.fee_account_id, &currency, conn)?.is_none() {
+        return Err(AppError::WalletNotFound {
+            account_id: req.fee_account_id,
+            currency,
+        });
+    }
+    if let Some(account_id) = req.account_id {
+        repositories::get_account_by_id(account_id, conn)?;
+        if account_id == req.fee_account_id {
+            return Err(AppError::BadRequest(
+                "The fee account cannot be charged fees".to_string(),
+            ));
+        }
+    }
+
+    let schedule = repositories::create_fee_schedule(
+        &NewFeeSchedule {
+            account_id: req.account_id,
+            tx_type: req.tx_type,
+            currency,
+            fee_type: req.fee_type,
+            flat_amount,
+            percentage: percentage.unwrap_or(0),
+            min_fee: req.min_fee,
+            max_fee: req.max_fee,
+            tiers: serde_json::to_value(&tiers)
+                .map_err(|e| AppError::InternalError(e.to_string()))?,
+            fee_account_id: req.fee_account_id,
+        },
+        conn,
+    )?;
+
+    tracing::info!(
+        fee_schedule_id = schedule.id,
+        account_id = ?schedule.account_id,
+        currency = %schedule.currency,
+        "Fee schedule created"
+    );
+
+    Ok(schedule.into())
+}
+
+pub fn list_fee_schedules(
+    query: ListFeeSchedulesQuery,
+    conn: &mut PgConnection,
+) -> Result<Vec<FeeScheduleResponse>, AppError> {
+    let schedules = repositories::list_fee_schedules(query.account_id, conn)?;
+    Ok(schedules.into_iter().map(Into::into).collect())
+}
+
+pub fn delete_fee_schedule(id: i64, conn: &mut PgConnection) -> Result<(), AppError> {
+    repositories::delete_fee_schedule(id, conn)
+}
+
+// The fee the paying account of a new debit or transfer is charged, if any.
+// Credits have no paying account and the fee account never pays itself.
+pub fn quote_fee(
+    tx_type: TransactionType,
+    from_account_id: Option<i64>,
+    currency: &str,
+    amount: i64,
+    conn: &mut PgConnection,
+) -> Result<Option<AppliedFee>, AppError> {
+    let Some(from_id) = from_account_id.filter(|_| tx_type != TransactionType::Credit) else {
+        return Ok(None);
+    };
+    let Some(schedule) =
+        repositories::get_applicable_fee_schedule(from_id, tx_type, currency, conn)?
+    else {
+        return Ok(None);
+    };
+    if schedule.fee_account_id == from_id {
+        return Ok(None);
+    }
+
+    let fee = compute_fee(&schedule, amount)?;
+    Ok((fee > 0).then_some(AppliedFee {
+        schedule_id: schedule.id,
+        fee_account_id: schedule.fee_account_id,
+        amount: fee,
+    }))
+}
+
+// Flat part plus percentage of the amount (from the tier the amount falls in for tiered
+// schedules), kept within min_fee and max_fee
+fn compute_fee(schedule: &FeeSchedule, amount: i64) -> Result<i64, AppError> {
+    let (flat_amount, percentage) = match schedule.fee_type {
+        FeeType::Flat => (schedule.flat_amount, 0),
+        FeeType::Percentage => (schedule.flat_amount, schedule.percentage),
+        FeeType::Tiered => {
+            let tiers: Vec<FeeTier> = serde_json::from_value(schedule.tiers.clone())
+                .map_err(|e| AppError::InternalError(format!("Invalid fee tiers: {}", e)))?;
+            let tier = tiers
+                .iter()
+                .find(|tier| tier.up_to.is_none_or(|up_to| amount <= up_to))
+                .ok_or_else(|| AppError::InternalError("Fee tiers are not bounded".to_string()))?;
+            let percentage = tier
+                .percentage
+                .as_deref()
+                .map(parse_percentage)
+                .transpose()?;
+            (tier.flat_amount, percentage.unwrap_or(0))
+        }
+    };
+
+    let mut fee = flat_amount.saturating_add(percentage_of(amount, percentage));
+    if let Some(min_fee) = schedule.min_fee {
+        fee = fee.max(min_fee);
+    }
+    if let Some(max_fee) = schedule.max_fee {
+        fee = fee.min(max_fee);
+    }
+    Ok(fee)
+}
+
+// `percentage` percent of `amount`, rounded half up to the nearest minor unit
+fn percentage_of(amount: i64, percentage: i64) -> i64 {
+    let denominator = 100 * i128::from(FX_RATE_SCALE);
+    let fee = (i128::from(amount) * i128::from(percentage) + denominator / 2) / denominator;
+    i64::try_from(fee).unwrap_or(i64::MAX)
+}
+
+fn parse_percentage(value: &str) -> Result<i64, AppError> {
+    parse_fx_rate(value)
+        .filter(|percentage| *percentage <= MAX_PERCENTAGE)
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "percentage must be a positive decimal up to 100 with at most {} decimals",
+                FX_RATE_DECIMALS
+            ))
+        })
+}
+
+// Tiers must go up in order and end with one that has no upper bound
+fn validate_tiers(tiers: &[FeeTier]) -> Result<(), AppError> {
+    let invalid = |reason: &str| Err(AppError::BadRequest(format!("Invalid tiers: {}", reason)));
+
+    let Some((last, bounded)) = tiers.split_last() else {
+        return invalid("a tiered fee needs at least one tier");
+    };
+    if last.up_to.is_some() {
+        return invalid("the last tier must have no up_to");
+    }
+
+    let mut previous = 0;
+    for tier in bounded {
+        match tier.up_to {
+            Some(up_to) if up_to > previous => previous = up_to,
+            _ => return invalid("up_to must be set and increase from tier to tier"),
+        }
+    }
+    for tier in tiers {
+        if tier.flat_amount < 0 {
+            return invalid("flat_amount cannot be negative");
+        }
+        if let Some(percentage) = tier.percentage.as_deref() {
+            parse_percentage(percentage)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn schedule(fee_type: FeeType) -> FeeSchedule {
+        FeeSchedule {
+            id: 1,
+            account_id: None,
+            tx_type: TransactionType::Transfer,
+            currency: "USD".to_string(),
+            fee_type,
+            flat_amount: 0,
+            percentage: 0,
+            min_fee: None,
+            max_fee: None,
+            tiers: serde_json::json!([]),
+            fee_account_id: 2,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn test_percentage_fee_is_rounded_and_clamped() {
+        // 2.9% + 30: 1000 -> 29 + 30
+        let mut percentage = schedule(FeeType::Percentage);
+        percentage.flat_amount = 30;
+        percentage.percentage = 290_000_000;
+        assert_eq!(compute_fee(&percentage, 1000).unwrap(), 59);
+        // 2.9% of 50 is 1.45, rounded to 1
+        assert_eq!(compute_fee(&percentage, 50).unwrap(), 31);
+
+        percentage.min_fee = Some(100);
+        percentage.max_fee = Some(500);
+        assert_eq!(compute_fee(&percentage, 1000).unwrap(), 100);
+        assert_eq!(compute_fee(&percentage, 1_000_000).unwrap(), 500);
+    }
+
+    #[test]
+    fn test_tiered_fee_uses_the_tier_of_the_amount() {
+        let mut tiered = schedule(FeeType::Tiered);
+        tiered.tiers = serde_json::json!([
+            { "up_to": 10000, "flat_amount": 50 },
+            { "up_to": 100000, "percentage": "1" },
+            { "up_to": null, "flat_amount": 500, "percentage": "0.5" },
+        ]);
+        assert_eq!(compute_fee(&tiered, 10000).unwrap(), 50);
+        assert_eq!(compute_fee(&tiered, 10001).unwrap(), 100);
+        assert_eq!(compute_fee(&tiered, 200000).unwrap(), 1500);
+    }
+
+    #[test]
+    fn test_validate_tiers() {
+        let tier = |up_to: Option<i64>| FeeTier {
+            up_to,
+            flat_amount: 10,
+            percentage: None,
+        };
+        assert!(validate_tiers(&[tier(Some(100)), tier(None)]).is_ok());
+        assert!(validate_tiers(&[]).is_err());
+        assert!(validate_tiers(&[tier(Some(100))]).is_err());
+        assert!(validate_tiers(&[tier(Some(100)), tier(Some(50)), tier(None)]).is_err());
+        assert!(validate_tiers(&[tier(None), tier(None)]).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{
+    models::*,
+    repositories,
+    utils::{app_error::AppError, currency, validation},
+};
+use diesel::PgConnection;
+
+// The rate and destination amount applied to a cross-currency transfer
+#[derive(Debug, Clone)]
+pub struct FxConversion {
+    pub rate: i64,
+    pub converted_amount: i64,
+    pub currency: String,
+}
+
+// Admin: create or replace the rate of a currency pair
+pub fn set_fx_rate(
+    req: SetFxRateRequest,
+    conn: &mut PgConnection,
+) -> Result<FxRateResponse, AppError> {
+    let base_currency =
+        validation::normalize_currency(&req.base_currency).map_err(AppError::BadRequest)?;
+    let quote_currency =
+        validation::normalize_currency(&req.quote_currency).map_err(AppError::BadRequest)?;
+    if base_currency == quote_currency {
+        return Err(AppError::BadRequest(
+            "Base and quote currency must differ".to_string(),
+        ));
+    }
+
+    let rate = parse_fx_rate(&req.rate).ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "Rate must be a positive decimal with at most {} decimals",
+            FX_RATE_DECIMALS
+        ))
+    })?;
+
+    let fx_rate = repositories::upsert_fx_rate(
+        &NewFxRate {
+            base_currency,
+            quote_currency,
+            rate,
+        },
+        conn,
+    )?;
+
+    tracing::info!(
+        base = %fx_rate.base_currency,
+        quote = %fx_rate.quote_currency,
+        rate = fx_rate.rate,
+        "FX rate updated"
+    );
+
+    Ok(fx_rate.into())
+}
+
+pub fn list_fx_rates(conn: &mut PgConnection) -> Result<Vec<FxRateResponse>, AppError> {
+    let rates = repositories::list_fx_rates(conn)?;
+    Ok(rates.into_iter().map(Into::into).collect())
+}
+
+pub fn delete_fx_rate(id: i64, conn: &mut PgConnection) -> Result<(), AppError> {
+    repositories::delete_fx_rate(id, conn)
+}
+
+// Decide how a transfer is credited. Money that stays in one currency needs no conversion;
+// a change of currency is only allowed when the caller asked to convert.
+pub fn resolve_conversion(
+    from_currency: &str,
+    to_currency: &str,
+    amount: i64,
+    convert: bool,
+    conn: &mut PgConnection,
+) -> Result<Option<FxConversion>, AppError> {
+    if from_currency == to_currency {
+        return Ok(None);
+    }
+    if !convert {
+        return Err(AppError::CurrencyMismatch {
+            from: from_currency.to_string(),
+            to: to_currency.to_string(),
+        });
+    }
+
+    let fx_rate =
+        repositories::get_fx_rate(from_currency, to_currency, conn)?.ok_or_else(|| {
+            AppError::FxRateNotFound {
+                base: from_currency.to_string(),
+                quote: to_currency.to_string(),
+            }
+        })?;
+
+    Ok(Some(FxConversion {
+        rate: fx_rate.rate,
+        converted_amount: convert_amount(amount, fx_rate.rate, from_currency, to_currency)?,
+        currency: to_currency.to_string(),
+    }))
+}
+
+// Convert minor units of one currency into minor units of another at a scaled rate
+// between their major units, rounding half up to the nearest minor unit
+pub fn convert_amount(
+    amount: i64,
+    rate: i64,
+    from_currency: &str,
+    to_currency: &str,
+) -> Result<i64, AppError> {
+    let numerator =
+        i128::from(amount) * i128::from(rate) * 10i128.pow(currency::exponent(to_currency));
+    let denominator = i128::from(FX_RATE_SCALE) * 10i128.pow(currency::exponent(from_currency));
+    let converted = (numerator + denominator / 2) / denominator;
+    if converted <= 0 {
+        return Err(AppError::BadRequest(
+            "Amount is too small to convert".to_string(),
+        ));
+    }
+    i64::try_from(converted)
+        .map_err(|_| AppError::BadRequest("Converted amount is too large".to_string()))
+}
+
+// Rate for converting back the other way, used to describe reversals
+pub fn invert_rate(rate: i64) -> i64 {
+    let scale = i128::from(FX_RATE_SCALE);
+    let inverted = (scale * scale + i128::from(rate) / 2) / i128::from(rate);
+    i64::try_from(inverted).unwrap_or(i64::MAX).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_amount_rounds_half_up() {
+        // 1.0845: 1000 -> 1084.5 -> 1085
+        assert_eq!(
+            convert_amount(1000, 108_450_000, "USD", "EUR").unwrap(),
+            1085
+        );
+        assert!(convert_amount(1, 1_000, "USD", "EUR").is_err());
+    }
+
+    #[test]
+    fn test_convert_amount_applies_exponents() {
+        // $1.00 at 150 yen per dollar
+        assert_eq!(
+            convert_amount(100, 15_000_000_000, "USD", "JPY").unwrap(),
+            150
+        );
+        // 150 yen back to dollars
+        assert_eq!(convert_amount(150, 666_667, "JPY", "USD").unwrap(), 100);
+        // 10.000 KWD at 3.25 USD per dinar
+        assert_eq!(
+            convert_amount(10_000, 325_000_000, "KWD", "USD").unwrap(),
+            3250
+        );
+    }
+
+    #[test]
+    fn test_invert_rate() {
+        assert_eq!(invert_rate(200_000_000), 50_000_000);
+        assert_eq!(invert_rate(FX_RATE_SCALE), FX_RATE_SCALE);
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{AppState, services::transaction_service, utils::app_error::AppError};
+use std::{sync::Arc, time::Duration};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+// Background task that voids authorizations whose hold has expired
+pub async fn run(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let state = state.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut conn = state
+                .db_pool
+                .get()
+                .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+            transaction_service::expire_authorizations(&mut conn)
+        })
+        .await;
+
+        match result {
+            Ok(Ok(0)) => {}
+            Ok(Ok(count)) => tracing::info!(count = count, "Voided expired authorizations"),
+            Ok(Err(e)) => tracing::error!(error = ?e, "Hold expiry run failed"),
+            Err(e) => tracing::error!(error = %e, "Hold expiry task panicked"),
+        }
+    }
+}
@@ -0,0 +1,368 @@
+use crate::{
+    models::*,
+    repositories,
+    services::transaction_service,
+    utils::{app_error::AppError, db, validation},
+};
+use diesel::{Connection, PgConnection};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+
+const DEFAULT_IMPORT_CHUNK_SIZE: usize = 500;
+
+// A row that passed validation and is ready to be written
+struct ImportRow {
+    line: usize,
+    new_tx: NewTransaction,
+    account_ids: Vec<i64>,
+    convert: bool,
+}
+
+// Import completed credits, debits and transfers from a CSV or NDJSON file.
+// Every row is validated before anything is written; valid rows are then applied
+// in chunks, each chunk in its own database transaction. Rows without an
+// idempotency key get one derived from the upload, what the row moves and how many
+// identical rows precede it. Uploading the same file again, or an edited file under
+// the same upload id, only skips the rows already imported; identical rows in other
+// uploads are imported as new transactions.
+pub fn import_transactions(
+    data: &str,
+    format: ImportFormat,
+    upload_id: Option<&str>,
+    dry_run: bool,
+    actor_key_id: Option<i64>,
+    conn: &mut PgConnection,
+) -> Result<ImportReport, AppError> {
+    let mut report = ImportReport {
+        dry_run,
+        ..Default::default()
+    };
+
+    let scope = upload_scope(data, upload_id)?;
+    let parsed = parse_rows(data, format, &mut report.errors)?;
+    report.total_rows = parsed.len() + report.errors.len();
+
+    let (rows, skipped) = validate_rows(parsed, &scope, &mut report.errors, conn)?;
+    report.skipped = skipped;
+
+    if !dry_run {
+        for chunk in rows.chunks(chunk_size()) {
+            let (imported, skipped, errors) = apply_chunk(chunk, actor_key_id, conn)?;
+            report.imported += imported;
+            report.skipped += skipped;
+            report.errors.extend(errors);
+        }
+    }
+
+    report.errors.sort_by_key(|e| e.line);
+    report.failed = report.errors.len();
+
+    tracing::info!(
+        total = report.total_rows,
+        imported = report.imported,
+        skipped = report.skipped,
+        failed = report.failed,
+        dry_run = dry_run,
+        "Transaction import finished"
+    );
+
+    Ok(report)
+}
+
+// What derived idempotency keys are scoped to: the caller's upload id, or else the
+// file contents so that only a re-upload of the same file is recognised
+fn upload_scope(data: &str, upload_id: Option<&str>) -> Result<String, AppError> {
+    match upload_id.map(str::trim) {
+        Some("") => Err(AppError::BadRequest(
+            "upload_id must not be empty".to_string(),
+        )),
+        Some(id) => Ok(format!("upload:{}", id)),
+        None => Ok(format!(
+            "file:{}",
+            hex::encode(Sha256::digest(data.as_bytes()))
+        )),
+    }
+}
+
+fn parse_rows(
+    data: &str,
+    format: ImportFormat,
+    errors: &mut Vec<ImportRowError>,
+) -> Result<Vec<(usize, CreateTransactionRequest)>, AppError> {
+    let mut rows = Vec::new();
+
+    match format {
+        ImportFormat::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(data.as_bytes());
+            let headers = reader
+                .headers()
+                .map_err(|e| AppError::BadRequest(format!("Invalid CSV header: {}", e)))?
+                .clone();
+
+            for record in reader.records() {
+                match record {
+                    Ok(record) => {
+                        let line = record.position().map_or(0, |p| p.line() as usize);
+                        match record.deserialize::<CreateTransactionRequest>(Some(&headers)) {
+                            Ok(req) => rows.push((line, req)),
+                            Err(e) => errors.push(ImportRowError {
+                                line,
+                                error: format!("Invalid row: {}", e),
+                            }),
+                        }
+                    }
+                    Err(e) => errors.push(ImportRowError {
+                        line: e.position().map_or(0, |p| p.line() as usize),
+                        error: format!("Invalid row: {}", e),
+                    }),
+                }
+            }
+        }
+        ImportFormat::Ndjson => {
+            for (index, text) in data.lines().enumerate() {
+                if text.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<CreateTransactionRequest>(text) {
+                    Ok(req) => rows.push((index + 1, req)),
+                    Err(e) => errors.push(ImportRowError {
+                        line: index + 1,
+                        error: format!("Invalid row: {}", e),
+                    }),
+                }
+            }
+        }
+    }
+
+    Ok(rows)
+}
+
+// Check every row up front. Returns the rows still to import and how many were
+// skipped because their idempotency key has already been imported.
+fn validate_rows(
+    parsed: Vec<(usize, CreateTransactionRequest)>,
+    scope: &str,
+    errors: &mut Vec<ImportRowError>,
+    conn: &mut PgConnection,
+) -> Result<(Vec<ImportRow>, usize), AppError> {
+    let mut seen_keys: HashMap<String, usize> = HashMap::new();
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut rows = Vec::with_capacity(parsed.len());
+
+    // Default currency of every account the file refers to; missing ones do not exist
+    let referenced: Vec<i64> = parsed
+        .iter()
+        .flat_map(|(_, req)| [req.from_account_id, req.to_account_id])
+        .flatten()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let currencies: HashMap<i64, String> = repositories::get_account_currencies(&referenced, conn)?
+        .into_iter()
+        .collect();
+
+    for (line, req) in parsed {
+        let checked = check_row(&req, &currencies).and_then(|(account_ids, currency, metadata)| {
+            let key = match &req.idempotency_key {
+                Some(key) => key.clone(),
+                None => {
+                    let fingerprint = row_fingerprint(scope, &req, &currency, &metadata);
+                    let occurrence = occurrences.entry(fingerprint.clone()).or_default();
+                    *occurrence += 1;
+                    format!("import_{}_{}", fingerprint, occurrence)
+                }
+            };
+            if let Some(first_line) = seen_keys.get(&key) {
+                return Err(AppError::BadRequest(format!(
+                    "Duplicate idempotency key (first used on line {})",
+                    first_line
+                )));
+            }
+            seen_keys.insert(key.clone(), line);
+            Ok((account_ids, currency, metadata, key))
+        });
+
+        match checked {
+            Ok((account_ids, currency, metadata, key)) => rows.push(ImportRow {
+                line,
+                new_tx: NewTransaction {
+                    from_account_id: req.from_account_id,
+                    to_account_id: req.to_account_id,
+                    amount: req.amount,
+                    tx_type: req.tx_type,
+                    status: TransactionStatus::Completed,
+                    description: req.description,
+                    idempotency_key: Some(key),
+                    reversal_of_id: None,
+                    batch_id: None,
+                    execute_at: None,
+                    fx_rate: None,
+                    converted_amount: None,
+                    converted_currency: None,
+                    currency,
+                    metadata,
+                    fee_amount: 0,
+                    fee_account_id: None,
+                },
+                account_ids,
+                convert: req.convert.unwrap_or(false),
+            }),
+            Err(e) => errors.push(ImportRowError {
+                line,
+                error: e.message(),
+            }),
+        }
+    }
+
+    // Rows from an earlier upload are skipped rather than reported
+    let keys: Vec<String> = rows
+        .iter()
+        .filter_map(|row| row.new_tx.idempotency_key.clone())
+        .collect();
+    let imported_keys: HashSet<String> = repositories::get_existing_idempotency_keys(&keys, conn)?
+        .into_iter()
+        .collect();
+
+    let before = rows.len();
+    rows.retain(|row| {
+        row.new_tx
+            .idempotency_key
+            .as_ref()
+            .is_none_or(|key| !imported_keys.contains(key))
+    });
+
+    let skipped = before - rows.len();
+
+    Ok((rows, skipped))
+}
+
+// Returns the accounts a row touches, the currency it moves and its metadata
+fn check_row(
+    req: &CreateTransactionRequest,
+    currencies: &HashMap<i64, String>,
+) -> Result<(Vec<i64>, String, Value), AppError> {
+    if req.amount <= 0 {
+        return Err(AppError::BadRequest("Amount must be positive".to_string()));
+    }
+    if req.capture == Some(false) {
+        return Err(AppError::BadRequest(
+            "Imported transactions cannot be authorizations".to_string(),
+        ));
+    }
+    if req.execute_at.is_some() {
+        return Err(AppError::BadRequest(
+            "Imported transactions cannot be scheduled".to_string(),
+        ));
+    }
+
+    let account_ids =
+        transaction_service::account_ids(req.tx_type, req.from_account_id, req.to_account_id)?;
+    if let Some(id) = account_ids.iter().find(|id| !currencies.contains_key(id)) {
+        return Err(AppError::BadRequest(format!("Account {} not found", id)));
+    }
+
+    // Like the API, default to the currency of the paying (or receiving) account
+    let currency = match &req.currency {
+        Some(currency) => validation::normalize_currency(currency).map_err(AppError::BadRequest)?,
+        None => currencies[&account_ids[0]].clone(),
+    };
+
+    // CSV cells carry the metadata object as JSON text
+    let metadata = match &req.metadata {
+        Some(Value::String(text)) => Some(
+            serde_json::from_str(text)
+                .map_err(|_| AppError::BadRequest("metadata must be a JSON object".to_string()))?,
+        ),
+        other => other.clone(),
+    };
+    let metadata = validation::validate_metadata(metadata).map_err(AppError::BadRequest)?;
+
+    Ok((account_ids, currency, metadata))
+}
+
+// Digest of the upload and everything a row moves, independent of where the row
+// sits in the file
+fn row_fingerprint(
+    scope: &str,
+    req: &CreateTransactionRequest,
+    currency: &str,
+    metadata: &Value,
+) -> String {
+    let fields = serde_json::json!([
+        scope,
+        req.tx_type,
+        req.from_account_id,
+        req.to_account_id,
+        req.amount,
+        currency,
+        req.description,
+        metadata,
+        req.convert.unwrap_or(false),
+    ]);
+    hex::encode(&Sha256::digest(fields.to_string().as_bytes())[..16])
+}
+
+// Write one chunk in a single database transaction. Each row runs in a savepoint so
+// a row failing at write time (e.g. insufficient balance) does not undo the others.
+fn apply_chunk(
+    chunk: &[ImportRow],
+    actor_key_id: Option<i64>,
+    conn: &mut PgConnection,
+) -> Result<(usize, usize, Vec<ImportRowError>), AppError> {
+    let account_ids: Vec<i64> = chunk
+        .iter()
+        .flat_map(|row| row.account_ids.iter().copied())
+        .collect();
+    let keys: Vec<String> = chunk
+        .iter()
+        .filter_map(|row| row.new_tx.idempotency_key.clone())
+        .collect();
+
+    db::run_in_transaction(conn, |conn| {
+        repositories::lock_accounts(&account_ids, conn)?;
+
+        // A concurrent upload of the same file may have imported some rows meanwhile
+        let imported_keys: HashSet<String> =
+            repositories::get_existing_idempotency_keys(&keys, conn)?
+                .into_iter()
+                .collect();
+
+        let (mut imported, mut skipped, mut errors) = (0, 0, Vec::new());
+        for row in chunk {
+            if row
+                .new_tx
+                .idempotency_key
+                .as_ref()
+                .is_some_and(|key| imported_keys.contains(key))
+            {
+                skipped += 1;
+                continue;
+            }
+
+            let result = conn.transaction(|conn| {
+                transaction_service::post_transaction(&row.new_tx, row.convert, actor_key_id, conn)
+            });
+            match result {
+                Ok(_) => imported += 1,
+                Err(AppError::TransactionConflict) => return Err(AppError::TransactionConflict),
+                Err(e) => errors.push(ImportRowError {
+                    line: row.line,
+                    error: e.message(),
+                }),
+            }
+        }
+
+        Ok((imported, skipped, errors))
+    })
+}
+
+fn chunk_size() -> usize {
+    std::env::var("IMPORT_CHUNK_SIZE")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|&size| size > 0)
+        .unwrap_or(DEFAULT_IMPORT_CHUNK_SIZE)
+}
@@ -0,0 +1,264 @@
+use crate::{
+    models::*,
+    repositories,
+    utils::{app_error::AppError, validation},
+};
+use chrono::{Datelike, NaiveDateTime, Utc};
+use diesel::PgConnection;
+
+// Admin: replace the limits of an account or API key in one currency
+pub fn set_limits(
+    scope: LimitScope,
+    scope_id: i64,
+    req: SetTransactionLimitsRequest,
+    conn: &mut PgConnection,
+) -> Result<TransactionLimitResponse, AppError> {
+    let account_id = scope_account(scope, scope_id, conn)?;
+    let currency = match req.currency.as_deref() {
+        Some(currency) => validation::normalize_currency(currency).map_err(AppError::BadRequest)?,
+        None => repositories::get_account_by_id(account_id, conn)?.currency,
+    };
+
+    if req.max_amount.is_some_and(|max| max <= 0) {
+        return Err(AppError::BadRequest(
+            "max_amount must be positive".to_string(),
+        ));
+    }
+    if req.max_daily_outflow.is_some_and(|max| max < 0)
+        || req.max_monthly_outflow.is_some_and(|max| max < 0)
+        || req.max_daily_transactions.is_some_and(|max| max < 0)
+    {
+        return Err(AppError::BadRequest(
+            "Limits cannot be negative".to_string(),
+        ));
+    }
+
+    let limit = repositories::upsert_transaction_limit(
+        &NewTransactionLimit {
+            account_id: (scope == LimitScope::Account).then_some(scope_id),
+            api_key_id: (scope == LimitScope::ApiKey).then_some(scope_id),
+            currency,
+            max_amount: req.max_amount,
+            max_daily_outflow: req.max_daily_outflow,
+            max_monthly_outflow: req.max_monthly_outflow,
+            max_daily_transactions: req.max_daily_transactions,
+        },
+        conn,
+    )?;
+
+    tracing::info!(
+        scope = scope.as_str(),
+        scope_id = scope_id,
+        currency = %limit.currency,
+        "Transaction limits updated"
+    );
+
+    let usage = get_usage(scope, scope_id, &limit.currency, conn)?;
+    Ok(TransactionLimitResponse::new(scope, scope_id, limit, usage))
+}
+
+// Limits of an account or API key in every currency, with their current usage
+pub fn get_limits(
+    scope: LimitScope,
+    scope_id: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<TransactionLimitResponse>, AppError> {
+    scope_account(scope, scope_id, conn)?;
+
+    repositories::get_transaction_limits(scope, scope_id, conn)?
+        .into_iter()
+        .map(|limit| {
+            let usage = get_usage(scope, scope_id, &limit.currency, conn)?;
+            Ok(TransactionLimitResponse::new(scope, scope_id, limit, usage))
+        })
+        .collect()
+}
+
+// Refuse a new debit or transfer that would break the limits of the paying account or
+// of the API key creating it. The paying account must already be locked so that
+// concurrent transactions cannot both squeeze under a limit.
+pub fn enforce_limits(
+    from_account_id: i64,
+    actor_key_id: Option<i64>,
+    currency: &str,
+    amount: i64,
+    fee_amount: i64,
+    conn: &mut PgConnection,
+) -> Result<(), AppError> {
+    let scopes = std::iter::once((LimitScope::Account, from_account_id))
+        .chain(actor_key_id.map(|key_id| (LimitScope::ApiKey, key_id)));
+
+    for (scope, scope_id) in scopes {
+        let Some(limit) = repositories::get_transaction_limit(scope, scope_id, currency, conn)?
+        else {
+            continue;
+        };
+        let usage = get_usage(scope, scope_id, currency, conn)?;
+        if let Some(breach) = check_limit(&limit, usage, amount, amount + fee_amount) {
+            return Err(AppError::LimitExceeded {
+                scope,
+                scope_id,
+                currency: currency.to_string(),
+                limit: breach.limit,
+                max: breach.max,
+                remaining: breach.remaining,
+            });
+        }
+    }
+    Ok(())
+}
+
+// The first limit a transaction of `amount` taking `total` (amount and fee) out of the
+// wallet would break, given what was used so far
+fn check_limit(
+    limit: &TransactionLimit,
+    usage: LimitUsage,
+    amount: i64,
+    total: i64,
+) -> Option<LimitBreach> {
+    let breach = |limit: &'static str, max: i64, used: i64| LimitBreach {
+        limit,
+        max,
+        remaining: (max - used).max(0),
+    };
+
+    if let Some(max) = limit.max_amount
+        && amount > max
+    {
+        return Some(breach("max_amount", max, 0));
+    }
+    if let Some(max) = limit.max_daily_transactions.map(i64::from)
+        && usage.daily_transactions + 1 > max
+    {
+        return Some(breach(
+            "max_daily_transactions",
+            max,
+            usage.daily_transactions,
+        ));
+    }
+    if let Some(max) = limit.max_daily_outflow
+        && usage.daily_outflow + total > max
+    {
+        return Some(breach("max_daily_outflow", max, usage.daily_outflow));
+    }
+    if let Some(max) = limit.max_monthly_outflow
+        && usage.monthly_outflow + total > max
+    {
+        return Some(breach("max_monthly_outflow", max, usage.monthly_outflow));
+    }
+    None
+}
+
+// Outflow of the current UTC day and month
+fn get_usage(
+    scope: LimitScope,
+    scope_id: i64,
+    currency: &str,
+    conn: &mut PgConnection,
+) -> Result<LimitUsage, AppError> {
+    let (day_start, month_start) = period_starts(Utc::now().naive_utc());
+    let (daily_outflow, daily_transactions) =
+        repositories::get_outflow_usage(scope, scope_id, currency, day_start, conn)?;
+    let (monthly_outflow, _) =
+        repositories::get_outflow_usage(scope, scope_id, currency, month_start, conn)?;
+
+    Ok(LimitUsage {
+        daily_outflow,
+        monthly_outflow,
+        daily_transactions,
+    })
+}
+
+// Midnight of the day and of the first day of the month `now` falls in
+fn period_starts(now: NaiveDateTime) -> (NaiveDateTime, NaiveDateTime) {
+    let day = now.date();
+    let month = day.with_day(1).unwrap_or(day);
+    (
+        day.and_hms_opt(0, 0, 0).unwrap_or(now),
+        month.and_hms_opt(0, 0, 0).unwrap_or(now),
+    )
+}
+
+// The account a scope belongs to, checking that it exists. Admin keys cannot
+// create transactions, so they have no limits.
+fn scope_account(
+    scope: LimitScope,
+    scope_id: i64,
+    conn: &mut PgConnection,
+) -> Result<i64, AppError> {
+    match scope {
+        LimitScope::Account => Ok(repositories::get_account_by_id(scope_id, conn)?.id),
+        LimitScope::ApiKey => repositories::get_api_key_by_id(scope_id, conn)?
+            .account_id
+            .ok_or_else(|| {
+                AppError::BadRequest("Admin keys cannot have transaction limits".to_string())
+            }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_limit() {
+        let limit = TransactionLimit {
+            id: 1,
+            account_id: Some(1),
+            api_key_id: None,
+            currency: "USD".to_string(),
+            max_amount: Some(1000),
+            max_daily_outflow: Some(2000),
+            max_monthly_outflow: Some(5000),
+            max_daily_transactions: Some(3),
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+        };
+        let usage = LimitUsage {
+            daily_outflow: 1500,
+            monthly_outflow: 4000,
+            daily_transactions: 2,
+        };
+
+        assert_eq!(check_limit(&limit, usage, 500, 500), None);
+        assert_eq!(
+            check_limit(&limit, usage, 1001, 1001).map(|b| (b.limit, b.remaining)),
+            Some(("max_amount", 1000))
+        );
+        // The fee counts towards outflow but not towards the single transaction amount
+        assert_eq!(
+            check_limit(&limit, usage, 500, 510).map(|b| (b.limit, b.remaining)),
+            Some(("max_daily_outflow", 500))
+        );
+
+        let usage = LimitUsage {
+            daily_transactions: 3,
+            ..usage
+        };
+        assert_eq!(
+            check_limit(&limit, usage, 100, 100).map(|b| (b.limit, b.remaining)),
+            Some(("max_daily_transactions", 0))
+        );
+
+        let usage = LimitUsage {
+            daily_outflow: 0,
+            monthly_outflow: 4900,
+            daily_transactions: 0,
+        };
+        assert_eq!(
+            check_limit(&limit, usage, 200, 200).map(|b| (b.limit, b.remaining)),
+            Some(("max_monthly_outflow", 100))
+        );
+    }
+
+    #[test]
+    fn test_period_starts() {
+        let now = chrono::NaiveDate::from_ymd_opt(2026, 3, 17)
+            .unwrap()
+            .and_hms_opt(15, 4, 5)
+            .unwrap();
+        let (day, month) = period_starts(now);
+        assert_eq!(day.to_string(), "2026-03-17 00:00:00");
+        assert_eq!(month.to_string(), "2026-03-01 00:00:00");
+    }
+}
@@ -2,4 +2,16 @@ pub mod account_service;
 pub mod transaction_service;
 pub mod api_key_service;
 pub mod webhook_service;
+pub mod hold_expiry_worker;
+pub mod import_service;
+pub mod scheduler_worker;
+pub mod recurring_transfer_service;
+pub mod fx_service;
+pub mod balance_snapshot_worker;
+pub mod statement_service;
+pub mod reconciliation_service;
+pub mod reconciliation_worker;
+pub mod fee_service;
+pub mod limit_service;
+pub mod risk_service;
 // pub mod webhook_worker;
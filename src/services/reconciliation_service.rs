@@ -0,0 +1,260 @@
+use crate::{
+    models::{
+        JournalMismatch, JournalProblem, PostingLeg, ReconciliationReport, Transaction, WalletDrift,
+    },
+    repositories,
+    services::{transaction_service, webhook_service},
+    utils::app_error::AppError,
+};
+use chrono::Utc;
+use diesel::PgConnection;
+use std::collections::{BTreeMap, HashMap};
+
+// Recompute every wallet's balance from its postings and report the wallets whose
+// stored balance disagrees, then check every completed transaction against its
+// journal entry. With `alert`, each drifted wallet also emits account.balance_drift
+// to the account's webhooks.
+pub fn reconcile(alert: bool, conn: &mut PgConnection) -> Result<ReconciliationReport, AppError> {
+    // All reads come from one snapshot so in-flight postings cannot show up as drift
+    let (stored, ledger, transactions, entries, postings) = conn
+        .build_transaction()
+        .repeatable_read()
+        .read_only()
+        .run(|conn| {
+            Ok::<_, AppError>((
+                repositories::get_all_wallet_balances(conn)?,
+                repositories::sum_postings_by_wallet(conn)?,
+                repositories::get_posted_transactions(conn)?,
+                repositories::get_all_journal_entry_ids(conn)?,
+                repositories::get_all_posting_legs(conn)?,
+            ))
+        })?;
+
+    let report = ReconciliationReport {
+        checked_at: Utc::now().naive_utc(),
+        wallets_checked: stored.len(),
+        drifted: find_drift(stored, ledger),
+        transactions_checked: transactions.len(),
+        mismatched: find_journal_mismatches(&transactions, entries, postings),
+    };
+
+    for mismatch in &report.mismatched {
+        tracing::warn!(
+            transaction_id = mismatch.transaction_id,
+            problem = ?mismatch.problem,
+            "Transaction disagrees with the journal"
+        );
+    }
+
+    for drift in &report.drifted {
+        tracing::warn!(
+            account_id = drift.account_id,
+            currency = %drift.currency,
+            stored_balance = drift.stored_balance,
+            ledger_balance = drift.ledger_balance,
+            delta = drift.delta,
+            "Wallet balance drift detected"
+        );
+        if alert {
+            let payload = serde_json::to_value(drift).unwrap_or_default();
+            webhook_service::emit_event(drift.account_id, "account.balance_drift", payload, conn)?;
+        }
+    }
+
+    Ok(report)
+}
+
+// Pair stored balances with posted totals per (account_id, currency). Postings to a
+// wallet that does not exist count against a stored balance of zero.
+fn find_drift(
+    stored: Vec<(i64, String, i64)>,
+    ledger: Vec<(i64, String, i64)>,
+) -> Vec<WalletDrift> {
+    let mut wallets: BTreeMap<(i64, String), (i64, i64)> = BTreeMap::new();
+    for (account_id, currency, balance) in stored {
+        wallets.entry((account_id, currency)).or_default().0 = balance;
+    }
+    for (account_id, currency, total) in ledger {
+        wallets.entry((account_id, currency)).or_default().1 = total;
+    }
+
+    wallets
+        .into_iter()
+        .filter(|(_, (stored, ledger))| stored != ledger)
+        .map(|((account_id, currency), (stored, ledger))| {
+            WalletDrift::new(account_id, currency, stored, ledger)
+        })
+        .collect()
+}
+
+// Pair each completed or reversed transaction with the journal entries written for it
+// and compare their postings with the legs its amounts call for. Entries left over
+// belong to transactions that never completed.
+fn find_journal_mismatches(
+    transactions: &[Transaction],
+    entries: Vec<(i64, i64)>,
+    postings: Vec<(i64, PostingLeg)>,
+) -> Vec<JournalMismatch> {
+    let mut legs_by_entry: HashMap<i64, Vec<PostingLeg>> = HashMap::new();
+    for (entry_id, leg) in postings {
+        legs_by_entry.entry(entry_id).or_default().push(leg);
+    }
+    let mut entries_by_tx: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+    for (entry_id, transaction_id) in entries {
+        entries_by_tx
+            .entry(transaction_id)
+            .or_default()
+            .push(entry_id);
+    }
+    let mut posted = |entry_ids: &[i64]| {
+        sorted_legs(
+            entry_ids
+                .iter()
+                .flat_map(|id| legs_by_entry.remove(id).unwrap_or_default())
+                .collect(),
+        )
+    };
+
+    let mut mismatched = Vec::new();
+    for tx in transactions {
+        let entry_ids = entries_by_tx.remove(&tx.id).unwrap_or_default();
+        let postings = posted(&entry_ids);
+        // A transaction whose accounts do not fit its type cannot have matching postings
+        let expected_postings =
+            sorted_legs(transaction_service::expected_legs(tx).unwrap_or_default());
+
+        let problem = match entry_ids.len() {
+            0 => JournalProblem::MissingEntry,
+            1 if postings == expected_postings => continue,
+            1 => JournalProblem::PostingsMismatch,
+            _ => JournalProblem::DuplicateEntries,
+        };
+        mismatched.push(JournalMismatch {
+            transaction_id: tx.id,
+            problem,
+            expected_postings,
+            postings,
+        });
+    }
+
+    for (transaction_id, entry_ids) in entries_by_tx {
+        mismatched.push(JournalMismatch {
+            transaction_id,
+            problem: JournalProblem::UnexpectedEntry,
+            expected_postings: Vec::new(),
+            postings: posted(&entry_ids),
+        });
+    }
+
+    mismatched.sort_by_key(|mismatch| mismatch.transaction_id);
+    mismatched
+}
+
+fn sorted_legs(mut legs: Vec<PostingLeg>) -> Vec<PostingLeg> {
+    legs.sort_by(|a, b| {
+        (a.account_id, &a.currency, a.amount).cmp(&(b.account_id, &b.currency, b.amount))
+    });
+    legs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{TransactionStatus, TransactionType};
+
+    #[test]
+    fn test_find_drift() {
+        let stored = vec![
+            (1, "USD".to_string(), 500),
+            (1, "EUR".to_string(), 0),
+            (2, "USD".to_string(), 120),
+        ];
+        let ledger = vec![
+            (1, "USD".to_string(), 500),
+            (2, "USD".to_string(), 100),
+            (3, "USD".to_string(), -40),
+        ];
+
+        let drift: Vec<_> = find_drift(stored, ledger)
+            .into_iter()
+            .map(|d| (d.account_id, d.currency, d.delta))
+            .collect();
+        assert_eq!(
+            drift,
+            vec![(2, "USD".to_string(), 20), (3, "USD".to_string(), 40)]
+        );
+    }
+
+    fn credit(id: i64, to_account_id: i64, amount: i64) -> Transaction {
+        let now = Utc::now().naive_utc();
+        Transaction {
+            id,
+            from_account_id: None,
+            to_account_id: Some(to_account_id),
+            amount,
+            tx_type: TransactionType::Credit,
+            status: TransactionStatus::Completed,
+            description: None,
+            idempotency_key: None,
+            created_at: now,
+            updated_at: now,
+            reversal_of_id: None,
+            refunded_amount: 0,
+            batch_id: None,
+            execute_at: None,
+            fx_rate: None,
+            converted_amount: None,
+            converted_currency: None,
+            currency: "USD".to_string(),
+            metadata: serde_json::json!({}),
+            fee_amount: 0,
+            fee_account_id: None,
+        }
+    }
+
+    fn leg(account_id: Option<i64>, amount: i64) -> PostingLeg {
+        PostingLeg {
+            account_id,
+            currency: "USD".to_string(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_find_journal_mismatches() {
+        let transactions = vec![credit(1, 7, 100), credit(2, 7, 200), credit(3, 7, 300)];
+        // Transaction 1 matches, 2 was posted for the wrong amount, 3 was never posted
+        // and 4 has an entry although it did not complete
+        let entries = vec![(10, 1), (20, 2), (40, 4)];
+        let postings = vec![
+            (10, leg(Some(7), 100)),
+            (10, leg(None, -100)),
+            (20, leg(None, -250)),
+            (20, leg(Some(7), 250)),
+            (40, leg(Some(7), 50)),
+            (40, leg(None, -50)),
+        ];
+
+        let mismatched = find_journal_mismatches(&transactions, entries, postings);
+        let problems: Vec<_> = mismatched
+            .iter()
+            .map(|m| (m.transaction_id, m.problem))
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                (2, JournalProblem::PostingsMismatch),
+                (3, JournalProblem::MissingEntry),
+                (4, JournalProblem::UnexpectedEntry),
+            ]
+        );
+        assert_eq!(
+            mismatched[0].expected_postings,
+            vec![leg(None, -200), leg(Some(7), 200)]
+        );
+        assert_eq!(
+            mismatched[0].postings,
+            vec![leg(None, -250), leg(Some(7), 250)]
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use crate::{AppState, services::reconciliation_service, utils::app_error::AppError};
+use std::{sync::Arc, time::Duration};
+
+const DEFAULT_CHECK_INTERVAL_SECONDS: u64 = 60 * 60;
+
+// Background task that checks every wallet balance and completed transaction
+// against the journal.
+// RECONCILIATION_ALERTS=true also emits account.balance_drift webhooks.
+pub async fn run(state: Arc<AppState>) {
+    let seconds = std::env::var("RECONCILIATION_INTERVAL_SECONDS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_CHECK_INTERVAL_SECONDS);
+    let alert = std::env::var("RECONCILIATION_ALERTS").is_ok_and(|value| value == "true");
+    let mut interval = tokio::time::interval(Duration::from_secs(seconds.max(1)));
+
+    loop {
+        interval.tick().await;
+
+        let state = state.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut conn = state
+                .db_pool
+                .get()
+                .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+            reconciliation_service::reconcile(alert, &mut conn)
+        })
+        .await;
+
+        match result {
+            Ok(Ok(report)) if report.drifted.is_empty() && report.mismatched.is_empty() => {}
+            Ok(Ok(report)) => tracing::error!(
+                wallets = report.drifted.len(),
+                transactions = report.mismatched.len(),
+                "Reconciliation found balance drift or journal mismatches"
+            ),
+            Ok(Err(e)) => tracing::error!(error = ?e, "Reconciliation run failed"),
+            Err(e) => tracing::error!(error = %e, "Reconciliation task panicked"),
+        }
+    }
+}
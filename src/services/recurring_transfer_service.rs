@@ -0,0 +1,509 @@
+use crate::{
+    models::*,
+    repositories,
+    services::{transaction_service, webhook_service},
+    utils::{app_error::AppError, db},
+};
+use chrono::{Duration, Months, NaiveDateTime, Utc};
+use diesel::{Connection, PgConnection};
+use std::str::FromStr;
+
+const DEFAULT_MISSED_RUN_GRACE_SECONDS: i64 = 60 * 60;
+const DUE_BATCH_SIZE: i64 = 100;
+// Upper bound on occurrences handled for one schedule per scheduler pass
+const MAX_RUNS_PER_PASS: usize = 100;
+
+// How occurrences of a schedule are generated
+enum Rule {
+    Interval {
+        unit: RecurrenceInterval,
+        count: i32,
+    },
+    Cron(Box<cron::Schedule>),
+}
+
+pub fn create_recurring_transfer(
+    req: CreateRecurringTransferRequest,
+    conn: &mut PgConnection,
+) -> Result<RecurringTransfer, AppError> {
+    if req.amount <= 0 {
+        return Err(AppError::BadRequest("Amount must be positive".to_string()));
+    }
+    if req.from_account_id == req.to_account_id {
+        return Err(AppError::BadRequest(
+            "Cannot transfer to same account".to_string(),
+        ));
+    }
+    if req.max_occurrences.is_some_and(|max| max <= 0) {
+        return Err(AppError::BadRequest(
+            "max_occurrences must be positive".to_string(),
+        ));
+    }
+
+    let interval_count = req.interval_count.unwrap_or(1);
+    let rule = match (req.interval, req.cron.as_deref()) {
+        (Some(unit), None) => {
+            if interval_count <= 0 {
+                return Err(AppError::BadRequest(
+                    "interval_count must be positive".to_string(),
+                ));
+            }
+            Rule::Interval {
+                unit,
+                count: interval_count,
+            }
+        }
+        (None, Some(expression)) => parse_cron(expression)?,
+        _ => {
+            return Err(AppError::BadRequest(
+                "Exactly one of interval or cron is required".to_string(),
+            ));
+        }
+    };
+
+    let start_at = req
+        .start_at
+        .map_or_else(|| Utc::now().naive_utc(), |at| at.naive_utc());
+    let end_at = req.end_at.map(|at| at.naive_utc());
+    if end_at.is_some_and(|end| end <= start_at) {
+        return Err(AppError::BadRequest(
+            "end_at must be after start_at".to_string(),
+        ));
+    }
+
+    let first_run = first_occurrence(&rule, start_at)
+        .filter(|at| end_at.is_none_or(|end| *at <= end))
+        .ok_or_else(|| AppError::BadRequest("Schedule has no occurrences".to_string()))?;
+
+    // Runs move the payer's default currency, which the payee must hold
+    let from = repositories::get_account_by_id(req.from_account_id, conn)?;
+    let to = repositories::get_account_by_id(req.to_account_id, conn)?;
+    if repositories::get_account_balance(to.id, &from.currency, conn)?.is_none() {
+        return Err(AppError::BadRequest(format!(
+            "Recurring transfers must be in one currency, account {} has no {} wallet",
+            to.id, from.currency
+        )));
+    }
+
+    let transfer = repositories::create_recurring_transfer(
+        &NewRecurringTransfer {
+            from_account_id: req.from_account_id,
+            to_account_id: req.to_account_id,
+            amount: req.amount,
+            description: req.description,
+            interval_unit: req.interval,
+            interval_count,
+            cron_expression: req.cron,
+            start_at,
+            end_at,
+            max_occurrences: req.max_occurrences,
+            next_run_at: Some(first_run),
+            catch_up_policy: req.catch_up_policy.unwrap_or_else(default_catch_up_policy),
+        },
+        conn,
+    )?;
+
+    tracing::info!(
+        recurring_transfer_id = transfer.id,
+        next_run_at = %first_run,
+        "Recurring transfer created"
+    );
+
+    Ok(transfer)
+}
+
+pub fn get_recurring_transfer(
+    id: i64,
+    conn: &mut PgConnection,
+) -> Result<RecurringTransfer, AppError> {
+    repositories::get_recurring_transfer_by_id(id, conn)
+}
+
+pub fn list_account_recurring_transfers(
+    account_id: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<RecurringTransfer>, AppError> {
+    repositories::get_recurring_transfers_by_account(account_id, conn)
+}
+
+pub fn list_recurring_transfer_runs(
+    id: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<RecurringTransferRun>, AppError> {
+    repositories::get_recurring_transfer_by_id(id, conn)?;
+    repositories::get_recurring_transfer_runs(id, conn)
+}
+
+pub fn pause_recurring_transfer(
+    id: i64,
+    conn: &mut PgConnection,
+) -> Result<RecurringTransfer, AppError> {
+    db::run_in_transaction(conn, |conn| {
+        let transfer = repositories::get_recurring_transfer_for_update(id, conn)?;
+        if transfer.status != RecurringTransferStatus::Active {
+            return Err(AppError::Conflict(
+                "Only active recurring transfers can be paused".to_string(),
+            ));
+        }
+
+        repositories::update_recurring_transfer_schedule(
+            transfer.id,
+            RecurringTransferStatus::Paused,
+            transfer.next_run_at,
+            transfer.occurrences,
+            conn,
+        )
+    })
+}
+
+// Occurrences that fell inside the pause are not caught up; the schedule
+// continues with its next occurrence from now on
+pub fn resume_recurring_transfer(
+    id: i64,
+    conn: &mut PgConnection,
+) -> Result<RecurringTransfer, AppError> {
+    db::run_in_transaction(conn, |conn| {
+        let transfer = repositories::get_recurring_transfer_for_update(id, conn)?;
+        if transfer.status != RecurringTransferStatus::Paused {
+            return Err(AppError::Conflict(
+                "Only paused recurring transfers can be resumed".to_string(),
+            ));
+        }
+
+        let rule = rule_for(&transfer)?;
+        let now = Utc::now().naive_utc();
+        let next_run_at = match transfer.next_run_at {
+            Some(at) if at > now => Some(at),
+            _ => next_occurrence(&rule, transfer.start_at, now),
+        }
+        .filter(|at| within_limits(&transfer, *at, transfer.occurrences));
+
+        let status = if next_run_at.is_some() {
+            RecurringTransferStatus::Active
+        } else {
+            RecurringTransferStatus::Completed
+        };
+
+        repositories::update_recurring_transfer_schedule(
+            transfer.id,
+            status,
+            next_run_at,
+            transfer.occurrences,
+            conn,
+        )
+    })
+}
+
+pub fn cancel_recurring_transfer(
+    id: i64,
+    conn: &mut PgConnection,
+) -> Result<RecurringTransfer, AppError> {
+    db::run_in_transaction(conn, |conn| {
+        let transfer = repositories::get_recurring_transfer_for_update(id, conn)?;
+        if !matches!(
+            transfer.status,
+            RecurringTransferStatus::Active | RecurringTransferStatus::Paused
+        ) {
+            return Err(AppError::Conflict(
+                "Recurring transfer has already ended".to_string(),
+            ));
+        }
+
+        repositories::update_recurring_transfer_schedule(
+            transfer.id,
+            RecurringTransferStatus::Cancelled,
+            None,
+            transfer.occurrences,
+            conn,
+        )
+    })
+}
+
+// Run every due occurrence of active schedules. Returns how many runs were recorded.
+pub fn run_due_recurring_transfers(conn: &mut PgConnection) -> Result<usize, AppError> {
+    let due = repositories::get_due_recurring_transfer_ids(DUE_BATCH_SIZE, conn)?;
+
+    let mut runs = 0;
+    for id in due {
+        runs += db::run_in_transaction(conn, |conn| run_recurring_transfer(id, conn))?;
+    }
+
+    Ok(runs)
+}
+
+fn run_recurring_transfer(id: i64, conn: &mut PgConnection) -> Result<usize, AppError> {
+    let transfer = repositories::get_recurring_transfer_for_update(id, conn)?;
+    let now = Utc::now().naive_utc();
+
+    // Paused, cancelled or already handled since the due schedules were loaded
+    if transfer.status != RecurringTransferStatus::Active
+        || transfer.next_run_at.is_none_or(|at| at > now)
+    {
+        return Ok(0);
+    }
+
+    let rule = rule_for(&transfer)?;
+
+    // Every occurrence that has come due, including ones missed during downtime
+    let mut due = Vec::new();
+    let mut next = transfer.next_run_at;
+    while let Some(at) = next
+        && at <= now
+        && due.len() < MAX_RUNS_PER_PASS
+    {
+        due.push(at);
+        next = next_occurrence(&rule, transfer.start_at, at)
+            .filter(|at| within_limits(&transfer, *at, transfer.occurrences + due.len() as i32));
+    }
+
+    let missed_before = now - missed_run_grace();
+    let latest = due.last().copied();
+    for &scheduled_for in &due {
+        let run = match transfer.catch_up_policy {
+            CatchUpPolicy::RunAll => true,
+            CatchUpPolicy::RunLatest => Some(scheduled_for) == latest,
+            CatchUpPolicy::Skip => scheduled_for >= missed_before,
+        };
+
+        let new_run = if run {
+            execute_occurrence(&transfer, scheduled_for, conn)?
+        } else {
+            NewRecurringTransferRun {
+                recurring_transfer_id: transfer.id,
+                scheduled_for,
+                status: RecurringRunStatus::Skipped,
+                transaction_id: None,
+                error: Some("Missed while the scheduler was not running".to_string()),
+            }
+        };
+        repositories::create_recurring_transfer_run(&new_run, conn)?;
+    }
+
+    let status = if next.is_some() {
+        RecurringTransferStatus::Active
+    } else {
+        RecurringTransferStatus::Completed
+    };
+    repositories::update_recurring_transfer_schedule(
+        transfer.id,
+        status,
+        next,
+        transfer.occurrences + due.len() as i32,
+        conn,
+    )?;
+
+    Ok(due.len())
+}
+
+// Create the ordinary transfer for one occurrence. The transfer runs in a savepoint
+// so a failure (e.g. insufficient balance) is recorded instead of aborting the pass.
+fn execute_occurrence(
+    transfer: &RecurringTransfer,
+    scheduled_for: NaiveDateTime,
+    conn: &mut PgConnection,
+) -> Result<NewRecurringTransferRun, AppError> {
+    let req = CreateTransactionRequest {
+        from_account_id: Some(transfer.from_account_id),
+        to_account_id: Some(transfer.to_account_id),
+        amount: transfer.amount,
+        tx_type: TransactionType::Transfer,
+        description: transfer
+            .description
+            .clone()
+            .or_else(|| Some(format!("Recurring transfer #{}", transfer.id))),
+        idempotency_key: Some(format!(
+            "recurring_{}_{}",
+            transfer.id,
+            scheduled_for.and_utc().timestamp()
+        )),
+        capture: None,
+        execute_at: None,
+        convert: None,
+        currency: None,
+        metadata: None,
+    };
+
+    let result = conn.transaction(|conn| {
+        transaction_service::create_transaction(transfer.from_account_id, None, req, conn)
+    });
+
+    let (status, transaction_id, error) = match result {
+        Ok(tx) => (RecurringRunStatus::Succeeded, Some(tx.id), None),
+        Err(AppError::TransactionConflict) => return Err(AppError::TransactionConflict),
+        Err(e) => {
+            let error = e.message();
+            webhook_service::emit_event(
+                transfer.from_account_id,
+                "recurring_transfer.failed",
+                serde_json::json!({
+                    "recurring_transfer_id": transfer.id,
+                    "scheduled_for": scheduled_for,
+                    "error": error,
+                }),
+                conn,
+            )?;
+            tracing::warn!(
+                recurring_transfer_id = transfer.id,
+                error = %error,
+                "Recurring transfer run failed"
+            );
+            (RecurringRunStatus::Failed, None, Some(error))
+        }
+    };
+
+    Ok(NewRecurringTransferRun {
+        recurring_transfer_id: transfer.id,
+        scheduled_for,
+        status,
+        transaction_id,
+        error,
+    })
+}
+
+fn rule_for(transfer: &RecurringTransfer) -> Result<Rule, AppError> {
+    match (transfer.interval_unit, transfer.cron_expression.as_deref()) {
+        (Some(unit), _) => Ok(Rule::Interval {
+            unit,
+            count: transfer.interval_count,
+        }),
+        (None, Some(expression)) => parse_cron(expression),
+        (None, None) => Err(AppError::InternalError(format!(
+            "Recurring transfer {} has no rule",
+            transfer.id
+        ))),
+    }
+}
+
+// Accepts standard 5-field expressions as well as the 6/7-field form with seconds
+fn parse_cron(expression: &str) -> Result<Rule, AppError> {
+    let expression = if expression.split_whitespace().count() == 5 {
+        format!("0 {}", expression)
+    } else {
+        expression.to_string()
+    };
+
+    cron::Schedule::from_str(&expression)
+        .map(|schedule| Rule::Cron(Box::new(schedule)))
+        .map_err(|e| AppError::BadRequest(format!("Invalid cron expression: {}", e)))
+}
+
+fn first_occurrence(rule: &Rule, start_at: NaiveDateTime) -> Option<NaiveDateTime> {
+    next_occurrence(rule, start_at, start_at - Duration::seconds(1))
+}
+
+// First occurrence strictly after `after`. Interval occurrences are anchored to
+// start_at, so monthly schedules starting on the 31st do not drift.
+fn next_occurrence(
+    rule: &Rule,
+    start_at: NaiveDateTime,
+    after: NaiveDateTime,
+) -> Option<NaiveDateTime> {
+    match rule {
+        Rule::Interval { unit, count } => {
+            if after < start_at {
+                return Some(start_at);
+            }
+
+            // Estimate the occurrence index from below, then step forward
+            let approx_seconds = match unit {
+                RecurrenceInterval::Hour => 60 * 60,
+                RecurrenceInterval::Day => 24 * 60 * 60,
+                RecurrenceInterval::Week => 7 * 24 * 60 * 60,
+                RecurrenceInterval::Month => 31 * 24 * 60 * 60,
+            } * *count as i64;
+            let mut index = (after - start_at).num_seconds() / approx_seconds;
+            loop {
+                let at = interval_occurrence(start_at, *unit, *count, index)?;
+                if at > after {
+                    return Some(at);
+                }
+                index += 1;
+            }
+        }
+        Rule::Cron(schedule) => schedule
+            .after(&after.and_utc())
+            .next()
+            .map(|at| at.naive_utc()),
+    }
+}
+
+fn interval_occurrence(
+    start_at: NaiveDateTime,
+    unit: RecurrenceInterval,
+    count: i32,
+    index: i64,
+) -> Option<NaiveDateTime> {
+    let steps = count as i64 * index;
+    match unit {
+        RecurrenceInterval::Hour => start_at.checked_add_signed(Duration::hours(steps)),
+        RecurrenceInterval::Day => start_at.checked_add_signed(Duration::days(steps)),
+        RecurrenceInterval::Week => start_at.checked_add_signed(Duration::weeks(steps)),
+        RecurrenceInterval::Month => start_at.checked_add_months(Months::new(steps as u32)),
+    }
+}
+
+// Whether an occurrence at `at` is allowed after `occurrences` earlier ones
+fn within_limits(transfer: &RecurringTransfer, at: NaiveDateTime, occurrences: i32) -> bool {
+    transfer.end_at.is_none_or(|end| at <= end)
+        && transfer.max_occurrences.is_none_or(|max| occurrences < max)
+}
+
+// Occurrences older than this when the scheduler gets to them count as missed
+fn missed_run_grace() -> Duration {
+    let seconds = std::env::var("RECURRING_MISSED_RUN_GRACE_SECONDS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_MISSED_RUN_GRACE_SECONDS);
+    Duration::seconds(seconds)
+}
+
+fn default_catch_up_policy() -> CatchUpPolicy {
+    std::env::var("RECURRING_CATCH_UP_POLICY")
+        .ok()
+        .and_then(|value| CatchUpPolicy::from_str(&value))
+        .unwrap_or(CatchUpPolicy::RunLatest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(year: i32, month: u32, day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_monthly_occurrences_are_anchored_to_start() {
+        let rule = Rule::Interval {
+            unit: RecurrenceInterval::Month,
+            count: 1,
+        };
+        let start = at(2026, 1, 31);
+
+        assert_eq!(first_occurrence(&rule, start), Some(start));
+        // Clamped to the end of February, then back to the 31st
+        assert_eq!(next_occurrence(&rule, start, start), Some(at(2026, 2, 28)));
+        assert_eq!(
+            next_occurrence(&rule, start, at(2026, 2, 28)),
+            Some(at(2026, 3, 31))
+        );
+        assert_eq!(
+            next_occurrence(&rule, start, at(2026, 12, 1)),
+            Some(at(2026, 12, 31))
+        );
+    }
+
+    #[test]
+    fn test_cron_accepts_five_fields() {
+        let rule = parse_cron("0 9 1 * *").expect("valid expression");
+        assert_eq!(
+            next_occurrence(&rule, at(2026, 1, 1), at(2026, 1, 15)),
+            Some(at(2026, 2, 1))
+        );
+        assert!(parse_cron("not a cron").is_err());
+    }
+}
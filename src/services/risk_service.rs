@@ -0,0 +1,399 @@
+use crate::{
+    models::*,
+    repositories,
+    services::{transaction_service, webhook_service},
+    utils::{app_error::AppError, db, validation},
+};
+use chrono::{Duration, Utc};
+use diesel::PgConnection;
+
+const MAX_RULE_NAME_LENGTH: usize = 100;
+const REVIEW_LIST_LIMIT: i64 = 100;
+
+// Admin: add a rule that flags or blocks matching debits and transfers
+pub fn create_rule(
+    req: CreateRiskRuleRequest,
+    conn: &mut PgConnection,
+) -> Result<RiskRuleResponse, AppError> {
+    let name = req.name.trim().to_string();
+    if name.is_empty() || name.len() > MAX_RULE_NAME_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "name must be between 1 and {} characters",
+            MAX_RULE_NAME_LENGTH
+        )));
+    }
+    let currency = req
+        .currency
+        .as_deref()
+        .map(validation::normalize_currency)
+        .transpose()
+        .map_err(AppError::BadRequest)?;
+    validate_condition(&req.definition)?;
+    validate_outcome(req.outcome)?;
+
+    let rule = repositories::create_risk_rule(
+        &NewRiskRule {
+            name,
+            currency,
+            definition: definition_json(&req.definition)?,
+            outcome: req.outcome,
+        },
+        conn,
+    )?;
+
+    tracing::info!(rule_id = rule.id, name = %rule.name, "Risk rule created");
+    Ok(rule.into())
+}
+
+pub fn list_rules(conn: &mut PgConnection) -> Result<Vec<RiskRuleResponse>, AppError> {
+    Ok(repositories::list_risk_rules(conn)?
+        .into_iter()
+        .map(RiskRuleResponse::from)
+        .collect())
+}
+
+// Admin: change what a rule looks for or does, or switch it on or off
+pub fn update_rule(
+    id: i64,
+    req: UpdateRiskRuleRequest,
+    conn: &mut PgConnection,
+) -> Result<RiskRuleResponse, AppError> {
+    if let Some(condition) = &req.definition {
+        validate_condition(condition)?;
+    }
+    if let Some(outcome) = req.outcome {
+        validate_outcome(outcome)?;
+    }
+
+    let changes = RiskRuleChanges {
+        definition: req.definition.as_ref().map(definition_json).transpose()?,
+        outcome: req.outcome,
+        is_active: req.is_active,
+    };
+    let rule = repositories::update_risk_rule(id, &changes, conn)?;
+
+    tracing::info!(
+        rule_id = rule.id,
+        is_active = rule.is_active,
+        "Risk rule updated"
+    );
+    Ok(rule.into())
+}
+
+pub fn delete_rule(id: i64, conn: &mut PgConnection) -> Result<(), AppError> {
+    repositories::delete_risk_rule(id, conn)
+}
+
+// Run every active rule against a new debit or transfer from a locked `from` account.
+// Returns the rules it matched; the transaction gets the most severe of their outcomes.
+pub fn evaluate(
+    new_tx: &NewTransaction,
+    from: &Account,
+    conn: &mut PgConnection,
+) -> Result<Vec<MatchedRiskRule>, AppError> {
+    let mut matched = Vec::new();
+    for rule in repositories::get_active_risk_rules(&new_tx.currency, conn)? {
+        let condition: RiskCondition = match serde_json::from_value(rule.definition.clone()) {
+            Ok(condition) => condition,
+            Err(e) => {
+                tracing::warn!(rule_id = rule.id, error = %e, "Skipping unreadable risk rule");
+                continue;
+            }
+        };
+        if condition_matches(&condition, new_tx, from, conn)? {
+            matched.push(MatchedRiskRule {
+                id: rule.id,
+                name: rule.name,
+                outcome: rule.outcome,
+            });
+        }
+    }
+    Ok(matched)
+}
+
+pub fn outcome(matched: &[MatchedRiskRule]) -> RiskOutcome {
+    matched
+        .iter()
+        .map(|rule| rule.outcome)
+        .max()
+        .unwrap_or(RiskOutcome::Allow)
+}
+
+// Queue a transaction that was held for review
+pub fn open_review(
+    tx: &Transaction,
+    matched: &[MatchedRiskRule],
+    capture: bool,
+    conn: &mut PgConnection,
+) -> Result<RiskReview, AppError> {
+    let review = repositories::create_risk_review(
+        &NewRiskReview {
+            transaction_id: tx.id,
+            matched_rules: serde_json::to_value(matched)
+                .map_err(|e| AppError::InternalError(e.to_string()))?,
+            capture,
+        },
+        conn,
+    )?;
+
+    tracing::warn!(
+        transaction_id = tx.id,
+        review_id = review.id,
+        rules = ?matched.iter().map(|rule| &rule.name).collect::<Vec<_>>(),
+        "Transaction held for risk review"
+    );
+    Ok(review)
+}
+
+// Admin: reviews in a status (pending by default), oldest first
+pub fn list_reviews(
+    query: ListRiskReviewsQuery,
+    conn: &mut PgConnection,
+) -> Result<Vec<RiskReviewResponse>, AppError> {
+    let status = query.status.unwrap_or(RiskReviewStatus::Pending);
+    Ok(
+        repositories::list_risk_reviews(status, REVIEW_LIST_LIMIT, conn)?
+            .into_iter()
+            .map(|(review, tx)| RiskReviewResponse::new(review, tx.into()))
+            .collect(),
+    )
+}
+
+// Admin: let a held transaction go ahead as it was requested
+pub fn approve_review(
+    id: i64,
+    reviewer_key_id: i64,
+    req: DecideRiskReviewRequest,
+    conn: &mut PgConnection,
+) -> Result<RiskReviewResponse, AppError> {
+    let reason = validation::validate_reason(req.reason).map_err(AppError::BadRequest)?;
+
+    let (review, tx) = db::run_in_transaction(conn, |conn| {
+        let review = get_pending_review(id, conn)?;
+        let tx = transaction_service::release_reviewed_transaction(
+            review.transaction_id,
+            review.capture,
+            reviewer_key_id,
+            conn,
+        )?;
+        let review = repositories::decide_risk_review(
+            review.id,
+            RiskReviewStatus::Approved,
+            reviewer_key_id,
+            reason.clone(),
+            conn,
+        )?;
+        Ok((review, tx))
+    })?;
+
+    tracing::info!(
+        review_id = review.id,
+        transaction_id = tx.id,
+        "Risk review approved"
+    );
+    Ok(RiskReviewResponse::new(review, tx.into()))
+}
+
+// Admin: fail a held transaction without moving any funds
+pub fn decline_review(
+    id: i64,
+    reviewer_key_id: i64,
+    req: DecideRiskReviewRequest,
+    conn: &mut PgConnection,
+) -> Result<RiskReviewResponse, AppError> {
+    let reason = validation::validate_reason(req.reason).map_err(AppError::BadRequest)?;
+
+    let (review, tx) = db::run_in_transaction(conn, |conn| {
+        let review = get_pending_review(id, conn)?;
+        let failure = reason
+            .clone()
+            .unwrap_or_else(|| "Declined in risk review".to_string());
+        let tx = transaction_service::fail_reviewed_transaction(
+            review.transaction_id,
+            reviewer_key_id,
+            failure.clone(),
+            conn,
+        )?;
+        if tx.status == TransactionStatus::Failed
+            && let Some(account_id) = tx.from_account_id
+        {
+            webhook_service::emit_event(
+                account_id,
+                "transaction.failed",
+                serde_json::json!({
+                    "transaction_id": tx.id,
+                    "reason": failure,
+                }),
+                conn,
+            )?;
+        }
+        let review = repositories::decide_risk_review(
+            review.id,
+            RiskReviewStatus::Declined,
+            reviewer_key_id,
+            reason.clone(),
+            conn,
+        )?;
+        Ok((review, tx))
+    })?;
+
+    tracing::info!(
+        review_id = review.id,
+        transaction_id = tx.id,
+        "Risk review declined"
+    );
+    Ok(RiskReviewResponse::new(review, tx.into()))
+}
+
+fn get_pending_review(id: i64, conn: &mut PgConnection) -> Result<RiskReview, AppError> {
+    let review = repositories::get_risk_review_for_update(id, conn)?;
+    if review.status != RiskReviewStatus::Pending {
+        return Err(AppError::Conflict(
+            "Risk review has already been decided".to_string(),
+        ));
+    }
+    Ok(review)
+}
+
+fn condition_matches(
+    condition: &RiskCondition,
+    new_tx: &NewTransaction,
+    from: &Account,
+    conn: &mut PgConnection,
+) -> Result<bool, AppError> {
+    match *condition {
+        RiskCondition::NewAccountOutflow {
+            account_age_hours,
+            max_outflow,
+        } => {
+            if Utc::now().naive_utc() - from.created_at >= Duration::hours(account_age_hours) {
+                return Ok(false);
+            }
+            let (outflow, _) = repositories::get_outflow_usage(
+                LimitScope::Account,
+                from.id,
+                &new_tx.currency,
+                from.created_at,
+                conn,
+            )?;
+            Ok(outflow + new_tx.amount + new_tx.fee_amount > max_outflow)
+        }
+        RiskCondition::DistinctRecipients {
+            window_minutes,
+            max_recipients,
+        } => {
+            let Some(to_id) = new_tx
+                .to_account_id
+                .filter(|_| new_tx.tx_type == TransactionType::Transfer)
+            else {
+                return Ok(false);
+            };
+            let since = Utc::now().naive_utc() - Duration::minutes(window_minutes);
+            let recipients = repositories::get_recent_recipients(from.id, since, conn)?;
+            let count = recipients.len() + usize::from(!recipients.contains(&to_id));
+            Ok(count as i64 > max_recipients)
+        }
+        RiskCondition::RoundAmount {
+            min_amount,
+            multiple_of,
+        } => Ok(is_round_amount(new_tx.amount, min_amount, multiple_of)),
+    }
+}
+
+fn is_round_amount(amount: i64, min_amount: i64, multiple_of: i64) -> bool {
+    amount >= min_amount && amount % multiple_of == 0
+}
+
+fn validate_condition(condition: &RiskCondition) -> Result<(), AppError> {
+    let valid = match *condition {
+        RiskCondition::NewAccountOutflow {
+            account_age_hours,
+            max_outflow,
+        } => account_age_hours > 0 && max_outflow >= 0,
+        RiskCondition::DistinctRecipients {
+            window_minutes,
+            max_recipients,
+        } => window_minutes > 0 && max_recipients > 0,
+        RiskCondition::RoundAmount {
+            min_amount,
+            multiple_of,
+        } => min_amount > 0 && multiple_of > 0,
+    };
+    if !valid {
+        return Err(AppError::BadRequest(
+            "Rule windows, counts and amounts must be positive".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_outcome(outcome: RiskOutcome) -> Result<(), AppError> {
+    if outcome == RiskOutcome::Allow {
+        return Err(AppError::BadRequest(
+            "A rule must either review or block".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn definition_json(condition: &RiskCondition) -> Result<serde_json::Value, AppError> {
+    serde_json::to_value(condition).map_err(|e| AppError::InternalError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matched(outcome: RiskOutcome) -> MatchedRiskRule {
+        MatchedRiskRule {
+            id: 1,
+            name: "rule".to_string(),
+            outcome,
+        }
+    }
+
+    #[test]
+    fn test_outcome_is_most_severe_match() {
+        assert_eq!(outcome(&[]), RiskOutcome::Allow);
+        assert_eq!(
+            outcome(&[matched(RiskOutcome::Review)]),
+            RiskOutcome::Review
+        );
+        assert_eq!(
+            outcome(&[matched(RiskOutcome::Review), matched(RiskOutcome::Block)]),
+            RiskOutcome::Block
+        );
+    }
+
+    #[test]
+    fn test_is_round_amount() {
+        assert!(is_round_amount(500_000, 100_000, 10_000));
+        assert!(!is_round_amount(500_001, 100_000, 10_000));
+        assert!(!is_round_amount(50_000, 100_000, 10_000));
+    }
+
+    #[test]
+    fn test_parse_condition() {
+        let condition: RiskCondition = serde_json::from_value(serde_json::json!({
+            "type": "distinct_recipients",
+            "window_minutes": 60,
+            "max_recipients": 5,
+        }))
+        .unwrap();
+        assert_eq!(
+            condition,
+            RiskCondition::DistinctRecipients {
+                window_minutes: 60,
+                max_recipients: 5,
+            }
+        );
+
+        let unknown_field = serde_json::from_value::<RiskCondition>(serde_json::json!({
+            "type": "round_amount",
+            "min_amount": 100,
+            "multiple_of": 10,
+            "currency": "USD",
+        }));
+        assert!(unknown_field.is_err());
+    }
+}
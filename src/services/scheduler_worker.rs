@@ -0,0 +1,53 @@
+use crate::{
+    AppState,
+    services::{recurring_transfer_service, transaction_service},
+    utils::app_error::AppError,
+};
+use std::{sync::Arc, time::Duration};
+
+const DEFAULT_CHECK_INTERVAL_SECONDS: u64 = 10;
+
+// Background task that executes scheduled transactions and recurring transfers once they are due
+pub async fn run(state: Arc<AppState>) {
+    let seconds = std::env::var("SCHEDULER_INTERVAL_SECONDS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_CHECK_INTERVAL_SECONDS);
+    let mut interval = tokio::time::interval(Duration::from_secs(seconds.max(1)));
+
+    loop {
+        interval.tick().await;
+
+        let state = state.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut conn = state
+                .db_pool
+                .get()
+                .map_err(|_| AppError::InternalError("DB connection failed".to_string()))?;
+
+            // Run both independently so a failure in one does not hold up the other
+            Ok::<_, AppError>((
+                transaction_service::execute_scheduled_transactions(&mut conn),
+                recurring_transfer_service::run_due_recurring_transfers(&mut conn),
+            ))
+        })
+        .await;
+
+        match result {
+            Ok(Ok((scheduled, recurring))) => {
+                match scheduled {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!(count = count, "Processed scheduled transactions"),
+                    Err(e) => tracing::error!(error = ?e, "Scheduled transactions run failed"),
+                }
+                match recurring {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!(count = count, "Processed recurring transfer runs"),
+                    Err(e) => tracing::error!(error = ?e, "Recurring transfers run failed"),
+                }
+            }
+            Ok(Err(e)) => tracing::error!(error = ?e, "Scheduler run failed"),
+            Err(e) => tracing::error!(error = %e, "Scheduler task panicked"),
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use crate::{
+    models::*,
+    repositories,
+    services::account_service,
+    utils::{app_error::AppError, currency, validation},
+};
+use chrono::{NaiveDateTime, SubsecRound, Utc};
+use diesel::PgConnection;
+
+const STATEMENT_PAGE_SIZE: i64 = 500;
+// Same layout as the JSON timestamps
+const CSV_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+// Check the request and work out the opening balance, so that errors are reported
+// before any of the statement has been streamed
+pub fn open_statement(
+    account_id: i64,
+    query: StatementQuery,
+    conn: &mut PgConnection,
+) -> Result<StatementHeader, AppError> {
+    let from = query.from.naive_utc();
+    // A period that is still running ends now
+    let to = query
+        .to
+        .naive_utc()
+        .min(Utc::now().naive_utc().trunc_subsecs(6));
+    if from >= to {
+        return Err(AppError::BadRequest(
+            "from must be before to and in the past".to_string(),
+        ));
+    }
+
+    let account = repositories::get_account_by_id(account_id, conn)?;
+    let currency = match query.currency.as_deref() {
+        Some(currency) => validation::normalize_currency(currency).map_err(AppError::BadRequest)?,
+        None => account.currency.clone(),
+    };
+    if repositories::get_account_balance(account.id, &currency, conn)?.is_none() {
+        return Err(AppError::WalletNotFound {
+            account_id: account.id,
+            currency,
+        });
+    }
+
+    let opening_balance = account_service::balance_as_of(account.id, &currency, from, conn)?;
+
+    Ok(StatementHeader {
+        account_id: account.id,
+        business_name: account.business_name,
+        opening_balance_decimal: currency::format_amount(opening_balance, &currency),
+        currency,
+        from,
+        to,
+        opening_balance,
+        format: query.format.unwrap_or(StatementFormat::Json),
+    })
+}
+
+// Write the statement one page of entries at a time. `emit` returns false once the
+// reader has gone away, which stops the export.
+pub fn write_statement(
+    statement: &StatementHeader,
+    conn: &mut PgConnection,
+    mut emit: impl FnMut(String) -> bool,
+) -> Result<(), AppError> {
+    if !emit(statement_start(statement)?) {
+        return Ok(());
+    }
+
+    let mut balance = statement.opening_balance;
+    let mut after = None;
+    let mut first = true;
+    loop {
+        let page = repositories::get_wallet_postings_page(
+            statement.account_id,
+            &statement.currency,
+            statement.from,
+            statement.to,
+            after,
+            STATEMENT_PAGE_SIZE,
+            conn,
+        )?;
+        let Some((last, _)) = page.last() else {
+            break;
+        };
+        after = Some((last.created_at, last.id));
+        let full_page = page.len() as i64 == STATEMENT_PAGE_SIZE;
+
+        let mut chunk = String::new();
+        for (posting, tx) in page {
+            balance += posting.amount;
+            let entry = StatementEntry {
+                posted_at: posting.created_at,
+                transaction_id: tx.id,
+                tx_type: tx.tx_type,
+                direction: if posting.amount > 0 {
+                    EntryDirection::Inflow
+                } else {
+                    EntryDirection::Outflow
+                },
+                description: tx.description,
+                amount: posting.amount,
+                amount_decimal: currency::format_amount(posting.amount, &statement.currency),
+                balance,
+                balance_decimal: currency::format_amount(balance, &statement.currency),
+            };
+            chunk.push_str(&statement_entry(statement.format, &entry, first)?);
+            first = false;
+        }
+        if !emit(chunk) {
+            return Ok(());
+        }
+        if !full_page {
+            break;
+        }
+    }
+
+    emit(statement_end(statement, balance)?);
+    Ok(())
+}
+
+// CSV: a header row, then the opening balance. JSON: the statement object up to its
+// entries array, which the entries and the closing balance complete.
+fn statement_start(statement: &StatementHeader) -> Result<String, AppError> {
+    match statement.format {
+        StatementFormat::Csv => {
+            let mut start = csv_row(&[
+                "posted_at",
+                "transaction_id",
+                "tx_type",
+                "direction",
+                "description",
+                "amount",
+                "amount_decimal",
+                "balance",
+                "balance_decimal",
+            ])?;
+            start.push_str(&balance_row(
+                statement,
+                statement.from,
+                "Opening balance",
+                statement.opening_balance,
+            )?);
+            Ok(start)
+        }
+        StatementFormat::Json => {
+            let mut start = serde_json::to_string(statement)
+                .map_err(|e| AppError::InternalError(e.to_string()))?;
+            start.pop(); // Reopen the object
+            start.push_str(",\"entries\":[");
+            Ok(start)
+        }
+    }
+}
+
+fn statement_entry(
+    format: StatementFormat,
+    entry: &StatementEntry,
+    first: bool,
+) -> Result<String, AppError> {
+    match format {
+        StatementFormat::Csv => csv_row(&[
+            &entry.posted_at.format(CSV_TIME_FORMAT).to_string(),
+            &entry.transaction_id.to_string(),
+            json_text(&entry.tx_type)?.as_str(),
+            json_text(&entry.direction)?.as_str(),
+            entry.description.as_deref().unwrap_or(""),
+            &entry.amount.to_string(),
+            &entry.amount_decimal,
+            &entry.balance.to_string(),
+            &entry.balance_decimal,
+        ]),
+        StatementFormat::Json => {
+            let json =
+                serde_json::to_string(entry).map_err(|e| AppError::InternalError(e.to_string()))?;
+            Ok(if first { json } else { format!(",{}", json) })
+        }
+    }
+}
+
+fn statement_end(statement: &StatementHeader, closing_balance: i64) -> Result<String, AppError> {
+    match statement.format {
+        StatementFormat::Csv => {
+            balance_row(statement, statement.to, "Closing balance", closing_balance)
+        }
+        StatementFormat::Json => Ok(format!(
+            "],\"closing_balance\":{},\"closing_balance_decimal\":\"{}\"}}",
+            closing_balance,
+            currency::format_amount(closing_balance, &statement.currency)
+        )),
+    }
+}
+
+fn balance_row(
+    statement: &StatementHeader,
+    at: NaiveDateTime,
+    label: &str,
+    balance: i64,
+) -> Result<String, AppError> {
+    csv_row(&[
+        &at.format(CSV_TIME_FORMAT).to_string(),
+        "",
+        "",
+        "",
+        label,
+        "",
+        "",
+        &balance.to_string(),
+        &currency::format_amount(balance, &statement.currency),
+    ])
+}
+
+fn csv_row(fields: &[&str]) -> Result<String, AppError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(fields)
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+    String::from_utf8(bytes).map_err(|e| AppError::InternalError(e.to_string()))
+}
+
+// The serde name of an enum value, e.g. "transfer"
+fn json_text<T: serde::Serialize>(value: &T) -> Result<String, AppError> {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(text)) => Ok(text),
+        Ok(other) => Ok(other.to_string()),
+        Err(e) => Err(AppError::InternalError(e.to_string())),
+    }
+}
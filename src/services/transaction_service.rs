@@ -1,8 +1,25 @@
-use crate::{models::*, repositories, utils::app_error::AppError};
-use diesel::PgConnection;
+use crate::{
+    models::*,
+    repositories,
+    services::{
+        account_service, fee_service, fx_service, limit_service, risk_service, webhook_service,
+    },
+    utils::{app_error::AppError, currency, db, validation},
+};
+use chrono::{Duration, Utc};
+use diesel::{Connection, PgConnection};
+use std::collections::HashMap;
+
+const DEFAULT_HOLD_EXPIRY_SECONDS: i64 = 7 * 24 * 60 * 60;
+const EXPIRY_BATCH_SIZE: i64 = 100;
+const MAX_BATCH_LEGS: usize = 100;
+const DEFAULT_LIST_LIMIT: i64 = 20;
+const MAX_LIST_LIMIT: i64 = 100;
+const SCHEDULER_BATCH_SIZE: i64 = 100;
 
 pub fn create_transaction(
     _account_id: i64,
+    actor_key_id: Option<i64>,
     req: CreateTransactionRequest,
     conn: &mut PgConnection,
 ) -> Result<TransactionResponse, AppError> {
@@ -11,64 +28,1362 @@ pub fn create_transaction(
         return Err(AppError::BadRequest("Amount must be positive".to_string()));
     }
 
+    // capture: false only authorizes the amount by placing a hold on the source account
+    let capture = req.capture.unwrap_or(true);
+    if !capture && req.tx_type == TransactionType::Credit {
+        return Err(AppError::BadRequest(
+            "Only debits and transfers can be authorized without capture".to_string(),
+        ));
+    }
+
+    // Scheduled transactions are stored as pending and left to the scheduler.
+    // Funds are not reserved; the balance is checked when the transaction runs.
+    let execute_at = req.execute_at.map(|at| at.naive_utc());
+    if let Some(at) = execute_at {
+        if !capture {
+            return Err(AppError::BadRequest(
+                "Scheduled transactions cannot be authorizations".to_string(),
+            ));
+        }
+        if at <= Utc::now().naive_utc() {
+            return Err(AppError::BadRequest(
+                "execute_at must be in the future".to_string(),
+            ));
+        }
+    }
+
+    let account_ids = account_ids(req.tx_type, req.from_account_id, req.to_account_id)?;
+    let currency = resolve_currency(
+        req.currency.as_deref(),
+        req.tx_type,
+        req.from_account_id,
+        req.to_account_id,
+        conn,
+    )?;
+    let convert = req.convert.unwrap_or(false);
+    let metadata = validation::validate_metadata(req.metadata).map_err(AppError::BadRequest)?;
+
     let new_tx = NewTransaction {
         from_account_id: req.from_account_id,
         to_account_id: req.to_account_id,
         amount: req.amount,
         tx_type: req.tx_type,
-        status: TransactionStatus::Completed,
+        status: if capture && execute_at.is_none() {
+            TransactionStatus::Completed
+        } else {
+            TransactionStatus::Pending
+        },
         description: req.description,
         idempotency_key: req.idempotency_key.clone(),
+        reversal_of_id: None,
+        batch_id: None,
+        execute_at,
+        fx_rate: None,
+        converted_amount: None,
+        converted_currency: None,
+        currency,
+        metadata,
+        fee_amount: 0,
+        fee_account_id: None,
     };
 
-    // Handle different transaction types
-    match req.tx_type {
-        TransactionType::Transfer => {
-            let from_id = req
-                .from_account_id
-                .ok_or(AppError::BadRequest("from_account_id required".to_string()))?;
-            let to_id = req
-                .to_account_id
-                .ok_or(AppError::BadRequest("to_account_id required".to_string()))?;
+    // Lock every account involved, then write the balances, the transaction row
+    // and its journal entry (or hold) atomically
+    let tx = db::run_in_transaction(conn, |conn| {
+        let accounts = repositories::lock_accounts(&account_ids, conn)?;
+        let mut new_tx = new_tx.clone();
+        let mut legs = apply_conversion(&mut new_tx, convert, &accounts, conn)?;
+
+        // The fee is quoted now and charged whenever the transaction moves money
+        if let Some(fee) = fee_service::quote_fee(
+            new_tx.tx_type,
+            new_tx.from_account_id,
+            &new_tx.currency,
+            new_tx.amount,
+            conn,
+        )? {
+            new_tx.fee_amount = fee.amount;
+            new_tx.fee_account_id = Some(fee.fee_account_id);
+        }
+        legs.extend(fee_legs(
+            new_tx.from_account_id,
+            new_tx.fee_account_id,
+            new_tx.fee_amount,
+            &new_tx.currency,
+        ));
+        if let Some(from_id) = new_tx.from_account_id {
+            limit_service::enforce_limits(
+                from_id,
+                actor_key_id,
+                &new_tx.currency,
+                new_tx.amount,
+                new_tx.fee_amount,
+                conn,
+            )?;
+
+            // Transactions flagged for review are kept pending, with nothing posted or
+            // held, until an admin approves them
+            let from = find_account(&accounts, from_id)?;
+            let matched = risk_service::evaluate(&new_tx, from, conn)?;
+            match risk_service::outcome(&matched) {
+                RiskOutcome::Block => {
+                    tracing::warn!(
+                        account_id = from_id,
+                        rules = ?matched.iter().map(|rule| &rule.name).collect::<Vec<_>>(),
+                        "Transaction blocked by risk rules"
+                    );
+                    return Err(AppError::RiskBlocked);
+                }
+                RiskOutcome::Review => {
+                    ensure_wallets(&legs, conn)?;
+                    new_tx.status = TransactionStatus::Pending;
+                    let tx = insert_transaction(&new_tx, actor_key_id, conn)?;
+                    risk_service::open_review(&tx, &matched, capture, conn)?;
+                    return Ok(tx);
+                }
+                RiskOutcome::Allow => {}
+            }
+        }
+
+        if execute_at.is_some() {
+            // Funds are checked when it runs, but the wallets must exist already
+            ensure_wallets(&legs, conn)?;
+            return insert_transaction(&new_tx, actor_key_id, conn);
+        }
+        ensure_available_balance(&legs, conn)?;
+
+        let tx = insert_transaction(&new_tx, actor_key_id, conn)?;
+        if capture {
+            post_journal_entry(&tx, &legs, conn)?;
+        } else {
+            place_authorization_hold(&tx, conn)?;
+        }
+        Ok(tx)
+    })?;
+
+    Ok(tx.into())
+}
+
+// Work out the fee a transaction would be charged without creating it
+pub fn quote_transaction(
+    req: CreateTransactionRequest,
+    conn: &mut PgConnection,
+) -> Result<FeeQuoteResponse, AppError> {
+    if req.amount <= 0 {
+        return Err(AppError::BadRequest("Amount must be positive".to_string()));
+    }
+    let currency = resolve_currency(
+        req.currency.as_deref(),
+        req.tx_type,
+        req.from_account_id,
+        req.to_account_id,
+        conn,
+    )?;
+    let fee = fee_service::quote_fee(
+        req.tx_type,
+        req.from_account_id,
+        &currency,
+        req.amount,
+        conn,
+    )?;
+    Ok(FeeQuoteResponse::new(
+        req.tx_type,
+        req.amount,
+        currency,
+        fee.as_ref(),
+    ))
+}
+
+// Create all legs of a batch under one parent id: either every leg is committed or none is
+pub fn create_batch(
+    actor_key_id: Option<i64>,
+    req: CreateBatchRequest,
+    conn: &mut PgConnection,
+) -> Result<BatchResponse, AppError> {
+    if req.legs.is_empty() {
+        return Err(AppError::BadRequest(
+            "Batch must contain at least one leg".to_string(),
+        ));
+    }
+    if req.legs.len() > MAX_BATCH_LEGS {
+        return Err(AppError::BadRequest(format!(
+            "Batch cannot contain more than {} legs",
+            MAX_BATCH_LEGS
+        )));
+    }
+
+    // Validate every leg before writing anything
+    let mut account_ids = Vec::new();
+    let mut leg_currencies = Vec::with_capacity(req.legs.len());
+    let mut leg_metadata = Vec::with_capacity(req.legs.len());
+    for (index, leg) in req.legs.iter().enumerate() {
+        if leg.amount <= 0 {
+            return Err(batch_leg_error(
+                index,
+                AppError::BadRequest("Amount must be positive".to_string()),
+            ));
+        }
+        leg_metadata.push(
+            validation::validate_metadata(leg.metadata.clone())
+                .map_err(|e| batch_leg_error(index, AppError::BadRequest(e)))?,
+        );
+        account_ids.extend(
+            self::account_ids(leg.tx_type, leg.from_account_id, leg.to_account_id)
+                .map_err(|e| batch_leg_error(index, e))?,
+        );
+        let currency = resolve_currency(
+            leg.currency.as_deref(),
+            leg.tx_type,
+            leg.from_account_id,
+            leg.to_account_id,
+            conn,
+        )
+        .map_err(|e| batch_leg_error(index, e))?;
+        leg_currencies.push(currency);
+    }
+
+    let new_batch = NewTransactionBatch {
+        description: req.description.clone(),
+        idempotency_key: req.idempotency_key.clone(),
+    };
+
+    // Lock every account in the batch up front, in id order, so concurrent batches cannot deadlock
+    let (batch, txs) = db::run_in_transaction(conn, |conn| {
+        let accounts = repositories::lock_accounts(&account_ids, conn)?;
+        let batch = repositories::create_batch(&new_batch, conn)?;
+
+        let mut txs = Vec::with_capacity(req.legs.len());
+        let legs = req.legs.iter().zip(&leg_currencies).zip(&leg_metadata);
+        for (index, ((leg, currency), metadata)) in legs.enumerate() {
+            let mut new_tx = NewTransaction {
+                from_account_id: leg.from_account_id,
+                to_account_id: leg.to_account_id,
+                amount: leg.amount,
+                tx_type: leg.tx_type,
+                status: TransactionStatus::Completed,
+                description: leg.description.clone().or(req.description.clone()),
+                idempotency_key: None,
+                reversal_of_id: None,
+                batch_id: Some(batch.id),
+                execute_at: None,
+                fx_rate: None,
+                converted_amount: None,
+                converted_currency: None,
+                currency: currency.clone(),
+                metadata: metadata.clone(),
+                fee_amount: 0,
+                fee_account_id: None,
+            };
+
+            // Each leg is charged the fee it would be charged on its own
+            if let Some(fee) = fee_service::quote_fee(
+                new_tx.tx_type,
+                new_tx.from_account_id,
+                &new_tx.currency,
+                new_tx.amount,
+                conn,
+            )
+            .map_err(|e| batch_leg_error(index, e))?
+            {
+                new_tx.fee_amount = fee.amount;
+                new_tx.fee_account_id = Some(fee.fee_account_id);
+            }
+
+            // Usage includes the legs already written in this database transaction, so legs
+            // paid from the same account count together against its limits
+            if let Some(from_id) = new_tx.from_account_id {
+                limit_service::enforce_limits(
+                    from_id,
+                    actor_key_id,
+                    &new_tx.currency,
+                    new_tx.amount,
+                    new_tx.fee_amount,
+                    conn,
+                )
+                .map_err(|e| batch_leg_error(index, e))?;
+
+                // A batch cannot wait for a review of one of its legs, so a leg that would
+                // be held for review fails the batch just like a blocked one
+                let from = find_account(&accounts, from_id)?;
+                let matched = risk_service::evaluate(&new_tx, from, conn)
+                    .map_err(|e| batch_leg_error(index, e))?;
+                let error = match risk_service::outcome(&matched) {
+                    RiskOutcome::Allow => None,
+                    RiskOutcome::Review => Some(AppError::RiskReviewRequired),
+                    RiskOutcome::Block => Some(AppError::RiskBlocked),
+                };
+                if let Some(error) = error {
+                    tracing::warn!(
+                        account_id = from_id,
+                        leg_index = index,
+                        rules = ?matched.iter().map(|rule| &rule.name).collect::<Vec<_>>(),
+                        "Batch leg stopped by risk rules"
+                    );
+                    return Err(batch_leg_error(index, error));
+                }
+            }
+
+            let tx = post_transaction(&new_tx, leg.convert.unwrap_or(false), actor_key_id, conn)
+                .map_err(|e| batch_leg_error(index, e))?;
+            txs.push(tx);
+        }
+        Ok((batch, txs))
+    })?;
+
+    tracing::info!(batch_id = batch.id, legs = txs.len(), "Batch committed");
+
+    Ok(batch_response(batch, txs))
+}
+
+// Capture a pending authorization, in full or for a smaller amount
+pub fn capture_transaction(
+    id: i64,
+    actor_key_id: Option<i64>,
+    req: CaptureTransactionRequest,
+    conn: &mut PgConnection,
+) -> Result<TransactionResponse, AppError> {
+    let tx = db::run_in_transaction(conn, |conn| {
+        let tx = repositories::get_transaction_for_update(id, conn)?;
+        ensure_transition(&tx, TransactionStatus::Completed)?;
+        let hold = get_authorization_hold(&tx, conn)?;
+
+        if hold
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now().naive_utc())
+        {
+            return Err(AppError::Conflict("Authorization has expired".to_string()));
+        }
+
+        let amount = req.amount.unwrap_or(tx.amount);
+        if amount <= 0 || amount > tx.amount {
+            return Err(AppError::BadRequest(format!(
+                "Capture amount must be between 1 and the authorized amount of {}",
+                tx.amount
+            )));
+        }
+
+        // Cross-currency authorizations are captured at the rate quoted when they were placed
+        let converted_amount = tx
+            .fx_rate
+            .zip(tx.converted_currency.as_deref())
+            .map(|(rate, to_currency)| {
+                fx_service::convert_amount(amount, rate, &tx.currency, to_currency)
+            })
+            .transpose()?;
+        // A partial capture pays the fee pro rata
+        let fee_amount = prorate(tx.fee_amount, amount, tx.amount);
+        let mut legs = build_legs(
+            tx.tx_type,
+            tx.from_account_id,
+            tx.to_account_id,
+            amount,
+            &tx.currency,
+            converted_amount.zip(tx.converted_currency.as_deref()),
+        )?;
+        legs.extend(fee_legs(
+            tx.from_account_id,
+            tx.fee_account_id,
+            fee_amount,
+            &tx.currency,
+        ));
+        let account_ids: Vec<i64> = legs.iter().filter_map(|leg| leg.account_id).collect();
+        repositories::lock_accounts(&account_ids, conn)?;
+
+        // The hold is converted into a real debit
+        repositories::release_hold(hold.id, conn)?;
+        ensure_available_balance(&legs, conn)?;
+
+        let tx = if amount != tx.amount {
+            repositories::update_transaction_amount(
+                tx.id,
+                amount,
+                converted_amount,
+                fee_amount,
+                conn,
+            )?
+        } else {
+            tx
+        };
+        post_journal_entry(&tx, &legs, conn)?;
+        transition_status(&tx, TransactionStatus::Completed, actor_key_id, None, conn)
+    })?;
+
+    Ok(tx.into())
+}
+
+// Release the hold of a pending authorization without moving any funds
+pub fn void_transaction(
+    id: i64,
+    actor_key_id: Option<i64>,
+    conn: &mut PgConnection,
+) -> Result<TransactionResponse, AppError> {
+    let tx = db::run_in_transaction(conn, |conn| {
+        let tx = repositories::get_transaction_for_update(id, conn)?;
+        ensure_transition(&tx, TransactionStatus::Cancelled)?;
+        let hold = get_authorization_hold(&tx, conn)?;
+
+        repositories::release_hold(hold.id, conn)?;
+        transition_status(
+            &tx,
+            TransactionStatus::Cancelled,
+            actor_key_id,
+            Some("Authorization voided".to_string()),
+            conn,
+        )
+    })?;
+
+    Ok(tx.into())
+}
+
+// Cancel any pending transaction, releasing its hold if it has one
+pub fn cancel_transaction(
+    id: i64,
+    actor_key_id: Option<i64>,
+    req: CancelTransactionRequest,
+    conn: &mut PgConnection,
+) -> Result<TransactionResponse, AppError> {
+    let tx = db::run_in_transaction(conn, |conn| {
+        let tx = repositories::get_transaction_for_update(id, conn)?;
+        ensure_transition(&tx, TransactionStatus::Cancelled)?;
+
+        if let Some(hold) = repositories::get_active_hold_by_transaction(tx.id, conn)? {
+            repositories::release_hold(hold.id, conn)?;
+        }
+
+        transition_status(
+            &tx,
+            TransactionStatus::Cancelled,
+            actor_key_id,
+            req.reason.clone(),
+            conn,
+        )
+    })?;
+
+    Ok(tx.into())
+}
+
+// Refund a completed transaction, fully or partially, with a linked compensating transaction
+pub fn reverse_transaction(
+    id: i64,
+    actor_key_id: Option<i64>,
+    req: ReverseTransactionRequest,
+    conn: &mut PgConnection,
+) -> Result<TransactionResponse, AppError> {
+    let metadata = validation::validate_metadata(req.metadata).map_err(AppError::BadRequest)?;
+
+    let (original, reversal) = db::run_in_transaction(conn, |conn| {
+        let original = repositories::get_transaction_for_update(id, conn)?;
+
+        // Partial refunds keep the original completed; it is reversed once fully refunded
+        ensure_transition(&original, TransactionStatus::Reversed)?;
+        if original.reversal_of_id.is_some() {
+            return Err(AppError::Conflict(
+                "A reversal cannot itself be reversed".to_string(),
+            ));
+        }
+
+        let remaining = original.amount - original.refunded_amount;
+        let amount = req.amount.unwrap_or(remaining);
+        if amount <= 0 || amount > remaining {
+            return Err(AppError::BadRequest(format!(
+                "Reversal amount must be between 1 and the remaining refundable amount of {}",
+                remaining
+            )));
+        }
+
+        // The compensating transaction moves the money back the other way
+        let (tx_type, from_account_id, to_account_id) = match original.tx_type {
+            TransactionType::Transfer => (
+                TransactionType::Transfer,
+                original.to_account_id,
+                original.from_account_id,
+            ),
+            TransactionType::Credit => (TransactionType::Debit, original.to_account_id, None),
+            TransactionType::Debit => (TransactionType::Credit, None, original.from_account_id),
+        };
+
+        // A cross-currency transfer is given back in the currency it was credited in, pro rata
+        // to the refunded share so that a full refund returns exactly the converted amount
+        let (reversal_amount, converted_amount) = match original.converted_amount {
+            Some(converted) => {
+                let refunded_before = prorate(converted, original.refunded_amount, original.amount);
+                let refunded_after = prorate(
+                    converted,
+                    original.refunded_amount + amount,
+                    original.amount,
+                );
+                if refunded_after == refunded_before {
+                    return Err(AppError::BadRequest(
+                        "Reversal amount is too small to convert".to_string(),
+                    ));
+                }
+                (refunded_after - refunded_before, Some(amount))
+            }
+            None => (amount, None),
+        };
+
+        let currency = original
+            .converted_currency
+            .clone()
+            .unwrap_or_else(|| original.currency.clone());
+        let converted_currency = converted_amount.map(|_| original.currency.clone());
+
+        let legs = build_legs(
+            tx_type,
+            from_account_id,
+            to_account_id,
+            reversal_amount,
+            &currency,
+            converted_amount.zip(converted_currency.as_deref()),
+        )?;
+        let account_ids: Vec<i64> = legs.iter().filter_map(|leg| leg.account_id).collect();
+        repositories::lock_accounts(&account_ids, conn)?;
+        ensure_available_balance(&legs, conn)?;
+
+        let new_tx = NewTransaction {
+            from_account_id,
+            to_account_id,
+            amount: reversal_amount,
+            tx_type,
+            status: TransactionStatus::Completed,
+            description: req
+                .description
+                .clone()
+                .or_else(|| Some(format!("Reversal of transaction {}", original.id))),
+            idempotency_key: req.idempotency_key.clone(),
+            reversal_of_id: Some(original.id),
+            batch_id: None,
+            execute_at: None,
+            fx_rate: original.fx_rate.map(fx_service::invert_rate),
+            converted_amount,
+            converted_currency,
+            currency,
+            metadata: metadata.clone(),
+            fee_amount: 0,
+            fee_account_id: None,
+        };
+        let reversal = insert_transaction(&new_tx, actor_key_id, conn)?;
+        post_journal_entry(&reversal, &legs, conn)?;
+
+        let mut original = repositories::add_refunded_amount(original.id, amount, conn)?;
+        if original.refunded_amount == original.amount {
+            original = transition_status(
+                &original,
+                TransactionStatus::Reversed,
+                actor_key_id,
+                Some("Fully refunded".to_string()),
+                conn,
+            )?;
+        }
+
+        let payload = serde_json::json!({
+            "transaction_id": original.id,
+            "reversal_id": reversal.id,
+            "amount": amount,
+            "refunded_amount": original.refunded_amount,
+        });
+        for account_id in account_ids.iter() {
+            webhook_service::emit_event(
+                *account_id,
+                "transaction.reversed",
+                payload.clone(),
+                conn,
+            )?;
+        }
+
+        Ok((original, reversal))
+    })?;
+
+    tracing::info!(
+        transaction_id = original.id,
+        reversal_id = reversal.id,
+        refunded_amount = original.refunded_amount,
+        "Transaction reversed"
+    );
+
+    Ok(reversal.into())
+}
+
+// Void every authorization whose hold has expired. Returns how many were voided.
+pub fn expire_authorizations(conn: &mut PgConnection) -> Result<usize, AppError> {
+    let expired = repositories::get_expired_holds(EXPIRY_BATCH_SIZE, conn)?;
+
+    let mut voided = 0;
+    for hold in expired {
+        let released = db::run_in_transaction(conn, |conn| {
+            let Some(transaction_id) = hold.transaction_id else {
+                return Ok(false);
+            };
+            let tx = repositories::get_transaction_for_update(transaction_id, conn)?;
+
+            // Captured or voided since the expired holds were loaded
+            if tx.status != TransactionStatus::Pending
+                || repositories::get_active_hold_by_transaction(tx.id, conn)?.is_none()
+            {
+                return Ok(false);
+            }
+
+            repositories::release_hold(hold.id, conn)?;
+            transition_status(
+                &tx,
+                TransactionStatus::Cancelled,
+                None,
+                Some("Authorization expired".to_string()),
+                conn,
+            )?;
+            Ok(true)
+        })?;
+
+        if released {
+            voided += 1;
+        }
+    }
+
+    Ok(voided)
+}
+
+// Carry out what was deferred while a transaction was held for risk review: post it, or
+// place its authorization hold. Scheduled transactions are left pending for the scheduler.
+// Must run inside the caller's database transaction.
+pub fn release_reviewed_transaction(
+    id: i64,
+    capture: bool,
+    actor_key_id: i64,
+    conn: &mut PgConnection,
+) -> Result<Transaction, AppError> {
+    let tx = repositories::get_transaction_for_update(id, conn)?;
+    ensure_transition(&tx, TransactionStatus::Completed)?;
+    if tx.execute_at.is_some() {
+        return Ok(tx);
+    }
+
+    let legs = expected_legs(&tx)?;
+    let account_ids: Vec<i64> = legs.iter().filter_map(|leg| leg.account_id).collect();
+    repositories::lock_accounts(&account_ids, conn)?;
+    ensure_available_balance(&legs, conn)?;
+
+    if !capture {
+        place_authorization_hold(&tx, conn)?;
+        return Ok(tx);
+    }
+
+    post_journal_entry(&tx, &legs, conn)?;
+    transition_status(
+        &tx,
+        TransactionStatus::Completed,
+        Some(actor_key_id),
+        Some("Approved in risk review".to_string()),
+        conn,
+    )
+}
+
+// Fail a transaction declined in risk review; one the customer cancelled in the meantime
+// is left as it is. Must run inside the caller's database transaction.
+pub fn fail_reviewed_transaction(
+    id: i64,
+    actor_key_id: i64,
+    reason: String,
+    conn: &mut PgConnection,
+) -> Result<Transaction, AppError> {
+    let tx = repositories::get_transaction_for_update(id, conn)?;
+    if tx.status == TransactionStatus::Cancelled {
+        return Ok(tx);
+    }
+    transition_status(
+        &tx,
+        TransactionStatus::Failed,
+        Some(actor_key_id),
+        Some(reason),
+        conn,
+    )
+}
+
+// Run scheduled transactions whose execute_at has passed. Returns how many were processed.
+pub fn execute_scheduled_transactions(conn: &mut PgConnection) -> Result<usize, AppError> {
+    let due = repositories::get_due_scheduled_transactions(SCHEDULER_BATCH_SIZE, conn)?;
+
+    let mut processed = 0;
+    for scheduled in due {
+        let executed = db::run_in_transaction(conn, |conn| {
+            let tx = repositories::get_transaction_for_update(scheduled.id, conn)?;
 
+            // Cancelled since the due transactions were loaded
+            if tx.status != TransactionStatus::Pending {
+                return Ok(None);
+            }
+
+            // Converted at the rate and charged the fee quoted when the transaction was scheduled
+            let legs = expected_legs(&tx)?;
+
+            // Post in a savepoint so a failed attempt leaves nothing behind but the status change
+            let result = conn.transaction(|conn| {
+                let account_ids: Vec<i64> = legs.iter().filter_map(|leg| leg.account_id).collect();
+                repositories::lock_accounts(&account_ids, conn)?;
+                ensure_available_balance(&legs, conn)?;
+                post_journal_entry(&tx, &legs, conn)
+            });
+
+            match result {
+                Ok(_) => {
+                    let tx = transition_status(
+                        &tx,
+                        TransactionStatus::Completed,
+                        None,
+                        Some("Executed on schedule".to_string()),
+                        conn,
+                    )?;
+                    Ok(Some(tx))
+                }
+                Err(
+                    e @ (AppError::InsufficientBalance
+                    | AppError::AccountFrozen { .. }
+                    | AppError::AccountInactive { .. }
+                    | AppError::AccountNotFound
+                    | AppError::WalletNotFound { .. }),
+                ) => {
+                    let reason = format!("{} at execution time", e.message());
+                    let tx = transition_status(
+                        &tx,
+                        TransactionStatus::Failed,
+                        None,
+                        Some(reason.clone()),
+                        conn,
+                    )?;
+                    if let Some(account_id) = tx.from_account_id.or(tx.to_account_id) {
+                        webhook_service::emit_event(
+                            account_id,
+                            "transaction.failed",
+                            serde_json::json!({
+                                "transaction_id": tx.id,
+                                "reason": reason,
+                            }),
+                            conn,
+                        )?;
+                    }
+                    Ok(Some(tx))
+                }
+                Err(e) => Err(e),
+            }
+        })?;
+
+        if let Some(tx) = executed {
+            if tx.status == TransactionStatus::Failed {
+                tracing::warn!(transaction_id = tx.id, "Scheduled transaction failed");
+            } else {
+                tracing::info!(transaction_id = tx.id, "Scheduled transaction executed");
+            }
+            processed += 1;
+        }
+    }
+
+    Ok(processed)
+}
+
+// Each transaction type becomes a pair of balanced legs, with the external
+// side (None) standing for money entering or leaving the ledger. A converted
+// transfer books each currency separately through the external side.
+pub fn build_legs(
+    tx_type: TransactionType,
+    from_account_id: Option<i64>,
+    to_account_id: Option<i64>,
+    amount: i64,
+    currency: &str,
+    conversion: Option<(i64, &str)>, // Converted amount and currency credited to the destination
+) -> Result<Vec<PostingLeg>, AppError> {
+    let leg = |account_id: Option<i64>, currency: &str, amount: i64| PostingLeg {
+        account_id,
+        currency: currency.to_string(),
+        amount,
+    };
+
+    let legs = match parties(tx_type, from_account_id, to_account_id)? {
+        (Some(from_id), Some(to_id)) => match conversion {
+            None => vec![
+                leg(Some(from_id), currency, -amount),
+                leg(Some(to_id), currency, amount),
+            ],
+            Some((converted_amount, converted_currency)) => vec![
+                leg(Some(from_id), currency, -amount),
+                leg(None, currency, amount),
+                leg(None, converted_currency, -converted_amount),
+                leg(Some(to_id), converted_currency, converted_amount),
+            ],
+        },
+        (None, Some(to_id)) => vec![
+            leg(None, currency, -amount),
+            leg(Some(to_id), currency, amount),
+        ],
+        (Some(from_id), None) => vec![
+            leg(Some(from_id), currency, -amount),
+            leg(None, currency, amount),
+        ],
+        (None, None) => unreachable!("every transaction type has at least one account"),
+    };
+    Ok(legs)
+}
+
+// The legs of a transaction's journal entry, from the amounts, rate and fee stored on it
+pub fn expected_legs(tx: &Transaction) -> Result<Vec<PostingLeg>, AppError> {
+    let mut legs = build_legs(
+        tx.tx_type,
+        tx.from_account_id,
+        tx.to_account_id,
+        tx.amount,
+        &tx.currency,
+        tx.converted_amount.zip(tx.converted_currency.as_deref()),
+    )?;
+    legs.extend(fee_legs(
+        tx.from_account_id,
+        tx.fee_account_id,
+        tx.fee_amount,
+        &tx.currency,
+    ));
+    Ok(legs)
+}
+
+// The fee is a separate pair of legs from the paying account to the fee account
+fn fee_legs(
+    from_account_id: Option<i64>,
+    fee_account_id: Option<i64>,
+    fee_amount: i64,
+    currency: &str,
+) -> Vec<PostingLeg> {
+    match (from_account_id, fee_account_id) {
+        (Some(from_id), Some(fee_account_id)) if fee_amount > 0 => vec![
+            PostingLeg {
+                account_id: Some(from_id),
+                currency: currency.to_string(),
+                amount: -fee_amount,
+            },
+            PostingLeg {
+                account_id: Some(fee_account_id),
+                currency: currency.to_string(),
+                amount: fee_amount,
+            },
+        ],
+        _ => Vec::new(),
+    }
+}
+
+// The accounts a transaction moves money between, checking that its type has the ones it needs
+fn parties(
+    tx_type: TransactionType,
+    from_account_id: Option<i64>,
+    to_account_id: Option<i64>,
+) -> Result<(Option<i64>, Option<i64>), AppError> {
+    let from_required =
+        || from_account_id.ok_or(AppError::BadRequest("from_account_id required".to_string()));
+    let to_required =
+        || to_account_id.ok_or(AppError::BadRequest("to_account_id required".to_string()));
+
+    match tx_type {
+        TransactionType::Transfer => {
+            let from_id = from_required()?;
+            let to_id = to_required()?;
             if from_id == to_id {
                 return Err(AppError::BadRequest(
                     "Cannot transfer to same account".to_string(),
                 ));
             }
+            Ok((Some(from_id), Some(to_id)))
+        }
+        TransactionType::Credit => Ok((None, Some(to_required()?))),
+        TransactionType::Debit => Ok((Some(from_required()?), None)),
+    }
+}
+
+// Ids of the accounts a transaction touches, the paying account first
+pub fn account_ids(
+    tx_type: TransactionType,
+    from_account_id: Option<i64>,
+    to_account_id: Option<i64>,
+) -> Result<Vec<i64>, AppError> {
+    let (from_id, to_id) = parties(tx_type, from_account_id, to_account_id)?;
+    Ok(from_id.into_iter().chain(to_id).collect())
+}
 
-            // Atomic transfer
-            repositories::debit_account(from_id, req.amount, conn)?;
-            repositories::credit_account(to_id, req.amount, conn)?;
+// The currency a new transaction moves: the one requested, or else the default currency
+// of the paying account (the receiving account for credits)
+pub fn resolve_currency(
+    requested: Option<&str>,
+    tx_type: TransactionType,
+    from_account_id: Option<i64>,
+    to_account_id: Option<i64>,
+    conn: &mut PgConnection,
+) -> Result<String, AppError> {
+    if let Some(currency) = requested {
+        return validation::normalize_currency(currency).map_err(AppError::BadRequest);
+    }
+    let account_id = account_ids(tx_type, from_account_id, to_account_id)?[0];
+    Ok(repositories::get_account_by_id(account_id, conn)?.currency)
+}
+
+// Debited wallets must cover everything taken from them (amount and fee) from their
+// available balance (ledger balance minus active holds) plus their overdraft limit.
+// Accounts must already be locked.
+fn ensure_available_balance(legs: &[PostingLeg], conn: &mut PgConnection) -> Result<(), AppError> {
+    ensure_wallets(legs, conn)?;
+    ensure_funds(legs, conn)
+}
+
+fn ensure_funds(legs: &[PostingLeg], conn: &mut PgConnection) -> Result<(), AppError> {
+    let mut debits: HashMap<(i64, &str), i64> = HashMap::new();
+    for leg in legs.iter().filter(|leg| leg.amount < 0) {
+        if let Some(account_id) = leg.account_id {
+            *debits
+                .entry((account_id, leg.currency.as_str()))
+                .or_default() -= leg.amount;
         }
-        TransactionType::Credit => {
-            let to_id = req
-                .to_account_id
-                .ok_or(AppError::BadRequest("to_account_id required".to_string()))?;
-            repositories::credit_account(to_id, req.amount, conn)?;
+    }
+
+    for ((account_id, currency), debit) in debits {
+        let wallet = get_wallet(account_id, currency, conn)?;
+        let held = repositories::get_held_amount(account_id, currency, conn)?;
+        if wallet.balance + wallet.overdraft_limit - held < debit {
+            return Err(AppError::InsufficientBalance);
         }
-        TransactionType::Debit => {
-            let from_id = req
-                .from_account_id
-                .ok_or(AppError::BadRequest("from_account_id required".to_string()))?;
-            repositories::debit_account(from_id, req.amount, conn)?;
+    }
+    Ok(())
+}
+
+// Every account leg must be booked to a wallet the account has opened on an active
+// account, and no account may be frozen for the direction its legs move money in
+fn ensure_wallets(legs: &[PostingLeg], conn: &mut PgConnection) -> Result<(), AppError> {
+    check_wallets(legs, None, conn)
+}
+
+// ensure_wallets, except that `closing_account_id` may already be inactive
+fn check_wallets(
+    legs: &[PostingLeg],
+    closing_account_id: Option<i64>,
+    conn: &mut PgConnection,
+) -> Result<(), AppError> {
+    for leg in legs {
+        if let Some(account_id) = leg.account_id {
+            get_wallet(account_id, &leg.currency, conn)?;
         }
     }
 
-    let tx = repositories::create_transaction(&new_tx, conn)?;
-    Ok(tx.into())
+    let account_ids: Vec<i64> = legs.iter().filter_map(|leg| leg.account_id).collect();
+    if let Some(account) = repositories::get_accounts(&account_ids, conn)?
+        .into_iter()
+        .find(|account| !account.is_active && Some(account.id) != closing_account_id)
+    {
+        return Err(AppError::AccountInactive {
+            account_id: account.id,
+            closed: account.closed_at.is_some(),
+        });
+    }
+    for freeze in repositories::get_active_freezes(&account_ids, conn)? {
+        if legs.iter().any(|leg| {
+            leg.account_id == Some(freeze.account_id) && freeze.scope.blocks(leg.amount < 0)
+        }) {
+            return Err(AppError::AccountFrozen {
+                account_id: freeze.account_id,
+                scope: freeze.scope,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn get_wallet(
+    account_id: i64,
+    currency: &str,
+    conn: &mut PgConnection,
+) -> Result<AccountBalance, AppError> {
+    repositories::get_account_balance(account_id, currency, conn)?.ok_or_else(|| {
+        AppError::WalletNotFound {
+            account_id,
+            currency: currency.to_string(),
+        }
+    })
+}
+
+// Write the journal entry of a transaction and raise the balance events of every
+// wallet it moved
+fn post_journal_entry(
+    tx: &Transaction,
+    legs: &[PostingLeg],
+    conn: &mut PgConnection,
+) -> Result<JournalEntry, AppError> {
+    let mut before: Vec<AccountBalance> = Vec::new();
+    for leg in legs {
+        if let Some(account_id) = leg.account_id
+            && !before
+                .iter()
+                .any(|wallet| wallet.account_id == account_id && wallet.currency == leg.currency)
+        {
+            before.push(get_wallet(account_id, &leg.currency, conn)?);
+        }
+    }
+
+    let entry = repositories::create_journal_entry(tx.id, tx.description.clone(), legs, conn)?;
+
+    for wallet in before {
+        let after = get_wallet(wallet.account_id, &wallet.currency, conn)?;
+        account_service::emit_balance_events(&wallet, &after, tx.id, conn)?;
+    }
+    Ok(entry)
+}
+
+fn get_authorization_hold(tx: &Transaction, conn: &mut PgConnection) -> Result<Hold, AppError> {
+    repositories::get_active_hold_by_transaction(tx.id, conn)?.ok_or(AppError::Conflict(
+        "Transaction has no active authorization".to_string(),
+    ))
+}
+
+// Insert a completed transaction and its journal entry, including the legs of any fee set
+// on it. Callers must already hold the account locks; they are taken again here to read
+// balances that include earlier writes.
+pub fn post_transaction(
+    new_tx: &NewTransaction,
+    convert: bool,
+    actor_key_id: Option<i64>,
+    conn: &mut PgConnection,
+) -> Result<Transaction, AppError> {
+    post(new_tx, convert, actor_key_id, None, conn)
+}
+
+// Post the transfer that empties a wallet of an account being closed. An admin may have
+// deactivated that account already, so only the receiving account has to be active.
+pub fn post_closing_sweep(
+    new_tx: &NewTransaction,
+    actor_key_id: Option<i64>,
+    conn: &mut PgConnection,
+) -> Result<Transaction, AppError> {
+    post(new_tx, false, actor_key_id, new_tx.from_account_id, conn)
+}
+
+fn post(
+    new_tx: &NewTransaction,
+    convert: bool,
+    actor_key_id: Option<i64>,
+    closing_account_id: Option<i64>,
+    conn: &mut PgConnection,
+) -> Result<Transaction, AppError> {
+    let account_ids = account_ids(new_tx.tx_type, new_tx.from_account_id, new_tx.to_account_id)?;
+    let accounts = repositories::lock_accounts(&account_ids, conn)?;
+
+    let mut new_tx = new_tx.clone();
+    let mut postings = apply_conversion(&mut new_tx, convert, &accounts, conn)?;
+    postings.extend(fee_legs(
+        new_tx.from_account_id,
+        new_tx.fee_account_id,
+        new_tx.fee_amount,
+        &new_tx.currency,
+    ));
+    check_wallets(&postings, closing_account_id, conn)?;
+    ensure_funds(&postings, conn)?;
+
+    let tx = insert_transaction(&new_tx, actor_key_id, conn)?;
+    post_journal_entry(&tx, &postings, conn)?;
+    Ok(tx)
+}
+
+// Apply the currency rules to a new transaction between locked accounts and build its legs.
+// A transfer is credited in its own currency when the destination holds a wallet in it;
+// otherwise it is converted into the destination's default currency, which needs `convert`.
+fn apply_conversion(
+    new_tx: &mut NewTransaction,
+    convert: bool,
+    accounts: &[Account],
+    conn: &mut PgConnection,
+) -> Result<Vec<PostingLeg>, AppError> {
+    if new_tx.tx_type == TransactionType::Transfer
+        && let Some(to_id) = new_tx.to_account_id
+        && repositories::get_account_balance(to_id, &new_tx.currency, conn)?.is_none()
+    {
+        let to = find_account(accounts, to_id)?;
+        if let Some(fx) = fx_service::resolve_conversion(
+            &new_tx.currency,
+            &to.currency,
+            new_tx.amount,
+            convert,
+            conn,
+        )? {
+            new_tx.fx_rate = Some(fx.rate);
+            new_tx.converted_amount = Some(fx.converted_amount);
+            new_tx.converted_currency = Some(fx.currency);
+        }
+    }
+
+    build_legs(
+        new_tx.tx_type,
+        new_tx.from_account_id,
+        new_tx.to_account_id,
+        new_tx.amount,
+        &new_tx.currency,
+        new_tx
+            .converted_amount
+            .zip(new_tx.converted_currency.as_deref()),
+    )
+}
+
+fn find_account(accounts: &[Account], id: i64) -> Result<&Account, AppError> {
+    accounts
+        .iter()
+        .find(|account| account.id == id)
+        .ok_or(AppError::AccountNotFound)
+}
+
+// `part / whole` of `total`, rounded down
+fn prorate(total: i64, part: i64, whole: i64) -> i64 {
+    (i128::from(total) * i128::from(part) / i128::from(whole)) as i64
+}
+
+// Attribute a client error to the leg that caused it. Conflicts are passed through
+// untouched so the whole batch is retried.
+fn batch_leg_error(index: usize, error: AppError) -> AppError {
+    match error {
+        AppError::TransactionConflict | AppError::DatabaseError(_) | AppError::InternalError(_) => {
+            error
+        }
+        error => AppError::BatchLegFailed {
+            index,
+            error: Box::new(error),
+        },
+    }
+}
+
+fn batch_response(batch: TransactionBatch, txs: Vec<Transaction>) -> BatchResponse {
+    BatchResponse {
+        id: batch.id,
+        description: batch.description,
+        idempotency_key: batch.idempotency_key,
+        created_at: batch.created_at,
+        legs: txs
+            .into_iter()
+            .enumerate()
+            .map(|(index, tx)| BatchLegResult {
+                index,
+                transaction: tx.into(),
+            })
+            .collect(),
+    }
+}
+
+// Insert a transaction together with the first entry of its status history
+fn insert_transaction(
+    new_tx: &NewTransaction,
+    actor_key_id: Option<i64>,
+    conn: &mut PgConnection,
+) -> Result<Transaction, AppError> {
+    let tx = repositories::create_transaction(new_tx, conn)?;
+    repositories::create_status_history(
+        &NewTransactionStatusHistory {
+            transaction_id: tx.id,
+            from_status: None,
+            to_status: tx.status,
+            actor_api_key_id: actor_key_id,
+            reason: None,
+        },
+        conn,
+    )?;
+    Ok(tx)
+}
+
+fn ensure_transition(tx: &Transaction, to: TransactionStatus) -> Result<(), AppError> {
+    if !tx.status.can_transition_to(to) {
+        return Err(AppError::InvalidStatusTransition {
+            from: tx.status,
+            to,
+        });
+    }
+    Ok(())
+}
+
+// Move a transaction to a new status through the state machine and record the transition
+fn transition_status(
+    tx: &Transaction,
+    to: TransactionStatus,
+    actor_key_id: Option<i64>,
+    reason: Option<String>,
+    conn: &mut PgConnection,
+) -> Result<Transaction, AppError> {
+    ensure_transition(tx, to)?;
+
+    let updated = repositories::update_transaction_status(tx.id, to, conn)?;
+    repositories::create_status_history(
+        &NewTransactionStatusHistory {
+            transaction_id: tx.id,
+            from_status: Some(tx.status),
+            to_status: to,
+            actor_api_key_id: actor_key_id,
+            reason,
+        },
+        conn,
+    )?;
+    Ok(updated)
+}
+
+// Reserve everything capturing an authorization in full takes from the paying
+// wallet: the amount and its fee
+fn place_authorization_hold(tx: &Transaction, conn: &mut PgConnection) -> Result<Hold, AppError> {
+    let from_id = tx
+        .from_account_id
+        .ok_or(AppError::BadRequest("from_account_id required".to_string()))?;
+    repositories::create_hold(
+        &NewHold {
+            account_id: from_id,
+            transaction_id: Some(tx.id),
+            amount: tx.amount + tx.fee_amount,
+            expires_at: Some(Utc::now().naive_utc() + hold_expiry()),
+            currency: tx.currency.clone(),
+            reason: None,
+            placed_by_api_key_id: None,
+        },
+        conn,
+    )
+}
+
+fn hold_expiry() -> Duration {
+    let seconds = std::env::var("HOLD_EXPIRY_SECONDS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_HOLD_EXPIRY_SECONDS);
+    Duration::seconds(seconds)
+}
+
+pub fn get_batch(id: i64, conn: &mut PgConnection) -> Result<BatchResponse, AppError> {
+    let batch = repositories::get_batch_by_id(id, conn)?;
+    let txs = repositories::get_batch_transactions(batch.id, conn)?;
+    Ok(batch_response(batch, txs))
 }
 
 pub fn get_transaction(id: i64, conn: &mut PgConnection) -> Result<TransactionResponse, AppError> {
     let tx = repositories::get_transaction_by_id(id, conn)?;
-    Ok(tx.into())
+    let reversals = repositories::get_reversals(id, conn)?;
+
+    let mut response: TransactionResponse = tx.into();
+    response.reversals = reversals.into_iter().map(Into::into).collect();
+    Ok(response)
 }
 
-// List all transactions for an account (both sent and received)
+// List a page of an account's transactions (both sent and received), each with its
+// direction and the balance it left the account with
 pub fn list_account_transactions(
     account_id: i64,
+    query: ListTransactionsQuery,
+    conn: &mut PgConnection,
+) -> Result<AccountTransactionListResponse, AppError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+    if !(1..=MAX_LIST_LIMIT).contains(&limit) {
+        return Err(AppError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_LIST_LIMIT
+        )));
+    }
+    if let (Some(min), Some(max)) = (query.min_amount, query.max_amount)
+        && min > max
+    {
+        return Err(AppError::BadRequest(
+            "min_amount cannot exceed max_amount".to_string(),
+        ));
+    }
+    if query.metadata_value.is_some() && query.metadata_key.is_none() {
+        return Err(AppError::BadRequest(
+            "metadata_value requires metadata_key".to_string(),
+        ));
+    }
+    if let (Some(from), Some(to)) = (query.created_from, query.created_to)
+        && from > to
+    {
+        return Err(AppError::BadRequest(
+            "created_from cannot be after created_to".to_string(),
+        ));
+    }
+
+    // The cursor must be one of the account's own transactions
+    let after = match query.starting_after {
+        Some(id) => {
+            let tx = repositories::get_transaction_by_id(id, conn)?;
+            if tx.from_account_id != Some(account_id) && tx.to_account_id != Some(account_id) {
+                return Err(AppError::BadRequest(format!(
+                    "starting_after {} is not a transaction of account {}",
+                    id, account_id
+                )));
+            }
+            Some(tx)
+        }
+        None => None,
+    };
+
+    // One extra row tells whether another page follows
+    let mut transactions = repositories::get_account_transactions_page(
+        account_id,
+        &query,
+        after.as_ref(),
+        limit + 1,
+        conn,
+    )?;
+    let has_more = transactions.len() as i64 > limit;
+    transactions.truncate(limit as usize);
+
+    let ids: Vec<i64> = transactions.iter().map(|tx| tx.id).collect();
+    // Postings come in id order, so each transaction keeps its latest one
+    let last_postings: HashMap<i64, (String, i64)> =
+        repositories::get_account_postings_for_transactions(account_id, &ids, conn)?
+            .into_iter()
+            .map(|(transaction_id, currency, posting_id)| (transaction_id, (currency, posting_id)))
+            .collect();
+
+    transactions
+        .into_iter()
+        .map(|tx| {
+            let direction = if tx.to_account_id == Some(account_id) {
+                EntryDirection::Inflow
+            } else {
+                EntryDirection::Outflow
+            };
+            let (balance_after, balance_after_decimal) = match last_postings.get(&tx.id) {
+                Some((code, posting_id)) => {
+                    let balance = repositories::get_balance_after_posting(
+                        account_id,
+                        code,
+                        *posting_id,
+                        conn,
+                    )?;
+                    (Some(balance), Some(currency::format_amount(balance, code)))
+                }
+                None => (None, None),
+            };
+            Ok(AccountTransactionResponse {
+                transaction: tx.into(),
+                direction,
+                balance_after,
+                balance_after_decimal,
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()
+        .map(|data| AccountTransactionListResponse { data, has_more })
+}
+
+pub fn get_transaction_history(
+    id: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<TransactionStatusHistory>, AppError> {
+    repositories::get_transaction_by_id(id, conn)?;
+    repositories::get_status_history(id, conn)
+}
+
+// Journal entries (with their postings) recorded for a transaction
+pub fn get_transaction_journal(
+    id: i64,
     conn: &mut PgConnection,
-) -> Result<Vec<TransactionResponse>, AppError> {
-    let transactions = repositories::get_account_transactions(account_id, conn)?;
-    Ok(transactions.into_iter().map(Into::into).collect())
+) -> Result<Vec<JournalEntryResponse>, AppError> {
+    let entries = repositories::get_journal_entries_by_transaction(id, conn)?;
+    entries
+        .into_iter()
+        .map(|entry| {
+            let postings = repositories::get_postings_by_journal_entry(entry.id, conn)?;
+            Ok(JournalEntryResponse {
+                id: entry.id,
+                transaction_id: entry.transaction_id,
+                description: entry.description,
+                created_at: entry.created_at,
+                postings,
+            })
+        })
+        .collect()
 }
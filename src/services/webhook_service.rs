@@ -8,7 +8,7 @@ pub fn register_webhook(
     req: RegisterWebhookRequest,
     conn: &mut PgConnection,
 ) -> Result<WebhookEndpointResponse, AppError> {
-    let secret = format!("whsec_{}", Uuid::new_v4().to_string());
+    let secret = format!("whsec_{}", Uuid::new_v4());
     let events = json!(req.events);
 
     let new_endpoint = NewWebhookEndpoint {
@@ -46,6 +46,37 @@ pub fn get_webhook(id: i64, conn: &mut PgConnection) -> Result<WebhookEndpointRe
     })
 }
 
+// Queue an event for every active endpoint of the account subscribed to it
+pub fn emit_event(
+    account_id: i64,
+    event_type: &str,
+    payload: serde_json::Value,
+    conn: &mut PgConnection,
+) -> Result<(), AppError> {
+    let endpoints = repositories::get_webhook_endpoints_by_account(account_id, conn)?;
+
+    for endpoint in endpoints {
+        let subscribed = endpoint
+            .events
+            .as_array()
+            .is_some_and(|events| events.iter().any(|event| event == event_type));
+        if !endpoint.is_active || !subscribed {
+            continue;
+        }
+
+        let new_event = NewWebhookEvent {
+            webhook_endpoint_id: endpoint.id,
+            event_type: event_type.to_string(),
+            payload: payload.clone(),
+            status: WebhookStatus::Pending,
+            attempt_count: 0,
+        };
+        repositories::create_webhook_event(&new_event, conn)?;
+    }
+
+    Ok(())
+}
+
 pub fn delete_webhook(_id: i64, _conn: &mut PgConnection) -> Result<(), AppError> {
     // In production, implement soft delete
     Ok(())
@@ -6,10 +6,14 @@ use axum::{
 };
 use serde::Serialize;
 
+use crate::models::{FreezeScope, LimitScope, TransactionStatus};
+
 #[derive(Debug, Serialize)]
 pub struct ErrorResponse {
     pub error: String,
     pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
 }
 
 #[derive(Debug)]
@@ -29,11 +33,54 @@ pub enum AppError {
     AccountNotFound,
     TransactionNotFound,
     WebhookNotFound,
+    WalletNotFound {
+        account_id: i64,
+        currency: String,
+    },
 
     // 409
     Conflict(String),
     InsufficientBalance,
     DuplicateIdempotencyKey,
+    TransactionConflict, // Serialization failure or deadlock, safe to retry
+    InvalidStatusTransition {
+        from: TransactionStatus,
+        to: TransactionStatus,
+    },
+    AccountFrozen {
+        account_id: i64,
+        scope: FreezeScope,
+    },
+    AccountInactive {
+        account_id: i64,
+        closed: bool,
+    },
+
+    // Status of the underlying error; nothing in the batch was committed
+    BatchLegFailed {
+        index: usize,
+        error: Box<AppError>,
+    },
+
+    // 422
+    CurrencyMismatch {
+        from: String,
+        to: String,
+    },
+    FxRateNotFound {
+        base: String,
+        quote: String,
+    },
+    LimitExceeded {
+        scope: LimitScope,
+        scope_id: i64,
+        currency: String,
+        limit: &'static str,
+        max: i64,
+        remaining: i64,
+    },
+    RiskBlocked, // Matched rules are logged, not returned to the caller
+    RiskReviewRequired,
 
     // 429
     RateLimitExceeded,
@@ -43,10 +90,10 @@ pub enum AppError {
     InternalError(String),
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, code, message) = match self {
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "BAD_REQUEST", msg),
+impl AppError {
+    fn parts(&self) -> (StatusCode, &'static str, String) {
+        match self {
+            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "BAD_REQUEST", msg.clone()),
             AppError::Unauthorized => (
                 StatusCode::UNAUTHORIZED,
                 "UNAUTHORIZED",
@@ -82,6 +129,14 @@ impl IntoResponse for AppError {
                 "WEBHOOK_NOT_FOUND",
                 "Webhook not found".to_string(),
             ),
+            AppError::WalletNotFound {
+                account_id,
+                currency,
+            } => (
+                StatusCode::NOT_FOUND,
+                "WALLET_NOT_FOUND",
+                format!("Account {} has no {} wallet", account_id, currency),
+            ),
             AppError::InsufficientBalance => (
                 StatusCode::CONFLICT,
                 "INSUFFICIENT_BALANCE",
@@ -92,13 +147,93 @@ impl IntoResponse for AppError {
                 "DUPLICATE_REQUEST",
                 "Duplicate request".to_string(),
             ),
+            AppError::TransactionConflict => (
+                StatusCode::CONFLICT,
+                "TRANSACTION_CONFLICT",
+                "Concurrent update conflict, please retry".to_string(),
+            ),
+            AppError::InvalidStatusTransition { from, to } => (
+                StatusCode::CONFLICT,
+                "INVALID_STATUS_TRANSITION",
+                format!(
+                    "Cannot move transaction from {} to {}",
+                    from.as_str(),
+                    to.as_str()
+                ),
+            ),
+            AppError::AccountFrozen { account_id, scope } => (
+                StatusCode::CONFLICT,
+                "ACCOUNT_FROZEN",
+                match scope {
+                    FreezeScope::Outflows => {
+                        format!("Outflows from account {} are frozen", account_id)
+                    }
+                    FreezeScope::All => format!("Account {} is frozen", account_id),
+                },
+            ),
+            AppError::AccountInactive { account_id, closed } => (
+                StatusCode::CONFLICT,
+                "ACCOUNT_INACTIVE",
+                if *closed {
+                    format!("Account {} is closed", account_id)
+                } else {
+                    format!("Account {} is inactive", account_id)
+                },
+            ),
+            AppError::BatchLegFailed { index, error } => {
+                let (status, _, message) = error.parts();
+                (
+                    status,
+                    "BATCH_LEG_FAILED",
+                    format!("Batch leg {} failed: {}", index, message),
+                )
+            }
+            AppError::CurrencyMismatch { from, to } => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "CURRENCY_MISMATCH",
+                format!(
+                    "Cannot transfer {} to a {} account without conversion, set convert to true",
+                    from, to
+                ),
+            ),
+            AppError::FxRateNotFound { base, quote } => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "FX_RATE_NOT_FOUND",
+                format!("No exchange rate from {} to {}", base, quote),
+            ),
+            AppError::LimitExceeded {
+                scope,
+                limit,
+                remaining,
+                ..
+            } => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "LIMIT_EXCEEDED",
+                format!(
+                    "Transaction exceeds the {} limit {} ({} remaining)",
+                    scope.as_str(),
+                    limit,
+                    remaining
+                ),
+            ),
+            AppError::RiskBlocked => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "RISK_BLOCKED",
+                "Transaction was blocked by risk checks".to_string(),
+            ),
+            AppError::RiskReviewRequired => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "RISK_REVIEW_REQUIRED",
+                "Transaction needs a risk review, submit it on its own to have it held for review"
+                    .to_string(),
+            ),
             AppError::RateLimitExceeded => (
                 StatusCode::TOO_MANY_REQUESTS,
                 "RATE_LIMIT_EXCEEDED",
                 "Too many requests".to_string(),
             ),
-            AppError::Conflict(msg) => (StatusCode::CONFLICT, "CONFLICT", msg),
-            AppError::DatabaseError(ref msg) => {
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, "CONFLICT", msg.clone()),
+            AppError::DatabaseError(msg) => {
                 tracing::error!(error = %msg, "Database error occurred");
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -106,7 +241,7 @@ impl IntoResponse for AppError {
                     msg.clone(),
                 )
             }
-            AppError::InternalError(ref msg) => {
+            AppError::InternalError(msg) => {
                 tracing::error!(error = %msg, "Internal server error occurred");
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -114,7 +249,53 @@ impl IntoResponse for AppError {
                     msg.clone(),
                 )
             }
-        };
+        }
+    }
+
+    pub fn message(&self) -> String {
+        self.parts().2
+    }
+
+    // Extra machine-readable context for errors that carry it
+    fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            AppError::BatchLegFailed { index, error } => Some(serde_json::json!({
+                "leg_index": index,
+                "leg_code": error.parts().1,
+                "leg_details": error.details(),
+            })),
+            AppError::AccountFrozen { account_id, scope } => Some(serde_json::json!({
+                "account_id": account_id,
+                "scope": scope,
+            })),
+            AppError::AccountInactive { account_id, closed } => Some(serde_json::json!({
+                "account_id": account_id,
+                "closed": closed,
+            })),
+            AppError::LimitExceeded {
+                scope,
+                scope_id,
+                currency,
+                limit,
+                max,
+                remaining,
+            } => Some(serde_json::json!({
+                "scope": scope,
+                "scope_id": scope_id,
+                "currency": currency,
+                "limit": limit,
+                "max": max,
+                "remaining": remaining,
+            })),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let details = self.details();
+        let (status, code, message) = self.parts();
 
         // Log client and server errors
         if status.is_client_error() {
@@ -126,8 +307,26 @@ impl IntoResponse for AppError {
         let error_response = ErrorResponse {
             error: message,
             code: code.to_string(),
+            details,
         };
 
         (status, Json(error_response)).into_response()
     }
 }
+
+impl From<diesel::result::Error> for AppError {
+    fn from(e: diesel::result::Error) -> Self {
+        use diesel::result::{DatabaseErrorKind, Error};
+
+        match e {
+            Error::DatabaseError(DatabaseErrorKind::SerializationFailure, _) => {
+                AppError::TransactionConflict
+            }
+            // Postgres reports deadlocks (40P01) without a dedicated error kind
+            Error::DatabaseError(_, ref info) if info.message().contains("deadlock detected") => {
+                AppError::TransactionConflict
+            }
+            _ => AppError::DatabaseError(e.to_string()),
+        }
+    }
+}
@@ -1,12 +1,38 @@
+use crate::utils::app_error::AppError;
 use diesel::r2d2::{self, ConnectionManager};
-use diesel::PgConnection;
+use diesel::{Connection, PgConnection};
+use rand::Rng;
+use std::time::Duration;
 
 pub type DbPool = r2d2::Pool<ConnectionManager<PgConnection>>;
 
+const MAX_TRANSACTION_ATTEMPTS: u32 = 5;
+
 pub fn create_pool(database_url: &str) -> Result<DbPool, Box<dyn std::error::Error>> {
     let manager = ConnectionManager::<PgConnection>::new(database_url);
     let pool = r2d2::Pool::builder()
         .max_size(20)
         .build(manager)?;
     Ok(pool)
-}
\ No newline at end of file
+}
+
+/// Run `f` inside a single database transaction, retrying the whole closure
+/// when Postgres aborts it with a serialization failure or a deadlock.
+pub fn run_in_transaction<T, F>(conn: &mut PgConnection, mut f: F) -> Result<T, AppError>
+where
+    F: FnMut(&mut PgConnection) -> Result<T, AppError>,
+{
+    let mut attempt = 1;
+    loop {
+        match conn.transaction(&mut f) {
+            Err(AppError::TransactionConflict) if attempt < MAX_TRANSACTION_ATTEMPTS => {
+                tracing::warn!(attempt = attempt, "Retrying transaction after conflict");
+                // Exponential backoff with jitter so retries do not collide again
+                let backoff = 10 * 2u64.pow(attempt) + rand::thread_rng().gen_range(0..10);
+                std::thread::sleep(Duration::from_millis(backoff));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub mod crypto;
 pub mod validation;
 pub mod app_error;
-pub mod db;
\ No newline at end of file
+pub mod db;
+pub mod currency;
\ No newline at end of file
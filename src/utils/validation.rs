@@ -1,4 +1,12 @@
 #![allow(dead_code)]
+use crate::utils::currency;
+use serde_json::Value;
+
+pub const MAX_METADATA_KEYS: usize = 50;
+pub const MAX_METADATA_KEY_LENGTH: usize = 40;
+pub const MAX_METADATA_VALUE_LENGTH: usize = 500;
+pub const MAX_REASON_LENGTH: usize = 500;
+
 pub fn validate_amount(amount: i64) -> Result<(), String> {
     if amount <= 0 {
         return Err("Amount must be positive".to_string());
@@ -13,3 +21,89 @@ pub fn validate_email(email: &str) -> Result<(), String> {
         Err("Invalid email".to_string())
     }
 }
+
+// Currency codes must be ISO 4217 codes from the registry, stored in upper case
+pub fn normalize_currency(code: &str) -> Result<String, String> {
+    let code = code.trim().to_ascii_uppercase();
+    match currency::find(&code) {
+        Some(currency) => Ok(currency.code.to_string()),
+        None => Err(format!("Unsupported currency code '{}'", code)),
+    }
+}
+
+// Free-text reasons kept in audit records; a blank reason counts as none
+pub fn validate_reason(reason: Option<String>) -> Result<Option<String>, String> {
+    let Some(reason) = reason.map(|reason| reason.trim().to_string()) else {
+        return Ok(None);
+    };
+    if reason.is_empty() {
+        return Ok(None);
+    }
+    if reason.chars().count() > MAX_REASON_LENGTH {
+        return Err(format!(
+            "reason cannot be longer than {} characters",
+            MAX_REASON_LENGTH
+        ));
+    }
+    Ok(Some(reason))
+}
+
+// Metadata is a flat object of string values; it is stored as {} when absent
+pub fn validate_metadata(metadata: Option<Value>) -> Result<Value, String> {
+    let Some(metadata) = metadata else {
+        return Ok(Value::Object(Default::default()));
+    };
+    let Some(object) = metadata.as_object() else {
+        return Err("metadata must be a JSON object".to_string());
+    };
+    if object.len() > MAX_METADATA_KEYS {
+        return Err(format!(
+            "metadata cannot have more than {} keys",
+            MAX_METADATA_KEYS
+        ));
+    }
+    for (key, value) in object {
+        if key.is_empty() || key.chars().count() > MAX_METADATA_KEY_LENGTH {
+            return Err(format!(
+                "metadata keys must be 1 to {} characters long",
+                MAX_METADATA_KEY_LENGTH
+            ));
+        }
+        match value.as_str() {
+            Some(value) if value.chars().count() <= MAX_METADATA_VALUE_LENGTH => {}
+            Some(_) => {
+                return Err(format!(
+                    "metadata value of '{}' cannot be longer than {} characters",
+                    key, MAX_METADATA_VALUE_LENGTH
+                ));
+            }
+            None => return Err(format!("metadata value of '{}' must be a string", key)),
+        }
+    }
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_metadata() {
+        assert_eq!(validate_metadata(None), Ok(json!({})));
+        assert_eq!(
+            validate_metadata(Some(json!({ "order_id": "A-1001" }))),
+            Ok(json!({ "order_id": "A-1001" }))
+        );
+        assert!(validate_metadata(Some(json!(["order_id"]))).is_err());
+        assert!(validate_metadata(Some(json!({ "order_id": 1001 }))).is_err());
+        assert!(validate_metadata(Some(json!({ "": "x" }))).is_err());
+        assert!(validate_metadata(Some(json!({ "k".repeat(41): "x" }))).is_err());
+        assert!(validate_metadata(Some(json!({ "note": "x".repeat(501) }))).is_err());
+
+        let too_many: serde_json::Map<String, Value> = (0..51)
+            .map(|i| (format!("key_{}", i), json!("x")))
+            .collect();
+        assert!(validate_metadata(Some(Value::Object(too_many))).is_err());
+    }
+}
@@ -2,6 +2,10 @@
 // cargo test test_rate_limiting
 // cargo test test_rate_limiting   -- --nocapture   (this allows to see print statements)
 
+#![allow(clippy::needless_borrows_for_generic_args)]
+#![allow(clippy::single_component_path_imports)]
+
+use diesel::{Connection, PgConnection, RunQueryDsl, sql_query, sql_types::BigInt};
 use reqwest;
 use serde_json::json;
 use std::time::Duration;
@@ -9,6 +13,19 @@ use tokio::time::sleep;
 
 const BASE_URL: &str = "http://localhost:8080";
 
+// Admin key of the server under test, for the admin-only endpoints
+fn admin_key() -> String {
+    dotenvy::dotenv().ok();
+    std::env::var("ADMIN_API_KEY").expect("ADMIN_API_KEY must be set")
+}
+
+// Connection to the same database the server under test uses
+fn db_connection() -> PgConnection {
+    dotenvy::dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    PgConnection::establish(&database_url).expect("Failed to connect to database")
+}
+
 // Test helper to create an account and get API key
 async fn create_test_account(client: &reqwest::Client) -> (i64, String) {
     let response = client
@@ -210,7 +227,8 @@ async fn test_list_account_transactions() {
 
     assert_eq!(response.status(), 200);
 
-    let transactions: Vec<serde_json::Value> = response.json().await.expect("Failed to parse");
+    let body: serde_json::Value = response.json().await.expect("Failed to parse");
+    let transactions = body["data"].as_array().expect("No data");
 
     // Account 1 should see BOTH transactions (credit and transfer)
     assert_eq!(transactions.len(), 2);
@@ -469,3 +487,2514 @@ async fn test_transfer_to_same_account() {
 
     assert_eq!(response.status(), 400); // Bad Request
 }
+
+// cargo test --test transaction_tests test_concurrent_transfers_conserve_balances -- --nocapture
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn test_concurrent_transfers_conserve_balances() {
+    const ACCOUNTS: usize = 10;
+    const TRANSFERS: usize = 300; // 30 per account, stays under the 60/min key limit
+    const INITIAL_BALANCE: i64 = 5000;
+
+    let client = reqwest::Client::new();
+
+    let mut accounts = Vec::new();
+    for _ in 0..ACCOUNTS {
+        let (account_id, api_key) = create_test_account(&client).await;
+        let response = client
+            .post(format!("{}/api/transactions", BASE_URL))
+            .header("x-api-key", &api_key)
+            .json(&json!({
+                "tx_type": "credit",
+                "to_account_id": account_id,
+                "amount": INITIAL_BALANCE,
+                "idempotency_key": format!("concurrency_seed_{}", uuid::Uuid::new_v4())
+            }))
+            .send()
+            .await
+            .expect("Failed to seed account");
+        assert_eq!(response.status(), 200);
+        accounts.push((account_id, api_key));
+    }
+
+    // Fire every transfer at once, including opposite-direction pairs that would
+    // deadlock without ordered row locking
+    let mut handles = Vec::new();
+    for i in 0..TRANSFERS {
+        let from = i % ACCOUNTS;
+        let to = (from + 1 + (i * 7) % (ACCOUNTS - 1)) % ACCOUNTS;
+        let amount = 100 + (i as i64 * 37) % 900;
+        let (from_id, api_key) = accounts[from].clone();
+        let to_id = accounts[to].0;
+        let client = client.clone();
+
+        handles.push(tokio::spawn(async move {
+            let response = client
+                .post(format!("{}/api/transactions", BASE_URL))
+                .header("x-api-key", &api_key)
+                .json(&json!({
+                    "tx_type": "transfer",
+                    "from_account_id": from_id,
+                    "to_account_id": to_id,
+                    "amount": amount,
+                    "idempotency_key": format!("concurrency_{}", uuid::Uuid::new_v4())
+                }))
+                .send()
+                .await
+                .expect("Failed to send transfer");
+            (from, to, amount, response.status().as_u16())
+        }));
+    }
+
+    let mut expected = [INITIAL_BALANCE; ACCOUNTS];
+    let mut completed = 0;
+    for handle in handles {
+        let (from, to, amount, status) = handle.await.expect("Transfer task panicked");
+        match status {
+            200 => {
+                expected[from] -= amount;
+                expected[to] += amount;
+                completed += 1;
+            }
+            409 => {} // Insufficient balance
+            status => panic!("Unexpected status {}", status),
+        }
+    }
+    println!("✅ {} of {} transfers completed", completed, TRANSFERS);
+
+    let mut total = 0;
+    for (index, (account_id, api_key)) in accounts.iter().enumerate() {
+        let response = client
+            .get(format!("{}/api/accounts/{}/balance", BASE_URL, account_id))
+            .header("x-api-key", api_key)
+            .send()
+            .await
+            .expect("Failed to get balance");
+        let body: serde_json::Value = response.json().await.expect("Failed to parse");
+        let balance = body["balance"].as_i64().expect("No balance");
+
+        assert!(balance >= 0, "Account {} was overdrawn", account_id);
+        assert_eq!(
+            balance, expected[index],
+            "Balance mismatch for {}",
+            account_id
+        );
+        total += balance;
+    }
+
+    assert_eq!(
+        total,
+        INITIAL_BALANCE * ACCOUNTS as i64,
+        "Money was created or lost"
+    );
+}
+
+#[tokio::test]
+async fn test_partial_refunds_cannot_exceed_original_amount() {
+    let client = reqwest::Client::new();
+    let (payer_id, payer_key) = create_test_account(&client).await;
+    let (merchant_id, merchant_key) = create_test_account(&client).await;
+
+    client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &payer_key)
+        .json(&json!({
+            "tx_type": "credit",
+            "to_account_id": payer_id,
+            "amount": 10000,
+            "idempotency_key": format!("refund_seed_{}", uuid::Uuid::new_v4())
+        }))
+        .send()
+        .await
+        .expect("Failed to credit");
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &payer_key)
+        .json(&json!({
+            "tx_type": "transfer",
+            "from_account_id": payer_id,
+            "to_account_id": merchant_id,
+            "amount": 4000,
+            "idempotency_key": format!("refund_payment_{}", uuid::Uuid::new_v4())
+        }))
+        .send()
+        .await
+        .expect("Failed to transfer");
+    let payment: serde_json::Value = response.json().await.expect("Failed to parse");
+    let payment_id = payment["id"].as_i64().expect("No transaction ID");
+
+    // The payer cannot refund themselves
+    let response = client
+        .post(format!(
+            "{}/api/transactions/{}/reverse",
+            BASE_URL, payment_id
+        ))
+        .header("x-api-key", &payer_key)
+        .json(&json!({}))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), 403);
+
+    // Partial refund by the merchant
+    let response = client
+        .post(format!(
+            "{}/api/transactions/{}/reverse",
+            BASE_URL, payment_id
+        ))
+        .header("x-api-key", &merchant_key)
+        .json(&json!({ "amount": 1500 }))
+        .send()
+        .await
+        .expect("Failed to refund");
+    assert_eq!(response.status(), 200);
+    let refund: serde_json::Value = response.json().await.expect("Failed to parse");
+    assert_eq!(refund["reversal_of_id"], payment_id);
+    assert_eq!(refund["from_account_id"], merchant_id);
+    assert_eq!(refund["to_account_id"], payer_id);
+
+    // More than what is left is rejected
+    let response = client
+        .post(format!(
+            "{}/api/transactions/{}/reverse",
+            BASE_URL, payment_id
+        ))
+        .header("x-api-key", &merchant_key)
+        .json(&json!({ "amount": 3000 }))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), 400);
+
+    // The original lists its reversal and the refunded amount
+    let response = client
+        .get(format!("{}/api/transactions/{}", BASE_URL, payment_id))
+        .header("x-api-key", &payer_key)
+        .send()
+        .await
+        .expect("Failed to get transaction");
+    let original: serde_json::Value = response.json().await.expect("Failed to parse");
+    assert_eq!(original["refunded_amount"], 1500);
+    assert_eq!(original["reversals"].as_array().map(Vec::len), Some(1));
+
+    let response = client
+        .get(format!("{}/api/accounts/{}/balance", BASE_URL, payer_id))
+        .header("x-api-key", &payer_key)
+        .send()
+        .await
+        .expect("Failed to get balance");
+    let balance: serde_json::Value = response.json().await.expect("Failed to parse");
+    assert_eq!(balance["balance"], 7500);
+}
+
+#[tokio::test]
+async fn test_batch_is_all_or_nothing() {
+    let client = reqwest::Client::new();
+    let (buyer_id, buyer_key) = create_test_account(&client).await;
+    let (seller_id, _) = create_test_account(&client).await;
+    let (platform_id, _) = create_test_account(&client).await;
+
+    client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &buyer_key)
+        .json(&json!({
+            "tx_type": "credit",
+            "to_account_id": buyer_id,
+            "amount": 1000,
+            "idempotency_key": format!("batch_seed_{}", uuid::Uuid::new_v4())
+        }))
+        .send()
+        .await
+        .expect("Failed to credit");
+
+    // The second leg overdraws the buyer, so neither leg may be applied
+    let idempotency_key = format!("batch_{}", uuid::Uuid::new_v4());
+    let response = client
+        .post(format!("{}/api/transactions/batch", BASE_URL))
+        .header("x-api-key", &buyer_key)
+        .json(&json!({
+            "idempotency_key": idempotency_key,
+            "legs": [
+                { "tx_type": "transfer", "from_account_id": buyer_id, "to_account_id": seller_id, "amount": 900 },
+                { "tx_type": "transfer", "from_account_id": buyer_id, "to_account_id": platform_id, "amount": 200 }
+            ]
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), 409);
+    let error: serde_json::Value = response.json().await.expect("Failed to parse");
+    assert_eq!(error["code"], "BATCH_LEG_FAILED");
+    assert_eq!(error["details"]["leg_index"], 1);
+
+    let response = client
+        .get(format!("{}/api/accounts/{}/balance", BASE_URL, buyer_id))
+        .header("x-api-key", &buyer_key)
+        .send()
+        .await
+        .expect("Failed to get balance");
+    let balance: serde_json::Value = response.json().await.expect("Failed to parse");
+    assert_eq!(balance["balance"], 1000);
+
+    // The same key can be used once the batch fits
+    let response = client
+        .post(format!("{}/api/transactions/batch", BASE_URL))
+        .header("x-api-key", &buyer_key)
+        .json(&json!({
+            "idempotency_key": idempotency_key,
+            "legs": [
+                { "tx_type": "transfer", "from_account_id": buyer_id, "to_account_id": seller_id, "amount": 900 },
+                { "tx_type": "transfer", "from_account_id": buyer_id, "to_account_id": platform_id, "amount": 100 }
+            ]
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), 200);
+    let batch: serde_json::Value = response.json().await.expect("Failed to parse");
+    let legs = batch["legs"].as_array().expect("No legs");
+    assert_eq!(legs.len(), 2);
+    assert_eq!(legs[1]["transaction"]["batch_id"], batch["id"]);
+}
+
+#[tokio::test]
+async fn test_scheduled_transaction_can_be_cancelled() {
+    let client = reqwest::Client::new();
+    let (from_id, from_key) = create_test_account(&client).await;
+    let (to_id, _) = create_test_account(&client).await;
+
+    // Scheduled transfers are accepted without funds; the balance is checked when they run
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &from_key)
+        .json(&json!({
+            "tx_type": "transfer",
+            "from_account_id": from_id,
+            "to_account_id": to_id,
+            "amount": 500,
+            "execute_at": "2099-01-01T00:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("Failed to schedule transfer");
+    assert_eq!(response.status(), 200);
+    let scheduled: serde_json::Value = response.json().await.expect("Failed to parse");
+    assert_eq!(scheduled["status"], "pending");
+    let scheduled_id = scheduled["id"].as_i64().expect("No transaction ID");
+
+    let upcoming_url = format!(
+        "{}/api/transactions/account/{}?upcoming=true",
+        BASE_URL, from_id
+    );
+    let response = client
+        .get(&upcoming_url)
+        .header("x-api-key", &from_key)
+        .send()
+        .await
+        .expect("Failed to list transactions");
+    let upcoming: serde_json::Value = response.json().await.expect("Failed to parse");
+    let upcoming = upcoming["data"].as_array().expect("No data");
+    assert_eq!(upcoming.len(), 1);
+    assert_eq!(upcoming[0]["id"], scheduled_id);
+
+    let response = client
+        .post(format!(
+            "{}/api/transactions/{}/cancel",
+            BASE_URL, scheduled_id
+        ))
+        .header("x-api-key", &from_key)
+        .json(&json!({ "reason": "No longer needed" }))
+        .send()
+        .await
+        .expect("Failed to cancel");
+    assert_eq!(response.status(), 200);
+
+    let response = client
+        .get(&upcoming_url)
+        .header("x-api-key", &from_key)
+        .send()
+        .await
+        .expect("Failed to list transactions");
+    let upcoming: serde_json::Value = response.json().await.expect("Failed to parse");
+    assert!(upcoming["data"].as_array().expect("No data").is_empty());
+}
+
+#[tokio::test]
+async fn test_recurring_transfer_pause_and_resume() {
+    let client = reqwest::Client::new();
+    let (from_id, from_key) = create_test_account(&client).await;
+    let (to_id, to_key) = create_test_account(&client).await;
+
+    let request = json!({
+        "from_account_id": from_id,
+        "to_account_id": to_id,
+        "amount": 2500,
+        "interval": "month",
+        "start_at": "2099-01-01T09:00:00Z",
+        "max_occurrences": 12
+    });
+
+    // Only the paying account can set up a standing order
+    let response = client
+        .post(format!("{}/api/recurring_transfers", BASE_URL))
+        .header("x-api-key", &to_key)
+        .json(&request)
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), 403);
+
+    let response = client
+        .post(format!("{}/api/recurring_transfers", BASE_URL))
+        .header("x-api-key", &from_key)
+        .json(&request)
+        .send()
+        .await
+        .expect("Failed to create recurring transfer");
+    assert_eq!(response.status(), 200);
+    let transfer: serde_json::Value = response.json().await.expect("Failed to parse");
+    assert_eq!(transfer["status"], "active");
+    assert_eq!(transfer["next_run_at"], "2099-01-01T09:00:00");
+    let transfer_id = transfer["id"].as_i64().expect("No recurring transfer ID");
+
+    for (action, expected_status, expected_code) in [
+        ("pause", "paused", 200),
+        ("pause", "", 409),
+        ("resume", "active", 200),
+    ] {
+        let response = client
+            .post(format!(
+                "{}/api/recurring_transfers/{}/{}",
+                BASE_URL, transfer_id, action
+            ))
+            .header("x-api-key", &from_key)
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(response.status(), expected_code);
+        if expected_code == 200 {
+            let transfer: serde_json::Value = response.json().await.expect("Failed to parse");
+            assert_eq!(transfer["status"], expected_status);
+            assert_eq!(transfer["next_run_at"], "2099-01-01T09:00:00");
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_cross_currency_transfer_requires_conversion() {
+    let client = reqwest::Client::new();
+    let (usd_id, usd_key) = create_test_account(&client).await;
+
+    let response = client
+        .post(format!("{}/api/accounts", BASE_URL))
+        .json(&json!({
+            "business_name": "Test EUR Account",
+            "currency": "EUR"
+        }))
+        .send()
+        .await
+        .expect("Failed to create account");
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    let eur_id = body["account"]["id"].as_i64().expect("No account ID");
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &usd_key)
+        .json(&json!({
+            "to_account_id": usd_id,
+            "amount": 1000,
+            "tx_type": "credit"
+        }))
+        .send()
+        .await
+        .expect("Failed to credit account");
+    assert_eq!(response.status(), 200);
+
+    // Moving dollars into a euro account 1:1 must be refused
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &usd_key)
+        .json(&json!({
+            "from_account_id": usd_id,
+            "to_account_id": eur_id,
+            "amount": 500,
+            "tx_type": "transfer"
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), 422);
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(body["code"], "CURRENCY_MISMATCH");
+
+    let response = client
+        .get(format!("{}/api/accounts/{}/balance", BASE_URL, usd_id))
+        .header("x-api-key", &usd_key)
+        .send()
+        .await
+        .expect("Failed to get balance");
+    let balance: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(balance["balance"], 1000);
+}
+
+#[tokio::test]
+async fn test_currency_wallets_hold_separate_balances() {
+    let client = reqwest::Client::new();
+    let (account_id, api_key) = create_test_account(&client).await;
+
+    let credit = json!({
+        "to_account_id": account_id,
+        "amount": 2500,
+        "currency": "GBP",
+        "tx_type": "credit"
+    });
+
+    // Funds can only land in a wallet the account has opened
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &api_key)
+        .json(&credit)
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), 404);
+
+    let response = client
+        .post(format!("{}/api/accounts/{}/wallets", BASE_URL, account_id))
+        .header("x-api-key", &api_key)
+        .json(&json!({ "currency": "GBP" }))
+        .send()
+        .await
+        .expect("Failed to open wallet");
+    assert_eq!(response.status(), 200);
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &api_key)
+        .json(&credit)
+        .send()
+        .await
+        .expect("Failed to credit wallet");
+    assert_eq!(response.status(), 200);
+    let tx: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(tx["currency"], "GBP");
+
+    let response = client
+        .get(format!("{}/api/accounts/{}/balance", BASE_URL, account_id))
+        .header("x-api-key", &api_key)
+        .send()
+        .await
+        .expect("Failed to get balance");
+    let balance: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(balance["currency"], "USD");
+    assert_eq!(balance["balance"], 0);
+    assert_eq!(
+        balance["balances"],
+        json!([
+            {
+                "currency": "GBP",
+                "balance": 2500,
+                "available_balance": 2500,
+                "balance_decimal": "25.00",
+                "available_balance_decimal": "25.00",
+                "overdraft_limit": 0
+            },
+            {
+                "currency": "USD",
+                "balance": 0,
+                "available_balance": 0,
+                "balance_decimal": "0.00",
+                "available_balance_decimal": "0.00",
+                "overdraft_limit": 0
+            }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_balance_as_of_excludes_later_postings() {
+    let client = reqwest::Client::new();
+    let (account_id, api_key) = create_test_account(&client).await;
+
+    let credit = |amount: i64| {
+        json!({
+            "to_account_id": account_id,
+            "amount": amount,
+            "tx_type": "credit"
+        })
+    };
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &api_key)
+        .json(&credit(1000))
+        .send()
+        .await
+        .expect("Failed to credit account");
+    assert_eq!(response.status(), 200);
+
+    sleep(Duration::from_millis(1100)).await;
+    let as_of = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true);
+    sleep(Duration::from_millis(1100)).await;
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &api_key)
+        .json(&credit(250))
+        .send()
+        .await
+        .expect("Failed to credit account");
+    assert_eq!(response.status(), 200);
+
+    let response = client
+        .get(format!("{}/api/accounts/{}/balance", BASE_URL, account_id))
+        .query(&[("as_of", &as_of)])
+        .header("x-api-key", &api_key)
+        .send()
+        .await
+        .expect("Failed to get balance");
+    assert_eq!(response.status(), 200);
+    let balance: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(balance["balance"], 1000);
+
+    let response = client
+        .get(format!("{}/api/accounts/{}/balance", BASE_URL, account_id))
+        .header("x-api-key", &api_key)
+        .send()
+        .await
+        .expect("Failed to get balance");
+    let balance: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(balance["balance"], 1250);
+}
+
+#[tokio::test]
+async fn test_account_history_has_direction_and_running_balance() {
+    let client = reqwest::Client::new();
+    let (account_id, api_key) = create_test_account(&client).await;
+
+    for body in [
+        json!({ "to_account_id": account_id, "amount": 1000, "tx_type": "credit" }),
+        json!({ "from_account_id": account_id, "amount": 300, "tx_type": "debit" }),
+    ] {
+        let response = client
+            .post(format!("{}/api/transactions", BASE_URL))
+            .header("x-api-key", &api_key)
+            .json(&body)
+            .send()
+            .await
+            .expect("Failed to create transaction");
+        assert_eq!(response.status(), 200);
+    }
+
+    let response = client
+        .get(format!("{}/api/transactions/account/{}", BASE_URL, account_id))
+        .header("x-api-key", &api_key)
+        .send()
+        .await
+        .expect("Failed to list transactions");
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    let entries = body["data"].as_array().expect("No data");
+
+    // Newest first
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["tx_type"], "debit");
+    assert_eq!(entries[0]["direction"], "outflow");
+    assert_eq!(entries[0]["balance_after"], 700);
+    assert_eq!(entries[0]["balance_after_decimal"], "7.00");
+    assert_eq!(entries[1]["direction"], "inflow");
+    assert_eq!(entries[1]["balance_after"], 1000);
+}
+
+#[tokio::test]
+async fn test_account_transactions_are_paginated() {
+    let client = reqwest::Client::new();
+    let (account_id, api_key) = create_test_account(&client).await;
+
+    for amount in [100, 200, 300] {
+        let response = client
+            .post(format!("{}/api/transactions", BASE_URL))
+            .header("x-api-key", &api_key)
+            .json(&json!({ "to_account_id": account_id, "amount": amount, "tx_type": "credit" }))
+            .send()
+            .await
+            .expect("Failed to create transaction");
+        assert_eq!(response.status(), 200);
+    }
+
+    let list = |query: Vec<(&'static str, String)>| {
+        client
+            .get(format!(
+                "{}/api/transactions/account/{}",
+                BASE_URL, account_id
+            ))
+            .query(&query)
+            .header("x-api-key", &api_key)
+            .send()
+    };
+
+    let response = list(vec![("limit", "2".to_string())])
+        .await
+        .expect("Failed to list transactions");
+    assert_eq!(response.status(), 200);
+    let page: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(page["has_more"], true);
+    let amounts: Vec<i64> = page["data"]
+        .as_array()
+        .expect("No data")
+        .iter()
+        .map(|tx| tx["amount"].as_i64().unwrap())
+        .collect();
+    assert_eq!(amounts, vec![300, 200]);
+
+    let last_id = page["data"][1]["id"].as_i64().unwrap();
+    let response = list(vec![
+        ("limit", "2".to_string()),
+        ("starting_after", last_id.to_string()),
+    ])
+    .await
+    .expect("Failed to list transactions");
+    let page: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(page["has_more"], false);
+    assert_eq!(page["data"].as_array().unwrap().len(), 1);
+    assert_eq!(page["data"][0]["amount"], 100);
+
+    let response = list(vec![
+        ("min_amount", "150".to_string()),
+        ("max_amount", "250".to_string()),
+    ])
+    .await
+    .expect("Failed to list transactions");
+    let page: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(page["data"].as_array().unwrap().len(), 1);
+    assert_eq!(page["data"][0]["amount"], 200);
+}
+
+#[tokio::test]
+async fn test_transactions_searchable_by_metadata() {
+    let client = reqwest::Client::new();
+    let (account_id, api_key) = create_test_account(&client).await;
+
+    for order_id in ["A-1001", "A-1002"] {
+        let response = client
+            .post(format!("{}/api/transactions", BASE_URL))
+            .header("x-api-key", &api_key)
+            .json(&json!({
+                "to_account_id": account_id,
+                "amount": 100,
+                "tx_type": "credit",
+                "description": format!("Order {}", order_id),
+                "metadata": { "order_id": order_id }
+            }))
+            .send()
+            .await
+            .expect("Failed to create transaction");
+        assert_eq!(response.status(), 200);
+        let tx: serde_json::Value = response.json().await.expect("Failed to parse response");
+        assert_eq!(tx["metadata"]["order_id"], order_id);
+        assert_eq!(tx["description"], format!("Order {}", order_id));
+    }
+
+    // Metadata values must be strings
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &api_key)
+        .json(&json!({
+            "to_account_id": account_id,
+            "amount": 100,
+            "tx_type": "credit",
+            "metadata": { "order_id": 1003 }
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), 400);
+
+    let response = client
+        .get(format!(
+            "{}/api/transactions/account/{}",
+            BASE_URL, account_id
+        ))
+        .query(&[("metadata_key", "order_id"), ("metadata_value", "A-1002")])
+        .header("x-api-key", &api_key)
+        .send()
+        .await
+        .expect("Failed to list transactions");
+    assert_eq!(response.status(), 200);
+    let page: serde_json::Value = response.json().await.expect("Failed to parse response");
+    let data = page["data"].as_array().expect("No data");
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0]["metadata"], json!({ "order_id": "A-1002" }));
+}
+
+#[tokio::test]
+async fn test_statement_has_opening_running_and_closing_balances() {
+    let client = reqwest::Client::new();
+    let (account_id, api_key) = create_test_account(&client).await;
+
+    let create = |body: serde_json::Value| {
+        client
+            .post(format!("{}/api/transactions", BASE_URL))
+            .header("x-api-key", &api_key)
+            .json(&body)
+            .send()
+    };
+
+    let response =
+        create(json!({ "to_account_id": account_id, "amount": 1000, "tx_type": "credit" }))
+            .await
+            .expect("Failed to credit account");
+    assert_eq!(response.status(), 200);
+
+    sleep(Duration::from_millis(1100)).await;
+    let from = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true);
+    sleep(Duration::from_millis(1100)).await;
+
+    let response =
+        create(json!({ "from_account_id": account_id, "amount": 300, "tx_type": "debit" }))
+            .await
+            .expect("Failed to debit account");
+    assert_eq!(response.status(), 200);
+    let response =
+        create(json!({ "to_account_id": account_id, "amount": 50, "tx_type": "credit" }))
+            .await
+            .expect("Failed to credit account");
+    assert_eq!(response.status(), 200);
+
+    let to = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true);
+    let statement_url = format!("{}/api/accounts/{}/statements", BASE_URL, account_id);
+
+    let response = client
+        .get(&statement_url)
+        .query(&[("from", &from), ("to", &to)])
+        .header("x-api-key", &api_key)
+        .send()
+        .await
+        .expect("Failed to export statement");
+    assert_eq!(response.status(), 200);
+    let statement: serde_json::Value = response.json().await.expect("Failed to parse statement");
+    assert_eq!(statement["opening_balance"], 1000);
+    let balances: Vec<i64> = statement["entries"]
+        .as_array()
+        .expect("No entries")
+        .iter()
+        .map(|entry| entry["balance"].as_i64().unwrap())
+        .collect();
+    assert_eq!(balances, vec![700, 750]);
+    assert_eq!(statement["closing_balance"], 750);
+
+    let response = client
+        .get(&statement_url)
+        .query(&[("from", &from), ("to", &to), ("format", &"csv".to_string())])
+        .header("x-api-key", &api_key)
+        .send()
+        .await
+        .expect("Failed to export statement");
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["content-type"], "text/csv");
+    let csv = response.text().await.expect("Failed to read statement");
+    let lines: Vec<&str> = csv.lines().collect();
+    // Header, opening balance, two entries and closing balance
+    assert_eq!(lines.len(), 5);
+    assert!(lines[1].contains("Opening balance"));
+    assert!(lines[4].ends_with("750,7.50"));
+}
+
+#[tokio::test]
+async fn test_close_account_sweeps_balance_and_revokes_keys() {
+    let client = reqwest::Client::new();
+    let (account_id, api_key) = create_test_account(&client).await;
+    let (target_id, target_key) = create_test_account(&client).await;
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &api_key)
+        .json(&json!({ "to_account_id": account_id, "amount": 1200, "tx_type": "credit" }))
+        .send()
+        .await
+        .expect("Failed to credit account");
+    assert_eq!(response.status(), 200);
+
+    let response = client
+        .patch(format!("{}/api/accounts/{}", BASE_URL, account_id))
+        .header("x-api-key", &api_key)
+        .json(&json!({ "business_name": "Renamed Account" }))
+        .send()
+        .await
+        .expect("Failed to update account");
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(body["business_name"], "Renamed Account");
+
+    // Customers cannot switch their own account on or off
+    let response = client
+        .patch(format!("{}/api/accounts/{}", BASE_URL, account_id))
+        .header("x-api-key", &api_key)
+        .json(&json!({ "is_active": false }))
+        .send()
+        .await
+        .expect("Failed to update account");
+    assert_eq!(response.status(), 403);
+
+    let close_url = format!("{}/api/accounts/{}/close", BASE_URL, account_id);
+    let response = client
+        .post(&close_url)
+        .header("x-api-key", &api_key)
+        .json(&json!({}))
+        .send()
+        .await
+        .expect("Failed to close account");
+    assert_eq!(response.status(), 409);
+
+    let response = client
+        .post(&close_url)
+        .header("x-api-key", &api_key)
+        .json(&json!({ "sweep_to_account_id": target_id }))
+        .send()
+        .await
+        .expect("Failed to close account");
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(body["account"]["is_active"], false);
+    assert_eq!(body["account"]["balance"], 0);
+    assert_eq!(body["sweeps"][0]["amount"], 1200);
+
+    // The closed account's keys no longer work and it cannot receive funds
+    let response = client
+        .get(format!("{}/api/accounts/{}", BASE_URL, account_id))
+        .header("x-api-key", &api_key)
+        .send()
+        .await
+        .expect("Failed to get account");
+    assert_eq!(response.status(), 401);
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &target_key)
+        .json(&json!({
+            "from_account_id": target_id,
+            "to_account_id": account_id,
+            "amount": 100,
+            "tx_type": "transfer"
+        }))
+        .send()
+        .await
+        .expect("Failed to create transfer");
+    assert_eq!(response.status(), 409);
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(body["code"], "ACCOUNT_INACTIVE");
+}
+
+#[tokio::test]
+async fn test_transfer_journal_is_balanced() {
+    let client = reqwest::Client::new();
+    let (payer_id, payer_key) = create_test_account(&client).await;
+    let (payee_id, _) = create_test_account(&client).await;
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &payer_key)
+        .json(&json!({ "to_account_id": payer_id, "amount": 1000, "tx_type": "credit" }))
+        .send()
+        .await
+        .expect("Failed to credit account");
+    assert_eq!(response.status(), 200);
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &payer_key)
+        .json(&json!({
+            "from_account_id": payer_id,
+            "to_account_id": payee_id,
+            "amount": 400,
+            "tx_type": "transfer"
+        }))
+        .send()
+        .await
+        .expect("Failed to create transfer");
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    let tx_id = body["id"].as_i64().expect("No transaction ID");
+
+    let response = client
+        .get(format!("{}/api/transactions/{}/journal", BASE_URL, tx_id))
+        .header("x-api-key", &payer_key)
+        .send()
+        .await
+        .expect("Failed to get journal");
+    assert_eq!(response.status(), 200);
+    let journal: serde_json::Value = response.json().await.expect("Failed to parse response");
+    let entries = journal.as_array().expect("Journal is not a list");
+    assert_eq!(entries.len(), 1);
+
+    let postings = entries[0]["postings"].as_array().expect("No postings");
+    let mut sums = std::collections::HashMap::new();
+    for posting in postings {
+        *sums
+            .entry(posting["currency"].as_str().unwrap().to_string())
+            .or_insert(0) += posting["amount"].as_i64().unwrap();
+    }
+    assert!(sums.values().all(|sum| *sum == 0), "Unbalanced: {:?}", sums);
+    assert!(
+        postings
+            .iter()
+            .any(|p| p["account_id"] == payer_id && p["amount"] == -400)
+    );
+    assert!(
+        postings
+            .iter()
+            .any(|p| p["account_id"] == payee_id && p["amount"] == 400)
+    );
+
+    // A one-sided entry is rejected by the deferred trigger when it commits
+    let mut conn = db_connection();
+    let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        sql_query(
+            "WITH entry AS ( \
+                 INSERT INTO journal_entries (transaction_id, description) \
+                 VALUES ($1, 'unbalanced') RETURNING id \
+             ) \
+             INSERT INTO postings (journal_entry_id, account_id, amount, currency) \
+             SELECT id, $2, 100, 'USD' FROM entry",
+        )
+        .bind::<BigInt, _>(tx_id)
+        .bind::<BigInt, _>(payee_id)
+        .execute(conn)?;
+        Ok(())
+    });
+    let error = result.expect_err("Unbalanced journal entry was committed");
+    assert!(error.to_string().contains("is not balanced"), "{}", error);
+
+    let response = client
+        .get(format!("{}/api/transactions/{}/journal", BASE_URL, tx_id))
+        .header("x-api-key", &payer_key)
+        .send()
+        .await
+        .expect("Failed to get journal");
+    let journal: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(journal.as_array().unwrap().len(), 1);
+}
+
+async fn get_balance(
+    client: &reqwest::Client,
+    account_id: i64,
+    api_key: &str,
+) -> serde_json::Value {
+    let response = client
+        .get(format!("{}/api/accounts/{}/balance", BASE_URL, account_id))
+        .header("x-api-key", api_key)
+        .send()
+        .await
+        .expect("Failed to get balance");
+    assert_eq!(response.status(), 200);
+    response.json().await.expect("Failed to parse response")
+}
+
+#[tokio::test]
+async fn test_authorization_reserves_fee_until_captured_or_voided() {
+    let client = reqwest::Client::new();
+    let (payer_id, payer_key) = create_test_account(&client).await;
+    let (payee_id, payee_key) = create_test_account(&client).await;
+    let (fee_account_id, fee_key) = create_test_account(&client).await;
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &payer_key)
+        .json(&json!({ "to_account_id": payer_id, "amount": 1000, "tx_type": "credit" }))
+        .send()
+        .await
+        .expect("Failed to credit account");
+    assert_eq!(response.status(), 200);
+
+    let response = client
+        .post(format!("{}/api/fee_schedules", BASE_URL))
+        .header("x-api-key", admin_key())
+        .json(&json!({
+            "account_id": payer_id,
+            "tx_type": "transfer",
+            "currency": "USD",
+            "fee_type": "flat",
+            "flat_amount": 100,
+            "fee_account_id": fee_account_id
+        }))
+        .send()
+        .await
+        .expect("Failed to create fee schedule");
+    assert_eq!(response.status(), 200);
+
+    let authorize = |amount: i64| {
+        client
+            .post(format!("{}/api/transactions", BASE_URL))
+            .header("x-api-key", &payer_key)
+            .json(&json!({
+                "from_account_id": payer_id,
+                "to_account_id": payee_id,
+                "amount": amount,
+                "tx_type": "transfer",
+                "capture": false
+            }))
+            .send()
+    };
+
+    // The hold covers the amount and the fee
+    let response = authorize(600).await.expect("Failed to authorize");
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(body["status"], "pending");
+    assert_eq!(body["fee_amount"], 100);
+    let partial_id = body["id"].as_i64().unwrap();
+
+    let balance = get_balance(&client, payer_id, &payer_key).await;
+    assert_eq!(balance["balance"], 1000);
+    assert_eq!(balance["available_balance"], 300);
+
+    // 250 plus its fee is more than the 300 still available
+    let response = authorize(250).await.expect("Failed to authorize");
+    assert_eq!(response.status(), 409);
+
+    // A partial capture posts the captured amount with a pro rata fee and frees the rest
+    let response = client
+        .post(format!(
+            "{}/api/transactions/{}/capture",
+            BASE_URL, partial_id
+        ))
+        .header("x-api-key", &payer_key)
+        .json(&json!({ "amount": 300 }))
+        .send()
+        .await
+        .expect("Failed to capture");
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(body["status"], "completed");
+    assert_eq!(body["amount"], 300);
+    assert_eq!(body["fee_amount"], 50);
+
+    let balance = get_balance(&client, payer_id, &payer_key).await;
+    assert_eq!(balance["balance"], 650);
+    assert_eq!(balance["available_balance"], 650);
+
+    // A full capture takes the whole amount and fee
+    let response = authorize(100).await.expect("Failed to authorize");
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    let full_id = body["id"].as_i64().unwrap();
+    let response = client
+        .post(format!("{}/api/transactions/{}/capture", BASE_URL, full_id))
+        .header("x-api-key", &payer_key)
+        .json(&json!({}))
+        .send()
+        .await
+        .expect("Failed to capture");
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(body["amount"], 100);
+    assert_eq!(body["fee_amount"], 100);
+
+    let balance = get_balance(&client, payer_id, &payer_key).await;
+    assert_eq!(balance["balance"], 450);
+    assert_eq!(balance["available_balance"], 450);
+
+    // Voiding releases the hold without moving any funds
+    let response = authorize(200).await.expect("Failed to authorize");
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    let voided_id = body["id"].as_i64().unwrap();
+    let balance = get_balance(&client, payer_id, &payer_key).await;
+    assert_eq!(balance["available_balance"], 150);
+
+    let response = client
+        .post(format!("{}/api/transactions/{}/void", BASE_URL, voided_id))
+        .header("x-api-key", &payer_key)
+        .send()
+        .await
+        .expect("Failed to void");
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(body["status"], "cancelled");
+
+    let response = client
+        .post(format!(
+            "{}/api/transactions/{}/capture",
+            BASE_URL, voided_id
+        ))
+        .header("x-api-key", &payer_key)
+        .json(&json!({}))
+        .send()
+        .await
+        .expect("Failed to capture");
+    assert_eq!(response.status(), 409);
+
+    let balance = get_balance(&client, payer_id, &payer_key).await;
+    assert_eq!(balance["balance"], 450);
+    assert_eq!(balance["available_balance"], 450);
+    assert_eq!(
+        get_balance(&client, payee_id, &payee_key).await["balance"],
+        400
+    );
+    assert_eq!(
+        get_balance(&client, fee_account_id, &fee_key).await["balance"],
+        150
+    );
+}
+
+#[tokio::test]
+async fn test_expired_authorization_cannot_be_captured() {
+    let client = reqwest::Client::new();
+    let (payer_id, payer_key) = create_test_account(&client).await;
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &payer_key)
+        .json(&json!({ "to_account_id": payer_id, "amount": 500, "tx_type": "credit" }))
+        .send()
+        .await
+        .expect("Failed to credit account");
+    assert_eq!(response.status(), 200);
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &payer_key)
+        .json(&json!({
+            "from_account_id": payer_id,
+            "amount": 200,
+            "tx_type": "debit",
+            "capture": false
+        }))
+        .send()
+        .await
+        .expect("Failed to authorize");
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    let tx_id = body["id"].as_i64().unwrap();
+    assert_eq!(
+        get_balance(&client, payer_id, &payer_key).await["available_balance"],
+        300
+    );
+
+    // Let the hold run out without waiting for the expiry job
+    sql_query(
+        "UPDATE holds SET expires_at = NOW() - INTERVAL '1 minute' WHERE transaction_id = $1",
+    )
+    .bind::<BigInt, _>(tx_id)
+    .execute(&mut db_connection())
+    .expect("Failed to expire hold");
+
+    let balance = get_balance(&client, payer_id, &payer_key).await;
+    assert_eq!(balance["balance"], 500);
+    assert_eq!(balance["available_balance"], 500);
+
+    let response = client
+        .post(format!("{}/api/transactions/{}/capture", BASE_URL, tx_id))
+        .header("x-api-key", &payer_key)
+        .json(&json!({}))
+        .send()
+        .await
+        .expect("Failed to capture");
+    assert_eq!(response.status(), 409);
+    assert_eq!(
+        get_balance(&client, payer_id, &payer_key).await["balance"],
+        500
+    );
+}
+
+#[tokio::test]
+async fn test_batch_cannot_debit_other_accounts() {
+    let client = reqwest::Client::new();
+    let (caller_id, caller_key) = create_test_account(&client).await;
+    let (victim_id, victim_key) = create_test_account(&client).await;
+
+    for (account_id, api_key) in [(caller_id, &caller_key), (victim_id, &victim_key)] {
+        let response = client
+            .post(format!("{}/api/transactions", BASE_URL))
+            .header("x-api-key", api_key)
+            .json(&json!({ "to_account_id": account_id, "amount": 1000, "tx_type": "credit" }))
+            .send()
+            .await
+            .expect("Failed to credit account");
+        assert_eq!(response.status(), 200);
+    }
+
+    // The second leg pays out of an account the caller does not own
+    let response = client
+        .post(format!("{}/api/transactions/batch", BASE_URL))
+        .header("x-api-key", &caller_key)
+        .json(&json!({
+            "legs": [
+                { "tx_type": "transfer", "from_account_id": caller_id, "to_account_id": victim_id, "amount": 100 },
+                { "tx_type": "transfer", "from_account_id": victim_id, "to_account_id": caller_id, "amount": 900 }
+            ]
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), 403);
+
+    assert_eq!(
+        get_balance(&client, caller_id, &caller_key).await["balance"],
+        1000
+    );
+    assert_eq!(
+        get_balance(&client, victim_id, &victim_key).await["balance"],
+        1000
+    );
+}
+
+async fn import_ndjson(
+    client: &reqwest::Client,
+    rows: &[serde_json::Value],
+    upload_id: Option<&str>,
+) -> serde_json::Value {
+    let body: Vec<String> = rows.iter().map(|row| row.to_string()).collect();
+    let mut url = format!("{}/api/transactions/import?format=ndjson", BASE_URL);
+    if let Some(upload_id) = upload_id {
+        url.push_str(&format!("&upload_id={}", upload_id));
+    }
+    let response = client
+        .post(url)
+        .header("x-api-key", admin_key())
+        .body(body.join("\n"))
+        .send()
+        .await
+        .expect("Failed to import");
+    assert_eq!(response.status(), 200);
+    response.json().await.expect("Failed to parse response")
+}
+
+#[tokio::test]
+async fn test_import_of_edited_file_posts_nothing_twice() {
+    let client = reqwest::Client::new();
+    let (account_id, api_key) = create_test_account(&client).await;
+    let credit =
+        |amount: i64| json!({ "tx_type": "credit", "to_account_id": account_id, "amount": amount });
+    let upload_id = format!("statement-{}", account_id);
+    let upload_id = Some(upload_id.as_str());
+
+    // Two identical rows are two separate credits
+    let first = vec![credit(100), credit(200), credit(100)];
+    let report = import_ndjson(&client, &first, upload_id).await;
+    assert_eq!(report["imported"], 3);
+    assert_eq!(
+        get_balance(&client, account_id, &api_key).await["balance"],
+        400
+    );
+
+    // Rows added, moved or changed elsewhere in the file do not affect the rows already imported
+    let edited = vec![
+        credit(50),
+        credit(100),
+        credit(300),
+        credit(200),
+        credit(100),
+        credit(100),
+    ];
+    let report = import_ndjson(&client, &edited, upload_id).await;
+    assert_eq!(report["imported"], 3);
+    assert_eq!(report["skipped"], 3);
+    assert_eq!(report["failed"], 0);
+    assert_eq!(
+        get_balance(&client, account_id, &api_key).await["balance"],
+        850
+    );
+
+    let report = import_ndjson(&client, &edited, upload_id).await;
+    assert_eq!(report["imported"], 0);
+    assert_eq!(report["skipped"], 6);
+    assert_eq!(
+        get_balance(&client, account_id, &api_key).await["balance"],
+        850
+    );
+}
+
+#[tokio::test]
+async fn test_import_reports_bad_rows_and_applies_the_rest() {
+    let client = reqwest::Client::new();
+    let (account_id, api_key) = create_test_account(&client).await;
+
+    let rows = vec![
+        json!({ "tx_type": "credit", "to_account_id": account_id, "amount": 500 }),
+        json!({ "tx_type": "credit", "to_account_id": account_id, "amount": -5 }),
+        json!({ "tx_type": "debit", "from_account_id": account_id, "amount": 200 }),
+        json!({ "tx_type": "debit", "from_account_id": account_id, "amount": 1000 }),
+    ];
+    let report = import_ndjson(&client, &rows, None).await;
+    assert_eq!(report["total_rows"], 4);
+    assert_eq!(report["imported"], 2);
+    assert_eq!(report["failed"], 2);
+    assert_eq!(report["errors"][0]["line"], 2);
+    assert_eq!(report["errors"][1]["line"], 4);
+    assert_eq!(
+        get_balance(&client, account_id, &api_key).await["balance"],
+        300
+    );
+}
+
+#[tokio::test]
+async fn test_cli_import_matches_api_import() {
+    let client = reqwest::Client::new();
+    let (account_id, api_key) = create_test_account(&client).await;
+
+    let upload_id = format!("cli-{}", account_id);
+    let run_import = |rows: &[serde_json::Value]| {
+        let path = std::env::temp_dir().join(format!("import_{}.ndjson", uuid::Uuid::new_v4()));
+        let body: Vec<String> = rows.iter().map(|row| row.to_string()).collect();
+        std::fs::write(&path, body.join("\n")).expect("Failed to write import file");
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_ledger-core"))
+            .arg("import")
+            .arg(&path)
+            .args(["--upload-id", &upload_id])
+            .output()
+            .expect("Failed to run import");
+        std::fs::remove_file(&path).ok();
+
+        // The report is printed last, after any log lines
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let start = stdout.find("{\n").expect("No import report");
+        let report: serde_json::Value =
+            serde_json::from_str(&stdout[start..]).expect("Failed to parse report");
+        (output.status.success(), report)
+    };
+
+    let rows = vec![
+        json!({ "tx_type": "credit", "to_account_id": account_id, "amount": 700 }),
+        json!({ "tx_type": "credit", "to_account_id": account_id, "amount": 0 }),
+        json!({ "tx_type": "credit", "to_account_id": account_id, "amount": 700 }),
+    ];
+    let (success, report) = run_import(&rows);
+    assert!(!success, "A failed row should fail the command");
+    assert_eq!(report["imported"], 2);
+    assert_eq!(report["errors"][0]["line"], 2);
+    assert_eq!(
+        get_balance(&client, account_id, &api_key).await["balance"],
+        1400
+    );
+
+    // Fixing the bad row only imports that row, through the CLI or the API alike
+    let mut fixed = rows.clone();
+    fixed[1] = json!({ "tx_type": "credit", "to_account_id": account_id, "amount": 25 });
+    let report = import_ndjson(&client, &fixed, Some(&upload_id)).await;
+    assert_eq!(report["imported"], 1);
+    assert_eq!(report["skipped"], 2);
+
+    let (success, report) = run_import(&fixed);
+    assert!(success);
+    assert_eq!(report["imported"], 0);
+    assert_eq!(report["skipped"], 3);
+    assert_eq!(
+        get_balance(&client, account_id, &api_key).await["balance"],
+        1425
+    );
+}
+
+#[tokio::test]
+async fn test_identical_rows_in_another_file_are_imported() {
+    let client = reqwest::Client::new();
+    let (account_id, api_key) = create_test_account(&client).await;
+    let credit = json!({ "tx_type": "credit", "to_account_id": account_id, "amount": 1000 });
+    let fee = json!({
+        "tx_type": "debit",
+        "from_account_id": account_id,
+        "amount": 25,
+        "description": "Monthly fee"
+    });
+
+    let january = vec![credit.clone(), fee.clone()];
+    let report = import_ndjson(&client, &january, None).await;
+    assert_eq!(report["imported"], 2);
+
+    // Next month's file repeats the fee row word for word
+    let february = vec![fee.clone()];
+    let report = import_ndjson(&client, &february, None).await;
+    assert_eq!(report["imported"], 1);
+    assert_eq!(report["skipped"], 0);
+    assert_eq!(
+        get_balance(&client, account_id, &api_key).await["balance"],
+        950
+    );
+
+    // Uploading either file again still posts nothing twice
+    for file in [&january, &february] {
+        let report = import_ndjson(&client, file, None).await;
+        assert_eq!(report["imported"], 0);
+        assert_eq!(report["skipped"], file.len());
+    }
+
+    // Rows with an explicit key are deduplicated across files
+    let keyed = |amount: i64| {
+        json!({
+            "tx_type": "credit",
+            "to_account_id": account_id,
+            "amount": amount,
+            "idempotency_key": format!("bank_ref_{}", account_id)
+        })
+    };
+    let report = import_ndjson(&client, &[keyed(300)], None).await;
+    assert_eq!(report["imported"], 1);
+    let report = import_ndjson(&client, &[credit.clone(), keyed(300)], None).await;
+    assert_eq!(report["imported"], 1);
+    assert_eq!(report["skipped"], 1);
+    assert_eq!(
+        get_balance(&client, account_id, &api_key).await["balance"],
+        2250
+    );
+}
+
+#[tokio::test]
+async fn test_deactivated_account_can_be_closed_with_sweep() {
+    let client = reqwest::Client::new();
+    let admin_key = admin_key();
+    let (account_id, api_key) = create_test_account(&client).await;
+    let (target_id, target_key) = create_test_account(&client).await;
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &api_key)
+        .json(&json!({ "to_account_id": account_id, "amount": 800, "tx_type": "credit" }))
+        .send()
+        .await
+        .expect("Failed to credit account");
+    assert_eq!(response.status(), 200);
+
+    let response = client
+        .patch(format!("{}/api/accounts/{}", BASE_URL, account_id))
+        .header("x-api-key", &admin_key)
+        .json(&json!({ "is_active": false }))
+        .send()
+        .await
+        .expect("Failed to deactivate account");
+    assert_eq!(response.status(), 200);
+
+    // The owner cannot close it to sweep out the funds the admin has stopped
+    let response = client
+        .post(format!("{}/api/accounts/{}/close", BASE_URL, account_id))
+        .header("x-api-key", &api_key)
+        .json(&json!({ "sweep_to_account_id": target_id }))
+        .send()
+        .await
+        .expect("Failed to close account");
+    assert_eq!(response.status(), 403);
+    assert_eq!(
+        get_balance(&client, account_id, &admin_key).await["balance"],
+        800
+    );
+
+    let response = client
+        .post(format!("{}/api/accounts/{}/close", BASE_URL, account_id))
+        .header("x-api-key", &admin_key)
+        .json(&json!({ "sweep_to_account_id": target_id }))
+        .send()
+        .await
+        .expect("Failed to close account");
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(body["sweeps"][0]["amount"], 800);
+    assert!(body["account"]["closed_at"].is_string());
+    assert_eq!(
+        get_balance(&client, target_id, &target_key).await["balance"],
+        800
+    );
+
+    // A closed account takes no new wallets, overdraft or holds
+    let requests = [
+        ("wallets", json!({ "currency": "EUR" })),
+        ("overdraft_limit", json!({ "overdraft_limit": 500 })),
+        ("holds", json!({ "amount": 100, "reason": "Court order" })),
+    ];
+    for (path, body) in requests {
+        let response = client
+            .post(format!("{}/api/accounts/{}/{}", BASE_URL, account_id, path))
+            .header("x-api-key", &admin_key)
+            .json(&body)
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(response.status(), 409, "{}", path);
+        let error: serde_json::Value = response.json().await.expect("Failed to parse response");
+        assert_eq!(error["code"], "ACCOUNT_INACTIVE");
+    }
+}
+
+#[tokio::test]
+async fn test_admin_account_listing() {
+    let client = reqwest::Client::new();
+    let admin_key = admin_key();
+    let tag = uuid::Uuid::new_v4().simple().to_string();
+
+    // (name suffix, currency, credit, active)
+    let specs = [
+        ("plain", "USD", 500, true),
+        ("100%", "USD", 1500, true),
+        ("a_c", "EUR", 700, true),
+        ("inactive", "USD", 0, false),
+        ("1000", "USD", 0, true),
+        ("abc", "USD", 0, true),
+    ];
+    let mut accounts = Vec::new();
+    for (suffix, currency, credit, active) in specs {
+        let response = client
+            .post(format!("{}/api/accounts", BASE_URL))
+            .json(&json!({ "business_name": format!("{} {}", tag, suffix), "currency": currency }))
+            .send()
+            .await
+            .expect("Failed to create account");
+        assert_eq!(response.status(), 200);
+        let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+        let account_id = body["account"]["id"].as_i64().unwrap();
+        let api_key = body["secret_api_key"].as_str().unwrap().to_string();
+
+        if credit > 0 {
+            let response = client
+                .post(format!("{}/api/transactions", BASE_URL))
+                .header("x-api-key", &api_key)
+                .json(
+                    &json!({ "to_account_id": account_id, "amount": credit, "tx_type": "credit" }),
+                )
+                .send()
+                .await
+                .expect("Failed to credit account");
+            assert_eq!(response.status(), 200);
+        }
+        if !active {
+            let response = client
+                .patch(format!("{}/api/accounts/{}", BASE_URL, account_id))
+                .header("x-api-key", &admin_key)
+                .json(&json!({ "is_active": false }))
+                .send()
+                .await
+                .expect("Failed to deactivate account");
+            assert_eq!(response.status(), 200);
+        }
+        accounts.push((account_id, api_key));
+    }
+
+    let list = |key: String, query: Vec<(&'static str, String)>| {
+        client
+            .get(format!("{}/api/accounts", BASE_URL))
+            .query(&query)
+            .header("x-api-key", key)
+            .send()
+    };
+    let names = |page: &serde_json::Value| -> Vec<String> {
+        page["data"]
+            .as_array()
+            .expect("No data")
+            .iter()
+            .map(|account| account["business_name"].as_str().unwrap().replace(&tag, ""))
+            .collect()
+    };
+    let search = |term: &str| ("search", format!("{}{}", tag, term));
+
+    let response = list(accounts[0].1.clone(), vec![search("")])
+        .await
+        .expect("Failed to list accounts");
+    assert_eq!(response.status(), 403);
+
+    // Counts and totals cover every match, not only the page
+    let response = list(
+        admin_key.clone(),
+        vec![search(""), ("limit", "2".to_string())],
+    )
+    .await
+    .expect("Failed to list accounts");
+    assert_eq!(response.status(), 200);
+    let page: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(page["total_count"], 6);
+    assert_eq!(page["active_count"], 5);
+    assert_eq!(page["inactive_count"], 1);
+    assert_eq!(page["closed_count"], 0);
+    assert_eq!(
+        page["totals"],
+        json!([
+            { "currency": "EUR", "wallet_count": 1, "total_balance": 700, "total_balance_decimal": "7.00" },
+            { "currency": "USD", "wallet_count": 5, "total_balance": 2000, "total_balance_decimal": "20.00" }
+        ])
+    );
+
+    // Newest first, continued with starting_after
+    let mut listed = Vec::new();
+    let mut page = page;
+    loop {
+        let data = page["data"].as_array().expect("No data");
+        assert!(data.len() <= 2);
+        listed.extend(data.iter().map(|account| account["id"].as_i64().unwrap()));
+        if page["has_more"] == false {
+            break;
+        }
+        let last_id = listed.last().unwrap().to_string();
+        let response = list(
+            admin_key.clone(),
+            vec![
+                search(""),
+                ("limit", "2".to_string()),
+                ("starting_after", last_id),
+            ],
+        )
+        .await
+        .expect("Failed to list accounts");
+        page = response.json().await.expect("Failed to parse response");
+    }
+    let expected: Vec<i64> = accounts.iter().rev().map(|account| account.0).collect();
+    assert_eq!(listed, expected);
+
+    // LIKE wildcards in the search are matched literally
+    let response = list(admin_key.clone(), vec![search(" 100%")])
+        .await
+        .unwrap();
+    let page: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(names(&page), vec![" 100%"]);
+    let response = list(admin_key.clone(), vec![search(" A_C")]).await.unwrap();
+    let page: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(names(&page), vec![" a_c"]);
+
+    let response = list(
+        admin_key.clone(),
+        vec![search(""), ("is_active", "false".to_string())],
+    )
+    .await
+    .unwrap();
+    let page: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(names(&page), vec![" inactive"]);
+
+    let response = list(
+        admin_key.clone(),
+        vec![search(""), ("currency", "eur".to_string())],
+    )
+    .await
+    .unwrap();
+    let page: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(names(&page), vec![" a_c"]);
+
+    // Balance filters apply to the wallet of the named currency
+    let response = list(
+        admin_key.clone(),
+        vec![
+            search(""),
+            ("currency", "USD".to_string()),
+            ("min_balance", "400".to_string()),
+            ("max_balance", "1000".to_string()),
+        ],
+    )
+    .await
+    .unwrap();
+    let page: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(names(&page), vec![" plain"]);
+    assert_eq!(page["totals"][0]["total_balance"], 500);
+
+    let response = list(
+        admin_key.clone(),
+        vec![search(""), ("min_balance", "1".to_string())],
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), 400);
+
+    // Creation time bounds are inclusive; spread the accounts over known days first
+    let mut conn = db_connection();
+    for (index, (account_id, _)) in accounts.iter().enumerate() {
+        sql_query("UPDATE accounts SET created_at = '2001-01-01'::timestamp + $1 * INTERVAL '1 day' WHERE id = $2")
+            .bind::<BigInt, _>(index as i64)
+            .bind::<BigInt, _>(*account_id)
+            .execute(&mut conn)
+            .expect("Failed to backdate account");
+    }
+    let response = list(
+        admin_key.clone(),
+        vec![
+            search(""),
+            ("created_from", "2001-01-03T00:00:00Z".to_string()),
+            ("created_to", "2001-01-04T00:00:00Z".to_string()),
+        ],
+    )
+    .await
+    .unwrap();
+    let page: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(names(&page), vec![" inactive", " a_c"]);
+}
+
+#[tokio::test]
+async fn test_cancel_records_status_history() {
+    let client = reqwest::Client::new();
+    let (account_id, api_key) = create_test_account(&client).await;
+
+    let response = client
+        .get(format!("{}/api/accounts/{}/keys", BASE_URL, account_id))
+        .header("x-api-key", &api_key)
+        .send()
+        .await
+        .expect("Failed to list keys");
+    let keys: serde_json::Value = response.json().await.expect("Failed to parse response");
+    let key_id = keys[0]["id"].as_i64().expect("No key ID");
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &api_key)
+        .json(&json!({ "to_account_id": account_id, "amount": 1000, "tx_type": "credit" }))
+        .send()
+        .await
+        .expect("Failed to credit account");
+    let credit: serde_json::Value = response.json().await.expect("Failed to parse response");
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &api_key)
+        .json(&json!({
+            "from_account_id": account_id,
+            "amount": 300,
+            "tx_type": "debit",
+            "capture": false
+        }))
+        .send()
+        .await
+        .expect("Failed to authorize");
+    assert_eq!(response.status(), 200);
+    let authorization: serde_json::Value = response.json().await.expect("Failed to parse response");
+    let tx_id = authorization["id"].as_i64().unwrap();
+
+    let cancel = |id: i64| {
+        client
+            .post(format!("{}/api/transactions/{}/cancel", BASE_URL, id))
+            .header("x-api-key", &api_key)
+            .json(&json!({ "reason": "Customer changed their mind" }))
+            .send()
+    };
+
+    let response = cancel(tx_id).await.expect("Failed to cancel");
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(body["status"], "cancelled");
+    assert_eq!(
+        get_balance(&client, account_id, &api_key).await["available_balance"],
+        1000
+    );
+
+    // Neither a cancelled nor a completed transaction can be cancelled
+    for id in [tx_id, credit["id"].as_i64().unwrap()] {
+        let response = cancel(id).await.expect("Failed to cancel");
+        assert_eq!(response.status(), 409);
+        let error: serde_json::Value = response.json().await.expect("Failed to parse response");
+        assert_eq!(error["code"], "INVALID_STATUS_TRANSITION");
+    }
+
+    let response = client
+        .get(format!("{}/api/transactions/{}/history", BASE_URL, tx_id))
+        .header("x-api-key", &api_key)
+        .send()
+        .await
+        .expect("Failed to get history");
+    assert_eq!(response.status(), 200);
+    let history: serde_json::Value = response.json().await.expect("Failed to parse response");
+    let history = history.as_array().expect("History is not a list");
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["from_status"], serde_json::Value::Null);
+    assert_eq!(history[0]["to_status"], "pending");
+    assert_eq!(history[0]["actor_api_key_id"], key_id);
+    assert_eq!(history[1]["from_status"], "pending");
+    assert_eq!(history[1]["to_status"], "cancelled");
+    assert_eq!(history[1]["actor_api_key_id"], key_id);
+    assert_eq!(history[1]["reason"], "Customer changed their mind");
+}
+
+#[tokio::test]
+async fn test_reconciliation_reports_transactions_that_disagree_with_the_journal() {
+    let client = reqwest::Client::new();
+    let (account_id, api_key) = create_test_account(&client).await;
+
+    let mut tx_ids = Vec::new();
+    for amount in [100, 200, 300, 400] {
+        let response = client
+            .post(format!("{}/api/transactions", BASE_URL))
+            .header("x-api-key", &api_key)
+            .json(&json!({ "to_account_id": account_id, "amount": amount, "tx_type": "credit" }))
+            .send()
+            .await
+            .expect("Failed to credit account");
+        let tx: serde_json::Value = response.json().await.expect("Failed to parse response");
+        tx_ids.push(tx["id"].as_i64().unwrap());
+    }
+
+    let mut conn = db_connection();
+    let entry_postings =
+        "journal_entry_id IN (SELECT id FROM journal_entries WHERE transaction_id = $1)";
+    // The postings of the second credit no longer match its amount
+    sql_query(format!(
+        "UPDATE postings SET amount = amount * 2 WHERE {}",
+        entry_postings
+    ))
+    .bind::<BigInt, _>(tx_ids[1])
+    .execute(&mut conn)
+    .expect("Failed to change postings");
+    // The third credit loses its journal entry
+    sql_query(format!("DELETE FROM postings WHERE {}", entry_postings))
+        .bind::<BigInt, _>(tx_ids[2])
+        .execute(&mut conn)
+        .expect("Failed to delete postings");
+    sql_query("DELETE FROM journal_entries WHERE transaction_id = $1")
+        .bind::<BigInt, _>(tx_ids[2])
+        .execute(&mut conn)
+        .expect("Failed to delete journal entry");
+    // The fourth credit is marked failed although it was posted
+    sql_query("UPDATE transactions SET status = 'failed' WHERE id = $1")
+        .bind::<BigInt, _>(tx_ids[3])
+        .execute(&mut conn)
+        .expect("Failed to change status");
+
+    let response = client
+        .post(format!("{}/api/reconciliation", BASE_URL))
+        .header("x-api-key", &api_key)
+        .send()
+        .await
+        .expect("Failed to reconcile");
+    assert_eq!(response.status(), 403);
+
+    let response = client
+        .post(format!("{}/api/reconciliation", BASE_URL))
+        .header("x-api-key", admin_key())
+        .send()
+        .await
+        .expect("Failed to reconcile");
+    assert_eq!(response.status(), 200);
+    let report: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert!(report["transactions_checked"].as_u64().unwrap() >= 2);
+
+    let mismatch = |tx_id: i64| {
+        report["mismatched"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|mismatch| mismatch["transaction_id"] == tx_id)
+            .cloned()
+    };
+    assert!(mismatch(tx_ids[0]).is_none());
+
+    let changed = mismatch(tx_ids[1]).expect("Changed postings not reported");
+    assert_eq!(changed["problem"], "postings_mismatch");
+    assert_eq!(
+        changed["expected_postings"],
+        json!([
+            { "account_id": null, "currency": "USD", "amount": -200 },
+            { "account_id": account_id, "currency": "USD", "amount": 200 }
+        ])
+    );
+    assert_eq!(changed["postings"][1]["amount"], 400);
+
+    let missing = mismatch(tx_ids[2]).expect("Missing entry not reported");
+    assert_eq!(missing["problem"], "missing_entry");
+    assert_eq!(missing["postings"], json!([]));
+
+    let unexpected = mismatch(tx_ids[3]).expect("Unexpected entry not reported");
+    assert_eq!(unexpected["problem"], "unexpected_entry");
+    assert_eq!(unexpected["postings"][1]["amount"], 400);
+
+    // The wallet drifts by what the changed and missing postings no longer account for
+    let drift = report["drifted"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|drift| drift["account_id"] == account_id)
+        .expect("Wallet drift not reported");
+    assert_eq!(drift["stored_balance"], 1000);
+    assert_eq!(drift["ledger_balance"], 900);
+}
+
+#[tokio::test]
+async fn test_batch_legs_are_charged_fees() {
+    let client = reqwest::Client::new();
+    let (payer_id, payer_key) = create_test_account(&client).await;
+    let (payee_id, payee_key) = create_test_account(&client).await;
+    let (fee_account_id, fee_key) = create_test_account(&client).await;
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &payer_key)
+        .json(&json!({ "to_account_id": payer_id, "amount": 1000, "tx_type": "credit" }))
+        .send()
+        .await
+        .expect("Failed to credit account");
+    assert_eq!(response.status(), 200);
+
+    let response = client
+        .post(format!("{}/api/fee_schedules", BASE_URL))
+        .header("x-api-key", admin_key())
+        .json(&json!({
+            "account_id": payer_id,
+            "tx_type": "transfer",
+            "currency": "USD",
+            "fee_type": "flat",
+            "flat_amount": 100,
+            "fee_account_id": fee_account_id
+        }))
+        .send()
+        .await
+        .expect("Failed to create fee schedule");
+    assert_eq!(response.status(), 200);
+
+    let batch = |amounts: &[i64]| {
+        let legs: Vec<serde_json::Value> = amounts
+            .iter()
+            .map(|amount| {
+                json!({
+                    "tx_type": "transfer",
+                    "from_account_id": payer_id,
+                    "to_account_id": payee_id,
+                    "amount": amount
+                })
+            })
+            .collect();
+        client
+            .post(format!("{}/api/transactions/batch", BASE_URL))
+            .header("x-api-key", &payer_key)
+            .json(&json!({ "legs": legs }))
+            .send()
+    };
+
+    let response = batch(&[300, 300]).await.expect("Failed to create batch");
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    for leg in body["legs"].as_array().unwrap() {
+        assert_eq!(leg["transaction"]["fee_amount"], 100);
+    }
+    assert_eq!(
+        get_balance(&client, payer_id, &payer_key).await["balance"],
+        200
+    );
+    assert_eq!(
+        get_balance(&client, payee_id, &payee_key).await["balance"],
+        600
+    );
+    assert_eq!(
+        get_balance(&client, fee_account_id, &fee_key).await["balance"],
+        200
+    );
+
+    // The remaining 200 covers the amount of this leg but not its fee
+    let response = batch(&[150]).await.expect("Failed to create batch");
+    assert_eq!(response.status(), 409);
+    let error: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(error["code"], "BATCH_LEG_FAILED");
+    assert_eq!(error["details"]["leg_index"], 0);
+    assert_eq!(
+        get_balance(&client, payer_id, &payer_key).await["balance"],
+        200
+    );
+}
+
+#[tokio::test]
+async fn test_limits_apply_to_transactions_and_batches() {
+    let client = reqwest::Client::new();
+    let admin_key = admin_key();
+    let (account_id, api_key) = create_test_account(&client).await;
+    let (payee_id, _) = create_test_account(&client).await;
+
+    let response = client
+        .get(format!("{}/api/accounts/{}/keys", BASE_URL, account_id))
+        .header("x-api-key", &api_key)
+        .send()
+        .await
+        .expect("Failed to list keys");
+    let keys: serde_json::Value = response.json().await.expect("Failed to parse response");
+    let key_id = keys[0]["id"].as_i64().expect("No key ID");
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &api_key)
+        .json(&json!({ "to_account_id": account_id, "amount": 5000, "tx_type": "credit" }))
+        .send()
+        .await
+        .expect("Failed to credit account");
+    assert_eq!(response.status(), 200);
+
+    for (url, limits) in [
+        (
+            format!("{}/api/accounts/{}/limits", BASE_URL, account_id),
+            json!({ "currency": "USD", "max_daily_outflow": 1000 }),
+        ),
+        (
+            format!("{}/api/keys/{}/limits", BASE_URL, key_id),
+            json!({ "currency": "USD", "max_amount": 400 }),
+        ),
+    ] {
+        let response = client
+            .post(url)
+            .header("x-api-key", &admin_key)
+            .json(&limits)
+            .send()
+            .await
+            .expect("Failed to set limits");
+        assert_eq!(response.status(), 200);
+    }
+
+    let debit = |amount: i64| {
+        client
+            .post(format!("{}/api/transactions", BASE_URL))
+            .header("x-api-key", &api_key)
+            .json(&json!({ "from_account_id": account_id, "amount": amount, "tx_type": "debit" }))
+            .send()
+    };
+    let batch = |amounts: &[i64]| {
+        let legs: Vec<serde_json::Value> = amounts
+            .iter()
+            .map(|amount| {
+                json!({
+                    "tx_type": "transfer",
+                    "from_account_id": account_id,
+                    "to_account_id": payee_id,
+                    "amount": amount
+                })
+            })
+            .collect();
+        client
+            .post(format!("{}/api/transactions/batch", BASE_URL))
+            .header("x-api-key", &api_key)
+            .json(&json!({ "legs": legs }))
+            .send()
+    };
+
+    // The key's limit caps a single transaction
+    let response = debit(500).await.expect("Failed to debit");
+    assert_eq!(response.status(), 422);
+    let error: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(error["code"], "LIMIT_EXCEEDED");
+    assert_eq!(error["details"]["scope"], "api_key");
+    assert_eq!(error["details"]["scope_id"], key_id);
+    assert_eq!(error["details"]["limit"], "max_amount");
+    assert_eq!(error["details"]["remaining"], 400);
+
+    let response = debit(300).await.expect("Failed to debit");
+    assert_eq!(response.status(), 200);
+
+    // Each leg fits on its own, but together they break the account's daily outflow
+    let response = batch(&[400, 400]).await.expect("Failed to create batch");
+    assert_eq!(response.status(), 422);
+    let error: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(error["code"], "BATCH_LEG_FAILED");
+    assert_eq!(error["details"]["leg_index"], 1);
+    assert_eq!(error["details"]["leg_code"], "LIMIT_EXCEEDED");
+    assert_eq!(error["details"]["leg_details"]["scope"], "account");
+    assert_eq!(
+        error["details"]["leg_details"]["limit"],
+        "max_daily_outflow"
+    );
+    assert_eq!(error["details"]["leg_details"]["remaining"], 300);
+
+    let response = client
+        .get(format!("{}/api/accounts/{}/limits", BASE_URL, account_id))
+        .header("x-api-key", &api_key)
+        .send()
+        .await
+        .expect("Failed to get limits");
+    assert_eq!(response.status(), 200);
+    let limits: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(limits[0]["daily_outflow"], 300);
+    assert_eq!(limits[0]["daily_transactions"], 1);
+    assert_eq!(limits[0]["remaining_daily_outflow"], 700);
+
+    let response = batch(&[400, 300]).await.expect("Failed to create batch");
+    assert_eq!(response.status(), 200);
+
+    let response = client
+        .get(format!("{}/api/accounts/{}/limits", BASE_URL, account_id))
+        .header("x-api-key", &api_key)
+        .send()
+        .await
+        .expect("Failed to get limits");
+    let limits: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(limits[0]["daily_outflow"], 1000);
+    assert_eq!(limits[0]["daily_transactions"], 3);
+    assert_eq!(limits[0]["remaining_daily_outflow"], 0);
+
+    let response = client
+        .get(format!("{}/api/keys/{}/limits", BASE_URL, key_id))
+        .header("x-api-key", &api_key)
+        .send()
+        .await
+        .expect("Failed to get limits");
+    assert_eq!(response.status(), 200);
+    let limits: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(limits[0]["scope"], "api_key");
+    assert_eq!(limits[0]["max_amount"], 400);
+    assert_eq!(limits[0]["daily_outflow"], 1000);
+
+    let response = debit(1).await.expect("Failed to debit");
+    assert_eq!(response.status(), 422);
+    let error: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(error["details"]["limit"], "max_daily_outflow");
+    assert_eq!(error["details"]["remaining"], 0);
+    assert_eq!(
+        get_balance(&client, account_id, &api_key).await["balance"],
+        4000
+    );
+}
+
+#[tokio::test]
+async fn test_risk_review_flow() {
+    let client = reqwest::Client::new();
+    let admin_key = admin_key();
+    let (payer_id, payer_key) = create_test_account(&client).await;
+    let (payee_id, payee_key) = create_test_account(&client).await;
+
+    // Rules apply to every account, so they only match amounts no other test uses
+    let review_multiple = 9_973_331;
+    let block_multiple = 8_999_993;
+    let mut rule_ids = Vec::new();
+    for (name, multiple_of, outcome) in [
+        ("Review test amounts", review_multiple, "review"),
+        ("Block test amounts", block_multiple, "block"),
+    ] {
+        let response = client
+            .post(format!("{}/api/risk_rules", BASE_URL))
+            .header("x-api-key", &admin_key)
+            .json(&json!({
+                "name": name,
+                "currency": "USD",
+                "definition": { "type": "round_amount", "min_amount": 1, "multiple_of": multiple_of },
+                "outcome": outcome
+            }))
+            .send()
+            .await
+            .expect("Failed to create risk rule");
+        assert_eq!(response.status(), 200);
+        let rule: serde_json::Value = response.json().await.expect("Failed to parse response");
+        rule_ids.push(rule["id"].as_i64().unwrap());
+    }
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &payer_key)
+        .json(&json!({ "to_account_id": payer_id, "amount": 30_000_000, "tx_type": "credit" }))
+        .send()
+        .await
+        .expect("Failed to credit account");
+    assert_eq!(response.status(), 200);
+
+    let transfer = |amount: i64| {
+        client
+            .post(format!("{}/api/transactions", BASE_URL))
+            .header("x-api-key", &payer_key)
+            .json(&json!({
+                "from_account_id": payer_id,
+                "to_account_id": payee_id,
+                "amount": amount,
+                "tx_type": "transfer"
+            }))
+            .send()
+    };
+    let get_journal = |tx_id: i64| {
+        client
+            .get(format!("{}/api/transactions/{}/journal", BASE_URL, tx_id))
+            .header("x-api-key", &payer_key)
+            .send()
+    };
+    let find_review = |tx_id: i64| {
+        let client = &client;
+        let admin_key = &admin_key;
+        async move {
+            let response = client
+                .get(format!("{}/api/risk_reviews", BASE_URL))
+                .header("x-api-key", admin_key)
+                .send()
+                .await
+                .expect("Failed to list reviews");
+            assert_eq!(response.status(), 200);
+            let reviews: serde_json::Value =
+                response.json().await.expect("Failed to parse response");
+            reviews
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|review| review["transaction"]["id"] == tx_id)
+                .cloned()
+                .expect("No review for the transaction")
+        }
+    };
+
+    // A rule hit leaves the transaction pending with nothing posted or held
+    let response = transfer(review_multiple).await.expect("Failed to transfer");
+    assert_eq!(response.status(), 200);
+    let tx: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(tx["status"], "pending");
+    let approved_id = tx["id"].as_i64().unwrap();
+
+    let journal: serde_json::Value = get_journal(approved_id)
+        .await
+        .expect("Failed to get journal")
+        .json()
+        .await
+        .expect("Failed to parse response");
+    assert_eq!(journal, json!([]));
+    let balance = get_balance(&client, payer_id, &payer_key).await;
+    assert_eq!(balance["balance"], 30_000_000);
+    assert_eq!(balance["available_balance"], 30_000_000);
+
+    let review = find_review(approved_id).await;
+    assert_eq!(review["status"], "pending");
+    assert_eq!(review["matched_rules"][0]["id"], rule_ids[0]);
+
+    // Approving posts it
+    let response = client
+        .post(format!(
+            "{}/api/risk_reviews/{}/approve",
+            BASE_URL, review["id"]
+        ))
+        .header("x-api-key", &admin_key)
+        .json(&json!({ "reason": "Known customer" }))
+        .send()
+        .await
+        .expect("Failed to approve review");
+    assert_eq!(response.status(), 200);
+
+    let response = client
+        .get(format!("{}/api/transactions/{}", BASE_URL, approved_id))
+        .header("x-api-key", &payer_key)
+        .send()
+        .await
+        .expect("Failed to get transaction");
+    let tx: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(tx["status"], "completed");
+    let journal: serde_json::Value = get_journal(approved_id)
+        .await
+        .expect("Failed to get journal")
+        .json()
+        .await
+        .expect("Failed to parse response");
+    assert_eq!(journal.as_array().unwrap().len(), 1);
+    let remaining = 30_000_000 - review_multiple;
+    assert_eq!(
+        get_balance(&client, payer_id, &payer_key).await["balance"],
+        remaining
+    );
+    assert_eq!(
+        get_balance(&client, payee_id, &payee_key).await["balance"],
+        review_multiple
+    );
+
+    // Declining fails it and leaves the funds where they were
+    let response = transfer(2 * review_multiple)
+        .await
+        .expect("Failed to transfer");
+    assert_eq!(response.status(), 200);
+    let tx: serde_json::Value = response.json().await.expect("Failed to parse response");
+    let declined_id = tx["id"].as_i64().unwrap();
+    let review = find_review(declined_id).await;
+
+    let response = client
+        .post(format!(
+            "{}/api/risk_reviews/{}/decline",
+            BASE_URL, review["id"]
+        ))
+        .header("x-api-key", &admin_key)
+        .json(&json!({ "reason": "Unusual amount" }))
+        .send()
+        .await
+        .expect("Failed to decline review");
+    assert_eq!(response.status(), 200);
+
+    let response = client
+        .get(format!("{}/api/transactions/{}", BASE_URL, declined_id))
+        .header("x-api-key", &payer_key)
+        .send()
+        .await
+        .expect("Failed to get transaction");
+    let tx: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(tx["status"], "failed");
+    let balance = get_balance(&client, payer_id, &payer_key).await;
+    assert_eq!(balance["balance"], remaining);
+    assert_eq!(balance["available_balance"], remaining);
+
+    // A batch cannot hold a leg for review, and a blocked leg fails it as well
+    for (amount, leg_code) in [
+        (review_multiple, "RISK_REVIEW_REQUIRED"),
+        (block_multiple, "RISK_BLOCKED"),
+    ] {
+        let response = client
+            .post(format!("{}/api/transactions/batch", BASE_URL))
+            .header("x-api-key", &payer_key)
+            .json(&json!({
+                "legs": [
+                    { "tx_type": "transfer", "from_account_id": payer_id, "to_account_id": payee_id, "amount": 100 },
+                    { "tx_type": "transfer", "from_account_id": payer_id, "to_account_id": payee_id, "amount": amount }
+                ]
+            }))
+            .send()
+            .await
+            .expect("Failed to create batch");
+        assert_eq!(response.status(), 422);
+        let error: serde_json::Value = response.json().await.expect("Failed to parse response");
+        assert_eq!(error["code"], "BATCH_LEG_FAILED");
+        assert_eq!(error["details"]["leg_index"], 1);
+        assert_eq!(error["details"]["leg_code"], leg_code);
+    }
+    assert_eq!(
+        get_balance(&client, payer_id, &payer_key).await["balance"],
+        remaining
+    );
+
+    for rule_id in rule_ids {
+        let response = client
+            .delete(format!("{}/api/risk_rules/{}", BASE_URL, rule_id))
+            .header("x-api-key", &admin_key)
+            .send()
+            .await
+            .expect("Failed to delete risk rule");
+        assert!(response.status().is_success());
+    }
+}
+
+#[tokio::test]
+async fn test_outflow_freeze_blocks_money_leaving_the_account() {
+    let client = reqwest::Client::new();
+    let admin_key = admin_key();
+    let (account_id, api_key) = create_test_account(&client).await;
+    let (other_id, other_key) = create_test_account(&client).await;
+
+    let create = |key: &str, body: serde_json::Value| {
+        client
+            .post(format!("{}/api/transactions", BASE_URL))
+            .header("x-api-key", key)
+            .json(&body)
+            .send()
+    };
+    let credit = json!({ "to_account_id": account_id, "amount": 1000, "tx_type": "credit" });
+    let debit = json!({ "from_account_id": account_id, "amount": 100, "tx_type": "debit" });
+    let transfer_out = json!({
+        "from_account_id": account_id,
+        "to_account_id": other_id,
+        "amount": 100,
+        "tx_type": "transfer"
+    });
+    let transfer_in = json!({
+        "from_account_id": other_id,
+        "to_account_id": account_id,
+        "amount": 100,
+        "tx_type": "transfer"
+    });
+
+    let response = create(&api_key, credit.clone())
+        .await
+        .expect("Failed to credit");
+    assert_eq!(response.status(), 200);
+    let response = create(
+        &other_key,
+        json!({ "to_account_id": other_id, "amount": 1000, "tx_type": "credit" }),
+    )
+    .await
+    .expect("Failed to credit");
+    assert_eq!(response.status(), 200);
+
+    let response = client
+        .post(format!("{}/api/accounts/{}/freeze", BASE_URL, account_id))
+        .header("x-api-key", &admin_key)
+        .json(&json!({ "scope": "outflows", "reason": "KYC refresh" }))
+        .send()
+        .await
+        .expect("Failed to freeze account");
+    assert_eq!(response.status(), 200);
+
+    for body in [debit.clone(), transfer_out.clone()] {
+        let response = create(&api_key, body)
+            .await
+            .expect("Failed to send request");
+        assert_eq!(response.status(), 409);
+        let error: serde_json::Value = response.json().await.expect("Failed to parse response");
+        assert_eq!(error["code"], "ACCOUNT_FROZEN");
+        assert_eq!(error["details"]["account_id"], account_id);
+        assert_eq!(error["details"]["scope"], "outflows");
+    }
+
+    // Money can still come in
+    let response = create(&api_key, credit.clone())
+        .await
+        .expect("Failed to credit");
+    assert_eq!(response.status(), 200);
+    let response = create(&other_key, transfer_in)
+        .await
+        .expect("Failed to transfer");
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        get_balance(&client, account_id, &api_key).await["balance"],
+        2100
+    );
+
+    let response = client
+        .post(format!("{}/api/accounts/{}/unfreeze", BASE_URL, account_id))
+        .header("x-api-key", &admin_key)
+        .json(&json!({ "reason": "KYC complete" }))
+        .send()
+        .await
+        .expect("Failed to unfreeze account");
+    assert_eq!(response.status(), 200);
+
+    for body in [debit, transfer_out] {
+        let response = create(&api_key, body)
+            .await
+            .expect("Failed to send request");
+        assert_eq!(response.status(), 200);
+    }
+    assert_eq!(
+        get_balance(&client, account_id, &api_key).await["balance"],
+        1900
+    );
+}
+
+#[tokio::test]
+async fn test_compliance_hold_reduces_available_balance() {
+    let client = reqwest::Client::new();
+    let admin_key = admin_key();
+    let (account_id, api_key) = create_test_account(&client).await;
+
+    let response = client
+        .post(format!("{}/api/transactions", BASE_URL))
+        .header("x-api-key", &api_key)
+        .json(&json!({ "to_account_id": account_id, "amount": 1000, "tx_type": "credit" }))
+        .send()
+        .await
+        .expect("Failed to credit account");
+    assert_eq!(response.status(), 200);
+
+    let response = client
+        .post(format!("{}/api/accounts/{}/holds", BASE_URL, account_id))
+        .header("x-api-key", &admin_key)
+        .json(&json!({ "amount": 600, "currency": "USD", "reason": "Garnishment order" }))
+        .send()
+        .await
+        .expect("Failed to place hold");
+    assert_eq!(response.status(), 200);
+    let hold: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(hold["transaction_id"], serde_json::Value::Null);
+
+    let balance = get_balance(&client, account_id, &api_key).await;
+    assert_eq!(balance["balance"], 1000);
+    assert_eq!(balance["available_balance"], 400);
+
+    let debit = |amount: i64| {
+        client
+            .post(format!("{}/api/transactions", BASE_URL))
+            .header("x-api-key", &api_key)
+            .json(&json!({ "from_account_id": account_id, "amount": amount, "tx_type": "debit" }))
+            .send()
+    };
+
+    // The balance would cover it, but what is available does not
+    let response = debit(500).await.expect("Failed to debit");
+    assert_eq!(response.status(), 409);
+    let error: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(error["code"], "INSUFFICIENT_BALANCE");
+
+    let response = debit(400).await.expect("Failed to debit");
+    assert_eq!(response.status(), 200);
+    let balance = get_balance(&client, account_id, &api_key).await;
+    assert_eq!(balance["balance"], 600);
+    assert_eq!(balance["available_balance"], 0);
+
+    let response = client
+        .post(format!("{}/api/holds/{}/release", BASE_URL, hold["id"]))
+        .header("x-api-key", &admin_key)
+        .json(&json!({ "reason": "Order lifted" }))
+        .send()
+        .await
+        .expect("Failed to release hold");
+    assert_eq!(response.status(), 200);
+
+    let balance = get_balance(&client, account_id, &api_key).await;
+    assert_eq!(balance["balance"], 600);
+    assert_eq!(balance["available_balance"], 600);
+    let response = debit(500).await.expect("Failed to debit");
+    assert_eq!(response.status(), 200);
+}